@@ -8,7 +8,9 @@
 //! `eframe` for rendering and handles all GUI-specific logic.
 
 use eframe::egui;
-use ferris_scan::{Node, ScanReport, Scanner, SharedProgress};
+#[cfg(feature = "pro")]
+use ferris_scan::ExportError;
+use ferris_scan::{flatten_files, format_explain, format_size, format_size_as, format_snapshot, is_filesystem_root, percent_of, snapshot_file_name, resolve_auto_export, resolve_scan_path, sort_flat_files, summary_line, ExportFormat, FlatSort, FileCategory, AUTO_EXPORT_FLAG, DEFAULT_EXPLAIN_TOP_N, FLAT_VIEW_LIMIT, EXPORT_FORMAT_FLAG, LOAD_SCAN_FLAG, SUPPRESS_ROOT_WARNING_ENV, Node, ScanError, ScanReport, Scanner, SharedProgress, SizeFormat, SizeMode};
 use std::{
     env,
     path::PathBuf,
@@ -16,22 +18,35 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex,
     },
-    thread,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
 // ============================================================================
 // TYPES
 // ============================================================================
 
+/// Rings drawn by the sunburst chart below the current directory
+const SUNBURST_MAX_DEPTH: usize = 4;
+
+/// Share of the current directory an item needs to get its own sunburst
+/// slice; smaller siblings are grouped into one "other" slice
+const SUNBURST_MIN_FRACTION: f64 = 0.005;
+
 enum ScanStatus {
     Idle,
     Scanning {
         progress: Arc<SharedProgress>,
         done_flag: Arc<AtomicBool>,
+        cancel: Arc<AtomicBool>,
+        /// When the scan was started, for elapsed time and rates
+        started: Instant,
     },
     Done {
-        root: Node,
-        report: ScanReport,
+        root: Box<Node>,
+        report: Box<ScanReport>,
+        /// Outcome of the `--auto-export`, shown once when results appear
+        auto_export: Option<String>,
     },
     Error(String),
 }
@@ -42,12 +57,72 @@ struct NavigationState {
     path: Vec<Node>,
 }
 
+/// Every file under the scan root as a single sortable table
+struct FlatView {
+    /// Full path and size of each file, in `sort` order
+    files: Vec<(PathBuf, u64)>,
+    /// Files left out because of `FLAT_VIEW_LIMIT`
+    omitted: usize,
+    sort: FlatSort,
+}
+
+/// One slice of the sunburst chart
+struct SunburstSegment {
+    /// Ring, 1 for the current directory's children
+    depth: usize,
+    /// Start and end as fractions of a full turn, clockwise from the top
+    start: f64,
+    end: f64,
+    name: String,
+    /// `None` for a group of small siblings
+    path: Option<PathBuf>,
+    size: u64,
+    is_dir: bool,
+}
+
+/// What a click on the sunburst chart asks for
+enum SunburstClick {
+    /// The centre: go to the parent directory
+    Up,
+    /// A directory slice: open it
+    Open(PathBuf),
+}
+
 struct FerrisScanApp {
     scan_path: String,
     status: Arc<Mutex<ScanStatus>>,
     popup_message: Option<String>,
     navigation: Option<NavigationState>,
     selected_index: usize,
+    /// Current results come from a top-level-only quick scan
+    quick_scan: bool,
+    /// Directory of the quick results being deep scanned; opened once it
+    /// has been filled in
+    deep_scan_target: Option<PathBuf>,
+    /// Worker thread of the most recent scan
+    scan_thread: Option<JoinHandle<()>>,
+    /// Flat "all files" table, shown instead of the tree panes while set
+    flat_view: Option<FlatView>,
+    /// Show the current directory as a sunburst chart instead of the panes
+    show_sunburst: bool,
+    /// Size metric shown in the tree, details and stats panes
+    size_mode: SizeMode,
+    /// Format of the tree view's size column
+    tree_format: SizeFormat,
+    /// Format of sizes in the details and stats panes
+    detail_format: SizeFormat,
+    /// Full-disk scan awaiting confirmation; the value is its `quick` flag
+    pending_full_disk_scan: Option<bool>,
+    /// Skip the full-disk confirmation for the rest of the session
+    root_warning_dismissed: bool,
+    /// Case-insensitive name substring the tree pane is filtered by
+    name_filter: String,
+    /// Export every complete full scan here (`--auto-export`)
+    auto_export: Option<(PathBuf, ExportFormat)>,
+    /// Bytes in `size_mode` and file count per `FileCategory` for the whole
+    /// scan, largest first. Computed when the results appear and when the
+    /// size mode changes.
+    category_totals: Vec<(FileCategory, u64, u64)>,
 }
 
 // ============================================================================
@@ -80,6 +155,17 @@ impl NavigationState {
         self.path.push(child);
     }
 
+    /// Navigate to a directory anywhere below the current one, pushing every
+    /// directory in between. Returns false if `path` is not in the subtree.
+    fn drill_into(&mut self, path: &std::path::Path) -> bool {
+        let Some(chain) = self.current().ancestors_of(path) else {
+            return false;
+        };
+        let chain: Vec<Node> = chain.into_iter().skip(1).cloned().collect();
+        self.path.extend(chain);
+        true
+    }
+
     /// Navigate up to parent directory
     fn drill_up(&mut self) -> bool {
         if self.path.len() > 1 {
@@ -90,18 +176,51 @@ impl NavigationState {
     }
 }
 
+impl FlatView {
+    fn new(root: &Node, mode: SizeMode) -> Self {
+        let (files, omitted) = flatten_files(root, FLAT_VIEW_LIMIT, mode);
+        Self {
+            files,
+            omitted,
+            sort: FlatSort::Size,
+        }
+    }
+
+    fn set_sort(&mut self, sort: FlatSort) {
+        if self.sort != sort {
+            self.sort = sort;
+            sort_flat_files(&mut self.files, sort);
+        }
+    }
+}
+
 impl FerrisScanApp {
-    fn new(initial_path: PathBuf) -> Self {
+    fn new(initial_path: PathBuf, auto_export: Option<(PathBuf, ExportFormat)>) -> Self {
         Self {
             scan_path: initial_path.display().to_string(),
             status: Arc::new(Mutex::new(ScanStatus::Idle)),
             popup_message: None,
             navigation: None,
             selected_index: 0,
+            quick_scan: false,
+            deep_scan_target: None,
+            scan_thread: None,
+            flat_view: None,
+            show_sunburst: false,
+            size_mode: SizeMode::default(),
+            tree_format: SizeFormat::default(),
+            detail_format: SizeFormat::default(),
+            pending_full_disk_scan: None,
+            root_warning_dismissed: env::var_os(SUPPRESS_ROOT_WARNING_ENV).is_some(),
+            name_filter: String::new(),
+            auto_export,
+            category_totals: Vec::new(),
         }
     }
 
-    fn start_scan(&mut self) {
+    /// Start a background scan. A `quick` scan only materializes the root's
+    /// immediate children.
+    fn start_scan(&mut self, quick: bool) {
         let path = PathBuf::from(&self.scan_path);
         
         if !path.exists() {
@@ -109,32 +228,176 @@ impl FerrisScanApp {
             return;
         }
 
-        let progress = Arc::new(SharedProgress::default());
-        let done_flag = Arc::new(AtomicBool::new(false));
-
-        *self.status.lock().unwrap() = ScanStatus::Scanning {
-            progress: Arc::clone(&progress),
-            done_flag: Arc::clone(&done_flag),
-        };
+        if !self.root_warning_dismissed && is_filesystem_root(&path) {
+            self.pending_full_disk_scan = Some(quick);
+            return;
+        }
+        self.spawn_scan(path, quick);
+    }
 
+    fn spawn_scan(&mut self, path: PathBuf, quick: bool) {
+        self.join_scan_thread();
+        self.quick_scan = quick;
+        self.deep_scan_target = None;
+        let (progress_clone, done_flag_clone, cancel) = self.begin_scan();
         let status_clone = Arc::clone(&self.status);
-        let progress_clone = Arc::clone(&progress);
-        let done_flag_clone = Arc::clone(&done_flag);
+        let auto_export = self.auto_export.clone();
 
-        thread::spawn(move || {
-            let scanner = Scanner::new();
-            let result = scanner.scan_with_progress(&path, progress_clone);
+        self.scan_thread = Some(thread::spawn(move || {
+            let mut scanner = gui_scanner(cancel);
+            if let Some((export_path, format)) = auto_export {
+                scanner = scanner.with_auto_export(export_path, format);
+            }
+            let result = if quick {
+                scanner.scan_top_level(&path, Some(progress_clone))
+            } else {
+                scanner.scan_with_progress(&path, progress_clone)
+            };
             done_flag_clone.store(true, Ordering::Relaxed);
 
             let new_status = match result {
                 Ok((root, report)) => {
-                    ScanStatus::Done { root, report }
+                    // Quick and partial scans would overwrite a report with an incomplete tree
+                    let auto_export = if quick || report.cancelled || report.root_lost {
+                        None
+                    } else {
+                        match scanner.auto_export(&root) {
+                            Ok(written) => written.map(|p| format!("Auto-export saved!\n\nSaved to:\n{}", p.display())),
+                            Err(e) => Some(format!("Auto-export failed:\n{:#}", e)),
+                        }
+                    };
+                    ScanStatus::Done { root: Box::new(root), report: Box::new(report), auto_export }
                 }
                 Err(e) => ScanStatus::Error(e.to_string()),
             };
 
             *status_clone.lock().unwrap() = new_status;
-        });
+        }));
+    }
+
+    /// Scan `dir`, a directory of the quick results, in full and graft it
+    /// into them, so the overview stays one "Go Up" away. The directory is
+    /// walked as part of the quick scan's root (see
+    /// `Scanner::rescan_subtree`). Cancelling leaves the quick results as
+    /// they were.
+    fn spawn_deep_scan(&mut self, dir: PathBuf) {
+        let overview = match &*self.status.lock().unwrap() {
+            ScanStatus::Done { root, report, .. } => Some((root.clone(), report.clone())),
+            _ => None,
+        };
+        let Some((mut root, report)) = overview else {
+            return;
+        };
+        self.join_scan_thread();
+        self.navigation = None;
+        self.selected_index = 0;
+        self.deep_scan_target = Some(dir.clone());
+        let (progress, done_flag, cancel) = self.begin_scan();
+        let status_clone = Arc::clone(&self.status);
+
+        self.scan_thread = Some(thread::spawn(move || {
+            let result = gui_scanner(cancel).rescan_subtree_with_progress(&mut root, &dir, progress);
+            done_flag.store(true, Ordering::Relaxed);
+
+            let new_status = match result {
+                // The quick scan already walked everything, so its report
+                // still covers the whole tree
+                Ok(()) | Err(ScanError::Interrupted) => ScanStatus::Done { root, report, auto_export: None },
+                Err(e) => ScanStatus::Error(e.to_string()),
+            };
+            *status_clone.lock().unwrap() = new_status;
+        }));
+    }
+
+    /// Switch to the scanning view with fresh progress, done and cancel
+    /// flags, returned for the worker
+    fn begin_scan(&mut self) -> (Arc<SharedProgress>, Arc<AtomicBool>, Arc<AtomicBool>) {
+        self.flat_view = None;
+        self.name_filter.clear();
+        let progress = Arc::new(SharedProgress::default());
+        let done_flag = Arc::new(AtomicBool::new(false));
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        *self.status.lock().unwrap() = ScanStatus::Scanning {
+            progress: Arc::clone(&progress),
+            done_flag: Arc::clone(&done_flag),
+            cancel: Arc::clone(&cancel),
+            started: Instant::now(),
+        };
+        (progress, done_flag, cancel)
+    }
+
+    /// Show a scan saved with `Scanner::save_scan` as if it had just
+    /// finished. A missing or unreadable file leaves the app idle with an
+    /// error popup.
+    fn open_saved_scan(&mut self, path: &std::path::Path) {
+        match load_saved_scan(path) {
+            Ok((root, report)) => {
+                self.scan_path = root.path.display().to_string();
+                *self.status.lock().unwrap() = ScanStatus::Done {
+                    root: Box::new(root),
+                    report: Box::new(report),
+                    auto_export: None,
+                };
+            }
+            Err(e) => {
+                self.popup_message = Some(format!(
+                    "Could not load saved scan:\n{}\n\n{:#}",
+                    path.display(),
+                    e
+                ));
+            }
+        }
+    }
+
+    /// Wait for the scan worker to finish. Must not be called while holding
+    /// the `status` lock, since the worker takes it to publish its result.
+    fn join_scan_thread(&mut self) {
+        if let Some(handle) = self.scan_thread.take() {
+            let _ = handle.join();
+        }
+    }
+
+    /// Stop a running scan and discard its partial result. A deep scan
+    /// goes back to the quick results it started from.
+    fn cancel_scan(&mut self) {
+        if let ScanStatus::Scanning { cancel, .. } = &*self.status.lock().unwrap() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        self.join_scan_thread();
+        if self.deep_scan_target.take().is_some() {
+            return;
+        }
+        *self.status.lock().unwrap() = ScanStatus::Idle;
+        self.navigation = None;
+        self.selected_index = 0;
+        self.quick_scan = false;
+        self.flat_view = None;
+    }
+
+    fn handle_snapshot(&mut self) {
+        let Some(ref nav) = self.navigation else {
+            return;
+        };
+
+        let snapshot = format_snapshot(&nav.breadcrumb(), nav.current(), self.size_mode);
+        // The working directory, since the scanned tree may not be writable
+        let file_name = snapshot_file_name(nav.current());
+        let output_path = env::current_dir().map_or_else(|_| PathBuf::from(&file_name), |dir| dir.join(&file_name));
+        let replaced = output_path.exists();
+
+        match std::fs::write(&output_path, snapshot) {
+            Ok(()) => {
+                self.popup_message = Some(format!(
+                    "Snapshot saved!\n\nSaved to:\n{}{}",
+                    output_path.display(),
+                    if replaced { "\n\n(replaced an earlier snapshot)" } else { "" }
+                ));
+            }
+            Err(e) => {
+                self.popup_message = Some(format!("Snapshot failed:\n{}", e));
+            }
+        }
     }
 
     fn handle_export(&mut self, root: &Node) {
@@ -151,6 +414,9 @@ impl FerrisScanApp {
                         output_path.display()
                     ));
                 }
+                Err(e) if e.downcast_ref::<ExportError>() == Some(&ExportError::EmptyTree) => {
+                    self.popup_message = Some("Nothing to export.\n\nThe scanned directory is empty.".to_string());
+                }
                 Err(e) => {
                     self.popup_message = Some(format!("Export failed:\n{}", e));
                 }
@@ -175,10 +441,16 @@ impl eframe::App for FerrisScanApp {
         ctx.request_repaint();
 
         let mut should_start_scan = false;
+        let mut should_cancel = false;
+        let mut should_quick_scan = false;
+        let mut deep_scan_path: Option<PathBuf> = None;
         let mut should_export = false;
+        let mut should_snapshot = false;
+        let mut explain_message: Option<String> = None;
         let mut should_reset = false;
         let mut should_drill_up = false;
         let mut should_drill_down: Option<Node> = None;
+        let mut should_drill_into: Option<PathBuf> = None;
         let mut root_for_export: Option<Node> = None;
 
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -190,7 +462,7 @@ impl eframe::App for FerrisScanApp {
             #[cfg(not(feature = "pro"))]
             let version = format!("v{} [FREE]", env!("CARGO_PKG_VERSION"));
 
-            ui.label(version);
+            ui.label(format!("{} | sizes: {}", version, self.size_mode.label()));
             ui.add_space(10.0);
 
             ui.horizontal(|ui| {
@@ -203,15 +475,25 @@ impl eframe::App for FerrisScanApp {
             let status = self.status.lock().unwrap();
             match &*status {
                 ScanStatus::Idle => {
-                    if ui.button("Start Scan").clicked() {
-                        should_start_scan = true;
-                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Start Scan").clicked() {
+                            should_start_scan = true;
+                        }
+                        if ui.button("Quick Scan").clicked() {
+                            should_quick_scan = true;
+                        }
+                    });
                 }
                 ScanStatus::Scanning {
                     progress,
                     done_flag,
+                    started,
+                    ..
                 } => {
                     let files = progress.files_scanned.load(Ordering::Relaxed);
+                    let bytes = progress.bytes_scanned.load(Ordering::Relaxed);
+                    let elapsed = started.elapsed();
+                    let secs = elapsed.as_secs_f64().max(0.001);
                     let last_path = progress
                         .last_path
                         .lock()
@@ -219,20 +501,47 @@ impl eframe::App for FerrisScanApp {
                         .and_then(|g| g.as_ref().map(|p| p.display().to_string()))
                         .unwrap_or_else(|| "Starting...".to_string());
 
-                    ui.label(format!("⟳ Scanning in progress..."));
-                    ui.label(format!("Files scanned: {}", files));
+                    ui.label("⟳ Scanning in progress...");
+                    ui.label(format!(
+                        "Dirs: {} / Files: {}",
+                        progress.dirs_scanned.load(Ordering::Relaxed),
+                        files
+                    ));
+                    ui.label(format!("Scanned: {}", format_size(bytes)));
+                    ui.label(format!("Elapsed: {}", format_elapsed(elapsed)));
+                    ui.label(format!(
+                        "Rate: {:.0} files/s, {}/s",
+                        files as f64 / secs,
+                        format_size((bytes as f64 / secs) as u64)
+                    ));
+                    if let Some(eta) = progress.eta() {
+                        ui.label(format!("~{} remaining", format_elapsed(eta)));
+                    }
                     ui.add_space(5.0);
                     ui.label("Current path:");
                     ui.label(last_path);
+                    ui.add_space(10.0);
+
+                    if ui.button("Cancel").clicked() {
+                        should_cancel = true;
+                    }
 
                     if done_flag.load(Ordering::Relaxed) {
                         ctx.request_repaint();
                     }
                 }
-                ScanStatus::Done { root, report } => {
+                ScanStatus::Done { root, report, auto_export } => {
                     if self.navigation.is_none() {
-                        self.navigation = Some(NavigationState::new(root.clone()));
+                        let mut navigation = NavigationState::new((**root).clone());
+                        if let Some(target) = self.deep_scan_target.take() {
+                            navigation.drill_into(&target);
+                        }
+                        self.navigation = Some(navigation);
                         self.selected_index = 0;
+                        self.category_totals = sorted_category_totals(root, self.size_mode);
+                        if let Some(message) = auto_export {
+                            self.popup_message = Some(message.clone());
+                        }
                     }
 
                     let breadcrumb = self.navigation
@@ -248,12 +557,48 @@ impl eframe::App for FerrisScanApp {
                         ui.label("Location:");
                         ui.label(egui::RichText::new(&breadcrumb).color(egui::Color32::from_rgb(100, 200, 255)));
                         
-                        if can_go_up {
-                            if ui.button("← Go Up").clicked() {
-                                should_drill_up = true;
+                        if can_go_up && ui.button("← Go Up").clicked() {
+                            should_drill_up = true;
+                        }
+
+                        ui.separator();
+                        ui.label("Sizes:");
+                        let previous_mode = self.size_mode;
+                        ui.selectable_value(&mut self.size_mode, SizeMode::Apparent, "Apparent");
+                        ui.selectable_value(&mut self.size_mode, SizeMode::Allocated, "Allocated");
+                        if ui.input(|i| i.key_pressed(egui::Key::A)) && !ui.ctx().wants_keyboard_input() {
+                            self.size_mode = self.size_mode.toggled();
+                        }
+                        if self.size_mode != previous_mode {
+                            self.category_totals = sorted_category_totals(root, self.size_mode);
+                            if let Some(flat) = self.flat_view.as_mut() {
+                                let sort = flat.sort;
+                                *flat = FlatView::new(root, self.size_mode);
+                                flat.set_sort(sort);
                             }
                         }
+
+                        let mut flat = self.flat_view.is_some();
+                        if ui.checkbox(&mut flat, "Flat file list").changed() {
+                            self.flat_view = flat.then(|| FlatView::new(root, self.size_mode));
+                        }
+                        ui.checkbox(&mut self.show_sunburst, "Sunburst chart");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Tree units:");
+                        ui.selectable_value(&mut self.tree_format, SizeFormat::Auto, "Auto");
+                        ui.selectable_value(&mut self.tree_format, SizeFormat::Bytes, "Bytes");
+                        ui.separator();
+                        ui.label("Details units:");
+                        ui.selectable_value(&mut self.detail_format, SizeFormat::Auto, "Auto");
+                        ui.selectable_value(&mut self.detail_format, SizeFormat::Bytes, "Bytes");
                     });
+                    if self.quick_scan {
+                        ui.label(
+                            egui::RichText::new("Quick scan: top-level totals only. Click a folder to deep scan it.")
+                                .italics(),
+                        );
+                    }
                     ui.separator();
 
                     let current_node = self.navigation
@@ -265,111 +610,212 @@ impl eframe::App for FerrisScanApp {
                         self.selected_index = current_node.children.len() - 1;
                     }
 
-                    // Multi-pane layout: Tree | Details | Stats
-                    ui.horizontal(|ui| {
-                        // Tree pane (left)
-                        ui.vertical(|ui| {
-                            ui.heading("Tree View");
-                            ui.separator();
+                    if let Some(flat) = self.flat_view.as_mut() {
+                        render_flat_view(ui, flat);
+                    } else if self.show_sunburst {
+                        match render_sunburst(ui, current_node, self.size_mode) {
+                            Some(SunburstClick::Up) => should_drill_up = true,
+                            Some(SunburstClick::Open(path))
+                                if current_node.find(&path).is_some_and(|n| needs_deep_scan(self.quick_scan, n)) =>
+                            {
+                                deep_scan_path = Some(path)
+                            }
+                            Some(SunburstClick::Open(path)) => should_drill_into = Some(path),
+                            None => {}
+                        }
+                    } else {
+                        // Multi-pane layout: Tree | Details | Stats
+                        ui.horizontal(|ui| {
+                            // Tree pane (left)
+                            ui.vertical(|ui| {
+                                ui.heading("Tree View");
+                                ui.horizontal(|ui| {
+                                    ui.label("Filter:");
+                                    ui.text_edit_singleline(&mut self.name_filter);
+                                    if ui
+                                        .add_enabled(!self.name_filter.is_empty(), egui::Button::new("✖ Clear"))
+                                        .clicked()
+                                    {
+                                        self.name_filter.clear();
+                                    }
+                                });
+                                let filter = self.name_filter.to_lowercase();
+                                if !filter.is_empty() {
+                                    let shown = current_node
+                                        .children
+                                        .iter()
+                                        .filter(|c| name_matches(&c.name, &filter))
+                                        .count();
+                                    ui.label(
+                                        egui::RichText::new(format!("{} of {} shown", shown, current_node.children.len()))
+                                            .italics(),
+                                    );
+                                }
+                                ui.separator();
                             
-                            egui::ScrollArea::vertical()
-                                .max_height(400.0)
-                                .show(ui, |ui| {
-                                    for (idx, child) in current_node.children.iter().enumerate() {
-                                        let icon = if child.is_dir { "📁" } else { "📄" };
-                                        let is_selected = idx == self.selected_index;
+                                egui::ScrollArea::vertical()
+                                    .max_height(400.0)
+                                    .show(ui, |ui| {
+                                        for (idx, child) in current_node.children.iter().enumerate() {
+                                            if !name_matches(&child.name, &filter) {
+                                                continue;
+                                            }
+                                            let icon = if child.access_denied { "🔒" } else if child.is_dir { "📁" } else { "📄" };
+                                            let is_selected = idx == self.selected_index;
                                         
-                                        ui.horizontal(|ui| {
-                                            let label_text = format!("{} {}", icon, child.name);
+                                            ui.horizontal(|ui| {
+                                                let label_text = format!("{} {}", icon, child.name);
                                             
-                                            if is_selected {
-                                                ui.visuals_mut().selection.bg_fill = egui::Color32::from_rgb(255, 255, 0);
-                                            }
+                                                if is_selected {
+                                                    ui.visuals_mut().selection.bg_fill = egui::Color32::from_rgb(255, 255, 0);
+                                                }
                                             
-                                            let response = if child.is_dir {
-                                                ui.selectable_label(is_selected, label_text)
-                                            } else {
-                                                ui.selectable_label(is_selected, label_text)
-                                            };
+                                                let response = ui.selectable_label(is_selected, label_text);
                                             
-                                            if response.clicked() {
-                                                self.selected_index = idx;
-                                                if child.is_dir {
-                                                    should_drill_down = Some(child.clone());
+                                                if response.clicked() {
+                                                    self.selected_index = idx;
+                                                    if needs_deep_scan(self.quick_scan, child) {
+                                                        deep_scan_path = Some(child.path.clone());
+                                                    } else if child.is_dir {
+                                                        should_drill_down = Some(child.clone());
+                                                    }
                                                 }
-                                            }
                                             
-                                            ui.with_layout(
-                                                egui::Layout::right_to_left(egui::Align::Center),
-                                                |ui| {
-                                                    ui.label(format_size(child.size));
-                                                },
-                                            );
-                                        });
-                                    }
-                                });
-                        });
-
-                        ui.separator();
+                                                ui.with_layout(
+                                                    egui::Layout::right_to_left(egui::Align::Center),
+                                                    |ui| {
+                                                        ui.label(format_size_as(child.size_in(self.size_mode), self.tree_format));
+                                                    },
+                                                );
+                                            });
+                                        }
+                                    });
+                            });
 
-                        // Details pane (middle)
-                        ui.vertical(|ui| {
-                            ui.heading("Details");
                             ui.separator();
+
+                            // Details pane (middle)
+                            ui.vertical(|ui| {
+                                ui.heading("Details");
+                                ui.separator();
                             
-                            if let Some(selected_item) = current_node.children.get(self.selected_index) {
-                                ui.label(egui::RichText::new("Selected Item Details").heading().color(egui::Color32::from_rgb(100, 200, 255)));
-                                ui.add_space(5.0);
+                                if let Some(selected_item) = current_node.children.get(self.selected_index) {
+                                    ui.label(egui::RichText::new("Selected Item Details").heading().color(egui::Color32::from_rgb(100, 200, 255)));
+                                    ui.add_space(5.0);
                                 
-                                ui.label(format!("Name: {}", selected_item.name));
-                                ui.label(format!("Type: {}", if selected_item.is_dir { "Directory" } else { "File" }));
-                                ui.label(format!("Size: {}", format_size(selected_item.size)));
-                                ui.add_space(5.0);
+                                    ui.label(format!("Name: {}", selected_item.name));
+                                    ui.label(format!("Type: {}", if selected_item.is_dir { "Directory" } else { "File" }));
+                                    ui.label(format!("Size: {}", format_size_as(selected_item.size_in(self.size_mode), self.detail_format)));
+                                    let item_size = selected_item.size_in(self.size_mode);
+                                    share_bar(ui, item_size, current_node.size_in(self.size_mode), "of parent");
+                                    share_bar(ui, item_size, root.size_in(self.size_mode), "of total");
+                                    ui.add_space(5.0);
                                 
-                                ui.label(egui::RichText::new("Path:").strong());
-                                ui.label(egui::RichText::new(selected_item.path.display().to_string()).color(egui::Color32::from_rgb(255, 255, 0)));
+                                    ui.label(egui::RichText::new("Path:").strong());
+                                    ui.label(egui::RichText::new(selected_item.path.display().to_string()).color(egui::Color32::from_rgb(255, 255, 0)));
                                 
-                                if selected_item.is_dir {
+                                    if selected_item.is_dir {
+                                        ui.add_space(5.0);
+                                        if selected_item.access_denied {
+                                            ui.colored_label(egui::Color32::YELLOW, "🔒 Access denied: contents not counted");
+                                        }
+                                        ui.label(format!("Children: {} items", selected_item.children.len()));
+                                        if let Some((path, size)) = &selected_item.largest_file {
+                                            let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+                                            ui.label(format!("Biggest: {} ({})", name, format_size_as(*size, self.detail_format)));
+                                        }
+                                        let average = selected_item
+                                            .average_file_size(self.size_mode)
+                                            .map_or_else(|| "no files".to_string(), |avg| format_size_as(avg, self.detail_format));
+                                        ui.label(format!("Avg file size: {}", average));
+                                        let (direct, nested) = selected_item.direct_and_nested_size(self.size_mode);
+                                        ui.label(format!(
+                                            "Direct files: {} / Subdirectories: {}",
+                                            format_size_as(direct, self.detail_format),
+                                            format_size_as(nested, self.detail_format)
+                                        ));
+
+                                        if ui.button("Explain Size").clicked() {
+                                            explain_message = Some(format_explain(selected_item, DEFAULT_EXPLAIN_TOP_N, self.size_mode));
+                                        }
+                                    }
+                                } else {
+                                    ui.label(egui::RichText::new("No item selected").italics().color(egui::Color32::GRAY));
                                     ui.add_space(5.0);
-                                    ui.label(format!("Children: {} items", selected_item.children.len()));
+                                    ui.label("Click an item in the tree to view details.");
                                 }
-                            } else {
-                                ui.label(egui::RichText::new("No item selected").italics().color(egui::Color32::GRAY));
-                                ui.add_space(5.0);
-                                ui.label("Click an item in the tree to view details.");
-                            }
-                        });
+                            });
 
-                        ui.separator();
-
-                        // Stats pane (right)
-                        ui.vertical(|ui| {
-                            ui.heading("Progress & Stats");
                             ui.separator();
+
+                            // Stats pane (right)
+                            ui.vertical(|ui| {
+                                ui.heading("Progress & Stats");
+                                ui.separator();
                             
-                            ui.label(egui::RichText::new("Scan Statistics").heading().color(egui::Color32::from_rgb(100, 200, 255)));
-                            ui.add_space(5.0);
+                                ui.label(egui::RichText::new("Scan Statistics").heading().color(egui::Color32::from_rgb(100, 200, 255)));
+                                ui.add_space(5.0);
+                                ui.label(summary_line(root, report));
                             
-                            ui.label(format!("Total Size: {}", format_size(root.size)));
-                            ui.label(format!("Skipped: {} entries", report.skipped.len()));
+                                ui.label(format!("Total Size: {}", format_size_as(root.size_in(self.size_mode), self.detail_format)));
+                                ui.label(format!("Scan time: {:.2}s", report.elapsed.as_secs_f64()));
+                                ui.label(format!("Nodes: {}", root.total_nodes()));
+                                ui.label(format!("Skipped: {} entries", report.skipped.len()));
+                                if !report.excluded.is_empty() {
+                                    ui.label(format!("Excluded: {} directories", report.excluded.len()))
+                                        .on_hover_text(
+                                            report.excluded.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join("\n"),
+                                        );
+                                }
+                                if report.root_lost {
+                                    ui.label(
+                                        egui::RichText::new("⚠ Scan root disappeared, results are partial")
+                                            .color(egui::Color32::from_rgb(255, 100, 100)),
+                                    );
+                                }
                             
-                            ui.add_space(10.0);
+                                ui.add_space(10.0);
+
+                                egui::CollapsingHeader::new("File Types")
+                                    .default_open(true)
+                                    .show(ui, |ui| {
+                                        if self.category_totals.is_empty() {
+                                            ui.label("No files");
+                                        }
+                                        for (category, bytes, files) in &self.category_totals {
+                                            ui.label(format!(
+                                                "{}: {} in {} files",
+                                                category.label(),
+                                                format_size_as(*bytes, self.detail_format),
+                                                files
+                                            ));
+                                            share_bar(ui, *bytes, root.size_in(self.size_mode), "of total");
+                                        }
+                                    });
+
+                                ui.add_space(10.0);
                             
-                            ui.label(egui::RichText::new("Current Directory").heading().color(egui::Color32::from_rgb(100, 200, 255)));
-                            ui.add_space(5.0);
+                                ui.label(egui::RichText::new("Current Directory").heading().color(egui::Color32::from_rgb(100, 200, 255)));
+                                ui.add_space(5.0);
                             
-                            ui.label(format!("Name: {}", current_node.name));
-                            ui.label(format!("Size: {}", format_size(current_node.size)));
-                            ui.label(format!("Items: {}", current_node.children.len()));
+                                ui.label(format!("Name: {}", current_node.name));
+                                ui.label(format!("Size: {}", format_size_as(current_node.size_in(self.size_mode), self.detail_format)));
+                                ui.label(format!("Items: {}", current_node.children.len()));
+                                ui.label(format!("Files: {}", current_node.file_count));
+                            });
                         });
-                    });
+                    }
 
                     ui.add_space(10.0);
 
                     ui.horizontal(|ui| {
                         if ui.button("Export CSV").clicked() {
                             should_export = true;
-                            root_for_export = Some(root.clone());
+                            root_for_export = Some((**root).clone());
+                        }
+
+                        if ui.button("Save Snapshot").clicked() {
+                            should_snapshot = true;
                         }
 
                         if ui.button("New Scan").clicked() {
@@ -388,29 +834,90 @@ impl eframe::App for FerrisScanApp {
             }
         });
 
+        if should_cancel {
+            self.cancel_scan();
+        }
         if should_start_scan {
-            self.start_scan();
+            self.start_scan(false);
+        }
+        if should_quick_scan {
+            self.start_scan(true);
+        }
+        if let Some(path) = deep_scan_path {
+            self.spawn_deep_scan(path);
         }
         if should_export {
             if let Some(root) = root_for_export {
                 self.handle_export(&root);
             }
         }
+        if let Some(message) = explain_message {
+            self.popup_message = Some(message);
+        }
+        if should_snapshot {
+            self.handle_snapshot();
+        }
         if should_reset {
             *self.status.lock().unwrap() = ScanStatus::Idle;
             self.navigation = None;
             self.selected_index = 0;
+            self.quick_scan = false;
+            self.deep_scan_target = None;
+            self.flat_view = None;
         }
         if should_drill_up {
             if let Some(ref mut nav) = self.navigation {
                 nav.drill_up();
                 self.selected_index = 0;
+                self.name_filter.clear();
             }
         }
         if let Some(child) = should_drill_down {
             if let Some(ref mut nav) = self.navigation {
                 nav.drill_down(child);
                 self.selected_index = 0;
+                self.name_filter.clear();
+            }
+        }
+        if let Some(path) = should_drill_into {
+            if let Some(ref mut nav) = self.navigation {
+                if nav.drill_into(&path) {
+                    self.selected_index = 0;
+                    self.name_filter.clear();
+                }
+            }
+        }
+
+        if let Some(quick) = self.pending_full_disk_scan {
+            let mut confirmed = false;
+            let mut cancelled = false;
+            egui::Window::new("Full Disk Scan")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!("{} is the root of a disk.", self.scan_path));
+                    ui.label("Scanning an entire disk may take a while and require permissions.");
+                    ui.add_space(5.0);
+                    ui.checkbox(&mut self.root_warning_dismissed, "Don't ask again this session");
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Scan Anyway").clicked() {
+                            confirmed = true;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancelled = true;
+                        }
+                    });
+                });
+
+            if confirmed {
+                self.pending_full_disk_scan = None;
+                self.spawn_scan(PathBuf::from(&self.scan_path), quick);
+            } else if cancelled {
+                self.pending_full_disk_scan = None;
+                self.root_warning_dismissed = false;
             }
         }
 
@@ -442,11 +949,30 @@ impl eframe::App for FerrisScanApp {
 // ============================================================================
 
 fn main() -> eframe::Result<()> {
-    let args: Vec<String> = env::args().collect();
-    let initial_path = if args.len() > 1 {
-        PathBuf::from(&args[1])
-    } else {
-        env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+    let mut path_arg = None;
+    let mut export_path = None;
+    let mut export_format = None;
+    let mut load_path = None;
+    let mut args = env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == AUTO_EXPORT_FLAG {
+            export_path = args.next();
+        } else if arg == EXPORT_FORMAT_FLAG {
+            export_format = args.next();
+        } else if arg == LOAD_SCAN_FLAG {
+            load_path = args.next().map(PathBuf::from);
+        } else if path_arg.is_none() {
+            path_arg = Some(arg);
+        }
+    }
+    let (initial_path, auto_export) = match resolve_scan_path(path_arg)
+        .and_then(|path| Ok((path, resolve_auto_export(export_path, export_format)?)))
+    {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
     };
 
     let options = eframe::NativeOptions {
@@ -459,7 +985,13 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "ferris-scan",
         options,
-        Box::new(|_cc| Ok(Box::new(FerrisScanApp::new(initial_path)))),
+        Box::new(|_cc| {
+            let mut app = FerrisScanApp::new(initial_path, auto_export);
+            if let Some(path) = load_path {
+                app.open_saved_scan(&path);
+            }
+            Ok(Box::new(app))
+        }),
     )
 }
 
@@ -467,19 +999,251 @@ fn main() -> eframe::Result<()> {
 // UTILITIES
 // ============================================================================
 
-fn format_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size = bytes as f64;
-    let mut unit_idx = 0;
+/// Read the file given to `--load`
+#[cfg(feature = "pro")]
+fn load_saved_scan(path: &std::path::Path) -> anyhow::Result<(Node, ScanReport)> {
+    Scanner::load_scan(path)
+}
+
+#[cfg(not(feature = "pro"))]
+fn load_saved_scan(_path: &std::path::Path) -> anyhow::Result<(Node, ScanReport)> {
+    anyhow::bail!(
+        "{} is only available in ferris-scan Pro.\n\
+        Build with: cargo build --release --features pro",
+        LOAD_SCAN_FLAG
+    )
+}
+
+fn render_flat_view(ui: &mut egui::Ui, flat: &mut FlatView) {
+    let mut heading = format!("All Files ({})", flat.files.len());
+    if flat.omitted > 0 {
+        heading.push_str(&format!(" - {} smaller files not shown", flat.omitted));
+    }
+    ui.heading(heading);
+
+    ui.horizontal(|ui| {
+        ui.label("Sort by:");
+        if ui.selectable_label(flat.sort == FlatSort::Size, "Size").clicked() {
+            flat.set_sort(FlatSort::Size);
+        }
+        if ui.selectable_label(flat.sort == FlatSort::Path, "Path").clicked() {
+            flat.set_sort(FlatSort::Path);
+        }
+    });
+    ui.separator();
+
+    // Only the visible rows are laid out, so large lists stay responsive
+    let row_height = ui.text_style_height(&egui::TextStyle::Body);
+    egui::ScrollArea::vertical()
+        .max_height(400.0)
+        .auto_shrink([false, true])
+        .show_rows(ui, row_height, flat.files.len(), |ui, rows| {
+            for (path, size) in &flat.files[rows] {
+                ui.horizontal(|ui| {
+                    ui.label(path.display().to_string());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.label(format_size(*size));
+                    });
+                });
+            }
+        });
+}
+
+/// Slices of the sunburst chart for `node`, ring by ring down to
+/// `SUNBURST_MAX_DEPTH`. Each slice's angle is its share of its parent's
+/// size; items below `SUNBURST_MIN_FRACTION` of `node` are grouped per
+/// parent into one "other" slice.
+fn sunburst_segments(node: &Node, mode: SizeMode) -> Vec<SunburstSegment> {
+    let total = node.size_in(mode);
+    let mut segments = Vec::new();
+    if total == 0 {
+        return segments;
+    }
+
+    let mut stack = vec![(node, 0, 0.0, 1.0)];
+    while let Some((parent, depth, start, end)) = stack.pop() {
+        let parent_size = parent.size_in(mode);
+        if depth == SUNBURST_MAX_DEPTH || parent_size == 0 {
+            continue;
+        }
+        let span = end - start;
+        let mut cursor = start;
+        let (mut other_size, mut other_count) = (0u64, 0usize);
+        for child in &parent.children {
+            let size = child.size_in(mode);
+            if (size as f64) < total as f64 * SUNBURST_MIN_FRACTION {
+                other_size += size;
+                other_count += 1;
+                continue;
+            }
+            let width = span * size as f64 / parent_size as f64;
+            if child.is_dir {
+                stack.push((child, depth + 1, cursor, cursor + width));
+            }
+            segments.push(SunburstSegment {
+                depth: depth + 1,
+                start: cursor,
+                end: cursor + width,
+                name: child.name.clone(),
+                path: Some(child.path.clone()),
+                size,
+                is_dir: child.is_dir,
+            });
+            cursor += width;
+        }
+        if other_size > 0 {
+            segments.push(SunburstSegment {
+                depth: depth + 1,
+                start: cursor,
+                end: cursor + span * other_size as f64 / parent_size as f64,
+                name: format!("{} smaller items", other_count),
+                path: None,
+                size: other_size,
+                is_dir: false,
+            });
+        }
+    }
+    segments
+}
+
+/// Draw `node` as a sunburst chart: the centre is `node`, each ring one
+/// level further down. Hovering shows a slice's name and size; returns what
+/// was clicked, if anything.
+fn render_sunburst(ui: &mut egui::Ui, node: &Node, mode: SizeMode) -> Option<SunburstClick> {
+    use std::f32::consts::{FRAC_PI_2, TAU};
+
+    let segments = sunburst_segments(node, mode);
+    let side = ui.available_width().min(ui.available_height()).clamp(200.0, 520.0);
+    let (response, painter) = ui.allocate_painter(egui::vec2(side, side), egui::Sense::click());
+    let center = response.rect.center();
+    let radius = side / 2.0 - 4.0;
+    let hole = radius * 0.2;
+    let ring = (radius - hole) / SUNBURST_MAX_DEPTH as f32;
+    let total = node.size_in(mode);
+
+    painter.circle_filled(center, hole, egui::Color32::from_gray(60));
+    for segment in &segments {
+        let inner = hole + ring * (segment.depth - 1) as f32;
+        let outer = inner + ring - 1.0;
+        let color: egui::Color32 = if segment.path.is_none() {
+            egui::Color32::from_gray(90)
+        } else {
+            let hue = ((segment.start + segment.end) / 2.0) as f32;
+            let value = 0.95 - 0.12 * (segment.depth - 1) as f32;
+            egui::ecolor::Hsva::new(hue, if segment.is_dir { 0.6 } else { 0.35 }, value, 1.0).into()
+        };
+
+        // Annular sector as a strip of quads, finer for wider slices
+        let steps = ((segment.end - segment.start) * 128.0).ceil().max(1.0) as u32;
+        let mut mesh = egui::Mesh::default();
+        for i in 0..=steps {
+            let t = segment.start + (segment.end - segment.start) * f64::from(i) / f64::from(steps);
+            let angle = t as f32 * TAU - FRAC_PI_2;
+            let dir = egui::vec2(angle.cos(), angle.sin());
+            mesh.colored_vertex(center + dir * inner, color);
+            mesh.colored_vertex(center + dir * outer, color);
+            if i > 0 {
+                let base = 2 * i;
+                mesh.add_triangle(base - 2, base - 1, base);
+                mesh.add_triangle(base - 1, base + 1, base);
+            }
+        }
+        painter.add(egui::Shape::mesh(mesh));
+    }
+    if segments.is_empty() {
+        painter.text(center, egui::Align2::CENTER_CENTER, "Nothing to chart", egui::FontId::default(), egui::Color32::GRAY);
+    }
+
+    // Map the pointer back to a ring and an angle
+    let hovered = response.hover_pos().and_then(|pos| {
+        let offset = pos - center;
+        let distance = offset.length();
+        if distance < hole {
+            return Some(None);
+        }
+        let depth = ((distance - hole) / ring) as usize + 1;
+        let turn = ((offset.y.atan2(offset.x) + FRAC_PI_2) / TAU).rem_euclid(1.0) as f64;
+        segments
+            .iter()
+            .find(|s| s.depth == depth && s.start <= turn && turn < s.end)
+            .map(Some)
+    });
 
-    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_idx += 1;
+    let clicked = response.clicked();
+    match hovered {
+        Some(None) => {
+            response.on_hover_text_at_pointer(format!("{} (click to go up)", node.name));
+            clicked.then_some(SunburstClick::Up)
+        }
+        Some(Some(segment)) => {
+            let mut text = format!(
+                "{}\n{} ({:.1}%)",
+                segment.name,
+                format_size(segment.size),
+                percent_of(segment.size, total)
+            );
+            if segment.is_dir {
+                text.push_str("\nClick to open");
+            }
+            response.on_hover_text_at_pointer(text);
+            match &segment.path {
+                Some(path) if clicked && segment.is_dir => Some(SunburstClick::Open(path.clone())),
+                _ => None,
+            }
+        }
+        None => None,
     }
+}
+
+/// The scanner behind every GUI scan, stopped by `cancel`. Deep scans
+/// reuse it so they walk a directory as the quick scan did.
+fn gui_scanner(cancel: Arc<AtomicBool>) -> Scanner {
+    Scanner::new()
+        .with_largest_file(true)
+        .with_estimate_total(true)
+        .with_cancel_token(cancel)
+}
+
+/// Whether opening `node` needs a deep scan first: a directory of quick
+/// results that has not been filled in yet
+fn needs_deep_scan(quick_scan: bool, node: &Node) -> bool {
+    quick_scan && node.is_dir && node.children.is_empty() && node.file_count > 0
+}
+
+/// Whether `name` contains `filter_lower` (already lowercased), ignoring case.
+/// An empty filter matches everything.
+fn name_matches(name: &str, filter_lower: &str) -> bool {
+    filter_lower.is_empty() || name.to_lowercase().contains(filter_lower)
+}
 
-    if unit_idx == 0 {
-        format!("{} {}", bytes, UNITS[unit_idx])
+/// Elapsed scan time, e.g. `42.3s` or `3m 07s`
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        format!("{:.1}s", elapsed.as_secs_f64())
     } else {
-        format!("{:.2} {}", size, UNITS[unit_idx])
+        format!("{}m {:02}s", secs / 60, secs % 60)
     }
 }
+
+/// `Node::size_by_category_in` as a list, largest first
+fn sorted_category_totals(root: &Node, mode: SizeMode) -> Vec<(FileCategory, u64, u64)> {
+    let mut totals: Vec<_> = root
+        .size_by_category_in(mode)
+        .into_iter()
+        .map(|(category, (bytes, files))| (category, bytes, files))
+        .collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    totals
+}
+
+/// Progress-style bar showing `part` as a share of `total`, labelled e.g.
+/// "42.0% of parent". Empty when `total` is zero.
+fn share_bar(ui: &mut egui::Ui, part: u64, total: u64, label: &str) {
+    let percent = percent_of(part, total);
+    ui.add(
+        egui::ProgressBar::new((percent / 100.0) as f32)
+            .desired_width(160.0)
+            .text(format!("{:.1}% {}", percent, label)),
+    );
+}
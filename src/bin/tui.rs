@@ -13,34 +13,71 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ferris_scan::{Node, Scanner, ScanReport, SharedProgress};
+#[cfg(feature = "pro")]
+use ferris_scan::ExportError;
+use ferris_scan::{flatten_files, format_explain, format_size, format_size_as, format_snapshot, is_filesystem_root, snapshot_file_name, percent_of, resolve_auto_export, AfterDelete, ExportFormat, AUTO_EXPORT_FLAG, DEFAULT_DOMINANT_FRACTION, DEFAULT_EXPLAIN_TOP_N, FLAT_VIEW_LIMIT, EXPORT_FORMAT_FLAG, LOAD_SCAN_FLAG, resolve_scan_path, sort_flat_files, summary_line, SUPPRESS_ROOT_WARNING_ENV, FlatSort, Node, Scanner, ScanReport, SharedProgress, SizeFormat, SizeMode, SkippedEntry, SortKey};
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Sparkline, Wrap},
     Frame, Terminal,
 };
 use std::{
+    collections::VecDeque,
     env,
     io,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        mpsc, Arc,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant, SystemTime},
 };
 
 // ============================================================================
 // TYPES
 // ============================================================================
 
+/// Number of files/sec samples kept for the scanning sparkline
+const RATE_HISTORY_LEN: usize = 120;
+
+/// Minimum time between files/sec samples
+const RATE_SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Widest a popup or modal may get, in columns, on very wide terminals
+const MODAL_MAX_WIDTH: u16 = 72;
+
+/// Number of files listed in the largest-files modal (`t`)
+const TOP_FILES_COUNT: usize = 20;
+
+/// Ages, in days, the stale-files modal (`o`) totals files by
+const STALE_AGE_DAYS: [u64; 3] = [30, 90, 365];
+
+/// Number of oldest files listed in the stale-files modal
+const OLDEST_FILES_COUNT: usize = 10;
+
+/// Most matches listed by the whole-tree search (`F`); the rest are counted
+const GLOBAL_SEARCH_LIMIT: usize = 500;
+
+/// Smallest terminal (columns, rows) the full layout is drawn in
+const DEFAULT_MIN_TERMINAL_SIZE: (u16, u16) = (80, 24);
+
+/// Environment variable overriding the minimum terminal size, e.g. "100x30"
+const MIN_TERMINAL_SIZE_ENV: &str = "FERRIS_SCAN_MIN_TERMINAL_SIZE";
+
+/// Print `key=value` totals to stdout after the TUI exits, for wrapping scripts
+const SUMMARY_ON_EXIT_FLAG: &str = "--summary-on-exit";
+
+/// Environment variable setting the share (0-1] a child needs to be followed
+/// automatically when "follow big" is on, e.g. "0.9"
+const DOMINANT_FRACTION_ENV: &str = "FERRIS_SCAN_DOMINANT_FRACTION";
+
 enum AppState {
     Scanning,
-    ViewingResults(Node, ScanReport),
+    ViewingResults(Box<Node>, Box<ScanReport>),
 }
 
 /// Navigation state for tree browsing
@@ -49,6 +86,79 @@ struct NavigationState {
     path: Vec<Node>,
     /// Currently selected item index in the list
     selected: usize,
+    /// Depth the user opened before `drill_down_following` descended further
+    /// on its own; `back` returns straight there
+    auto_from: Option<usize>,
+}
+
+/// How sizes are shown in the results panes
+#[derive(Debug, Clone, Copy, Default)]
+struct SizeDisplay {
+    /// Apparent or allocated size
+    mode: SizeMode,
+    /// Format of the tree view's size column
+    tree_format: SizeFormat,
+    /// Format of sizes in the details and stats panes
+    detail_format: SizeFormat,
+}
+
+/// Order of the tree view, changed with `s` (key) and `S` (direction)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct TreeSort {
+    key: SortKey,
+    ascending: bool,
+}
+
+impl TreeSort {
+    /// The next key in the `s` cycle, in its natural direction: names A-Z,
+    /// sizes and counts largest first
+    fn next_key(self) -> Self {
+        let key = match self.key {
+            SortKey::Size | SortKey::AllocatedSize => SortKey::Name,
+            SortKey::Name => SortKey::FileCount,
+            SortKey::FileCount => SortKey::Size,
+        };
+        Self { key, ascending: key == SortKey::Name }
+    }
+
+    fn reversed(self) -> Self {
+        Self { ascending: !self.ascending, ..self }
+    }
+
+    fn label(self) -> String {
+        format!("{} {}", self.key.label(), if self.ascending { "↑" } else { "↓" })
+    }
+}
+
+/// Every file under the scan root as a single sortable list
+struct FlatView {
+    /// Full path and size of each file, in `sort` order
+    files: Vec<(PathBuf, u64)>,
+    /// Files left out because of `FLAT_VIEW_LIMIT`
+    omitted: usize,
+    sort: FlatSort,
+    list_state: ListState,
+}
+
+/// Contents of the stale-files modal (`o`)
+struct StaleSummary {
+    /// (age in days, bytes, files) for each of `STALE_AGE_DAYS`
+    buckets: Vec<(u64, u64, u64)>,
+    /// Oldest files under the root with their age in days, oldest first
+    oldest: Vec<(PathBuf, u64)>,
+}
+
+/// Files anywhere under the root whose name matches a query, shown instead
+/// of the tree while set
+struct GlobalSearch {
+    query: String,
+    /// Full path and size of the first `GLOBAL_SEARCH_LIMIT` matches, in tree order
+    results: Vec<(PathBuf, u64)>,
+    /// Number of matches before the cap
+    total: usize,
+    /// Directories that could not be listed, so their contents were not searched
+    unreadable: usize,
+    list_state: ListState,
 }
 
 struct App {
@@ -60,7 +170,63 @@ struct App {
     navigation: Option<NavigationState>,
     list_state: ListState,
     show_delete_modal: bool,
-    pending_deletion: Option<PathBuf>,
+    /// Childless copy of the node awaiting confirmation, carrying its totals
+    pending_deletion: Option<Node>,
+    /// Ring buffer of recent files/sec samples, oldest first
+    rate_history: VecDeque<u64>,
+    /// Time and file count of the previous rate sample
+    last_rate_sample: (Instant, u64),
+    /// Flat "all files" view, shown instead of the tree while set
+    flat_view: Option<FlatView>,
+    /// Largest files anywhere under the root (full path, size), shown in a
+    /// modal while set
+    top_files: Option<Vec<(PathBuf, u64)>>,
+    /// Bytes and file count per extension, largest first, shown in a modal
+    /// while set
+    extension_totals: Option<Vec<(String, u64, u64)>>,
+    /// Sizes of files not modified for a while, shown in a modal while set
+    stale_summary: Option<StaleSummary>,
+    /// Size metric and formats used in the tree, details and stats panes
+    size_display: SizeDisplay,
+    /// Order of the tree view's children
+    tree_sort: TreeSort,
+    /// Whole-tree file search (`F`)
+    global_search: Option<GlobalSearch>,
+    /// Name filter being typed after `/`; the tree pane lists only the
+    /// current directory's children that match it
+    search_query: Option<String>,
+    /// Worker thread of the running scan, taken once its result is collected
+    scan_handle: Option<thread::JoinHandle<Result<(Node, ScanReport)>>>,
+    /// Set by the worker when the scan has finished
+    scan_done: Arc<AtomicBool>,
+    /// Cancel token of the running scan; `q` sets it to stop early and show
+    /// the partial results
+    scan_cancel: Arc<AtomicBool>,
+    /// Pause token of the running scan, toggled with the space bar
+    scan_pause: Arc<AtomicBool>,
+    /// Entries the running scan skipped, sent as they are found
+    skipped_rx: Option<mpsc::Receiver<SkippedEntry>>,
+    /// Entries received on `skipped_rx` so far, shown while scanning
+    skipped_live: usize,
+    /// Waiting for the user to confirm a full-disk scan before starting
+    confirm_full_disk: bool,
+    /// How the tree is refreshed after a deletion
+    after_delete: AfterDelete,
+    /// Settings of the scan behind the results, reused when part of the
+    /// tree is rescanned
+    scanner: Scanner,
+    /// Below this many (columns, rows) only a "too small" notice is drawn
+    min_terminal_size: (u16, u16),
+    /// Set while drawing when the terminal is below `min_terminal_size`
+    terminal_too_small: bool,
+    /// Opening a directory also follows dominant children ("follow big")
+    follow_dominant: bool,
+    /// Share a child needs to be followed
+    dominant_fraction: f64,
+    /// Export every completed scan here (`--auto-export`)
+    auto_export: Option<(PathBuf, ExportFormat)>,
+    /// Why the most recent auto-export failed, repeated on stderr at exit
+    auto_export_error: Option<String>,
 }
 
 // ============================================================================
@@ -72,6 +238,7 @@ impl NavigationState {
         Self {
             path: vec![root],
             selected: 0,
+            auto_from: None,
         }
     }
 
@@ -102,6 +269,38 @@ impl NavigationState {
         false
     }
 
+    /// Navigate into a child directory and, with `follow` set, keep going
+    /// while one child directory holds at least that share of the size
+    fn drill_down_following(&mut self, index: usize, follow: Option<f64>) -> bool {
+        if !self.drill_down(index) {
+            return false;
+        }
+        self.auto_from = None;
+        if let Some(fraction) = follow {
+            let opened = self.path.len();
+            while let Some(next) = self.current().dominant_child(fraction) {
+                self.drill_down(next);
+            }
+            if self.path.len() > opened {
+                self.auto_from = Some(opened);
+            }
+        }
+        true
+    }
+
+    /// Go back one step: to the directory the user opened if the last drill
+    /// descended automatically, otherwise to the parent
+    fn back(&mut self) -> bool {
+        if let Some(depth) = self.auto_from.take() {
+            if depth < self.path.len() {
+                self.path.truncate(depth);
+                self.selected = 0;
+                return true;
+            }
+        }
+        self.drill_up()
+    }
+
     /// Navigate up to parent directory
     fn drill_up(&mut self) -> bool {
         if self.path.len() > 1 {
@@ -112,52 +311,127 @@ impl NavigationState {
         false
     }
 
+    /// Open the directory holding `target` and select it. Returns false if
+    /// `target` is not in `root`, leaving the navigation unchanged.
+    fn reveal(&mut self, root: &Node, target: &Path) -> bool {
+        let Some(parent) = target.parent() else {
+            return false;
+        };
+        let Some(chain) = root.ancestors_of(parent) else {
+            return false;
+        };
+        let Some(index) = chain.last().and_then(|dir| dir.children.iter().position(|c| c.path == target)) else {
+            return false;
+        };
+        self.path = chain.into_iter().cloned().collect();
+        self.selected = index;
+        self.auto_from = None;
+        true
+    }
+
+    /// Rebuild the path stack against a fresh root, keeping the current
+    /// directory and the selected child (matched by path) where they still exist.
     fn rebuild_from_root(&mut self, root: &Node) {
-        if self.path.is_empty() {
-            self.path = vec![root.clone()];
-            self.selected = 0;
+        let selected_path = self
+            .path
+            .last()
+            .and_then(|n| n.children.get(self.selected))
+            .map(|c| c.path.clone());
+        let previous_selected = self.selected;
+        let target_path = self.path.last().map(|n| n.path.clone());
+
+        // Rebuild path from root, falling back to the root if the directory is gone
+        self.path = target_path
+            .and_then(|target| root.ancestors_of(&target))
+            .map(|chain| chain.into_iter().cloned().collect())
+            .unwrap_or_else(|| vec![root.clone()]);
+
+        let children = &self.current().children;
+        self.selected = selected_path
+            .and_then(|p| children.iter().position(|c| c.path == p))
+            .unwrap_or_else(|| previous_selected.min(children.len().saturating_sub(1)));
+    }
+}
+
+impl GlobalSearch {
+    fn new(root: &Node) -> Self {
+        Self {
+            query: String::new(),
+            results: Vec::new(),
+            total: 0,
+            unreadable: root.iter().filter(|n| n.access_denied).count(),
+            list_state: ListState::default(),
+        }
+    }
+
+    /// Re-run the query against `root`, starting again at the top
+    fn update(&mut self, root: &Node) {
+        (self.results, self.total) = search_tree(root, &self.query, GLOBAL_SEARCH_LIMIT);
+        self.list_state.select((!self.results.is_empty()).then_some(0));
+    }
+
+    fn selected_path(&self) -> Option<&Path> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.results.get(i))
+            .map(|(path, _)| path.as_path())
+    }
+
+    fn select_previous(&mut self) {
+        if self.results.is_empty() {
             return;
         }
+        let selected = self.list_state.selected().unwrap_or(0);
+        let new_selected = if selected > 0 { selected - 1 } else { self.results.len() - 1 };
+        self.list_state.select(Some(new_selected));
+    }
 
+    fn select_next(&mut self) {
+        if self.results.is_empty() {
+            return;
+        }
+        let selected = self.list_state.selected().unwrap_or(0);
+        let new_selected = if selected < self.results.len() - 1 { selected + 1 } else { 0 };
+        self.list_state.select(Some(new_selected));
+    }
+}
 
-        let target_path = self.path.last().map(|n| n.path.clone());
-        
-        // Rebuild path from root
-        self.path.clear();
-        self.path.push(root.clone());
-        
-        if let Some(ref target) = target_path {
-            if target == &root.path {
-                self.selected = 0;
-                return;
-            }
-            
-            if let Ok(relative) = target.strip_prefix(&root.path) {
-                let mut current = root;
-                let mut found = true;
-                
-                // Navigate through each component in the relative path
-                for component in relative.components() {
-                    let name = component.as_os_str().to_string_lossy();
-                    if let Some(child) = current.children.iter().find(|c| c.name == name) {
-                        self.path.push(child.clone());
-                        current = child;
-                    } else {
-                        found = false;
-                        break;
-                    }
-                }
-                
-                if !found {
-                    self.path = vec![root.clone()];
-                }
-            } else {
-                // Path doesn't start with root
-                self.path = vec![root.clone()];
-            }
+impl FlatView {
+    fn new(root: &Node, mode: SizeMode) -> Self {
+        let (files, omitted) = flatten_files(root, FLAT_VIEW_LIMIT, mode);
+        let mut list_state = ListState::default();
+        list_state.select((!files.is_empty()).then_some(0));
+        Self {
+            files,
+            omitted,
+            sort: FlatSort::Size,
+            list_state,
         }
-        
-        self.selected = 0;
+    }
+
+    /// Switch between size and path order, starting again at the top
+    fn toggle_sort(&mut self) {
+        self.sort = self.sort.toggled();
+        sort_flat_files(&mut self.files, self.sort);
+        self.list_state.select((!self.files.is_empty()).then_some(0));
+    }
+
+    fn select_previous(&mut self) {
+        if self.files.is_empty() {
+            return;
+        }
+        let selected = self.list_state.selected().unwrap_or(0);
+        let new_selected = if selected > 0 { selected - 1 } else { self.files.len() - 1 };
+        self.list_state.select(Some(new_selected));
+    }
+
+    fn select_next(&mut self) {
+        if self.files.is_empty() {
+            return;
+        }
+        let selected = self.list_state.selected().unwrap_or(0);
+        let new_selected = if selected < self.files.len() - 1 { selected + 1 } else { 0 };
+        self.list_state.select(Some(new_selected));
     }
 }
 
@@ -173,6 +447,272 @@ impl App {
             list_state: ListState::default(),
             show_delete_modal: false,
             pending_deletion: None,
+            rate_history: VecDeque::with_capacity(RATE_HISTORY_LEN),
+            last_rate_sample: (Instant::now(), 0),
+            flat_view: None,
+            top_files: None,
+            extension_totals: None,
+            stale_summary: None,
+            size_display: SizeDisplay::default(),
+            tree_sort: TreeSort::default(),
+            search_query: None,
+            global_search: None,
+            scan_handle: None,
+            scan_done: Arc::new(AtomicBool::new(false)),
+            scan_cancel: Arc::new(AtomicBool::new(false)),
+            scan_pause: Arc::new(AtomicBool::new(false)),
+            skipped_rx: None,
+            skipped_live: 0,
+            confirm_full_disk: false,
+            after_delete: AfterDelete::default(),
+            scanner: Scanner::new().with_largest_file(true),
+            min_terminal_size: DEFAULT_MIN_TERMINAL_SIZE,
+            terminal_too_small: false,
+            follow_dominant: false,
+            dominant_fraction: DEFAULT_DOMINANT_FRACTION,
+            auto_export: None,
+            auto_export_error: None,
+        }
+    }
+
+    /// Start (or restart) a scan of `scan_path` on a fresh worker thread,
+    /// discarding any previous results and navigation state.
+    fn start_scan(&mut self) {
+        self.state = AppState::Scanning;
+        self.navigation = None;
+        self.flat_view = None;
+        self.top_files = None;
+        self.extension_totals = None;
+        self.list_state = ListState::default();
+        self.shared_progress = Arc::new(SharedProgress::default());
+        self.rate_history.clear();
+        self.last_rate_sample = (Instant::now(), 0);
+        self.scan_done = Arc::new(AtomicBool::new(false));
+        self.scan_cancel = Arc::new(AtomicBool::new(false));
+        self.scan_pause = Arc::new(AtomicBool::new(false));
+        let (skipped_tx, skipped_rx) = mpsc::channel();
+        self.skipped_rx = Some(skipped_rx);
+        self.skipped_live = 0;
+
+        let scan_path = self.scan_path.clone();
+        let shared_progress = Arc::clone(&self.shared_progress);
+        let scan_done = Arc::clone(&self.scan_done);
+        let cancel = Arc::clone(&self.scan_cancel);
+        let pause = Arc::clone(&self.scan_pause);
+        self.scanner = Scanner::new().with_largest_file(true).with_estimate_total(true);
+        let scanner = self.scanner.clone();
+        self.scan_handle = Some(thread::spawn(move || {
+            let scanner = scanner
+                .with_cancel_token(cancel)
+                .with_pause_token(pause)
+                .with_skipped_sender(skipped_tx);
+            let result = scanner.scan_with_progress(&scan_path, shared_progress).map_err(anyhow::Error::from);
+            scan_done.store(true, Ordering::Relaxed);
+            result
+        }));
+    }
+
+    /// Collect the result of a finished scan and switch to the results view
+    fn poll_scan(&mut self) {
+        if !self.scan_done.load(Ordering::Relaxed) {
+            return;
+        }
+        let Some(handle) = self.scan_handle.take() else {
+            return;
+        };
+        match handle.join() {
+            Ok(Ok((root, report))) => {
+                self.run_auto_export(&root, &report);
+                self.show_results(root, report);
+            }
+            Ok(Err(e)) => {
+                self.show_popup(format!("Scan error: {}", e));
+            }
+            Err(_) => {
+                self.show_popup("Internal error: scan thread panicked".to_string());
+            }
+        }
+    }
+
+    /// Pause the running scan, or resume it if paused. Returns whether it is
+    /// now paused.
+    fn toggle_pause(&mut self) -> bool {
+        if !matches!(self.state, AppState::Scanning) || self.scan_handle.is_none() {
+            return false;
+        }
+        !self.scan_pause.fetch_xor(true, Ordering::Relaxed)
+    }
+
+    /// Ask the running scan to stop; `poll_scan` then shows what it found
+    /// so far. Returns false if there is no scan left to stop.
+    fn stop_scan(&mut self) -> bool {
+        if !matches!(self.state, AppState::Scanning)
+            || self.scan_handle.is_none()
+            || self.scan_cancel.swap(true, Ordering::Relaxed)
+        {
+            return false;
+        }
+        true
+    }
+
+    /// Switch to the results view for a finished (or loaded) scan
+    fn show_results(&mut self, root: Node, report: ScanReport) {
+        self.state = AppState::ViewingResults(Box::new(root.clone()), Box::new(report));
+        self.navigation = Some(NavigationState::new(root));
+        self.list_state.select(Some(0));
+        if self.tree_sort != TreeSort::default() {
+            self.apply_tree_sort();
+        }
+    }
+
+    /// Write the `--auto-export` file for a finished scan and report the
+    /// outcome in a popup. Partial results are not exported.
+    fn run_auto_export(&mut self, root: &Node, report: &ScanReport) {
+        let Some((path, format)) = self.auto_export.clone() else {
+            return;
+        };
+        if report.cancelled || report.root_lost {
+            let message = "Auto-export skipped: the scan did not complete".to_string();
+            self.show_popup(format!("⚠ {}", message));
+            self.auto_export_error = Some(message);
+            return;
+        }
+        match Scanner::new().with_auto_export(path, format).auto_export(root) {
+            Ok(written) => {
+                self.auto_export_error = None;
+                self.show_popup(format!(
+                    "✓ Auto-export saved!\n\nSaved to:\n{}",
+                    written.unwrap_or_default().display()
+                ));
+            }
+            Err(e) => {
+                self.show_popup(format!("✗ Auto-export failed:\n{:#}", e));
+                self.auto_export_error = Some(format!("{:#}", e));
+            }
+        }
+    }
+
+    fn toggle_flat_view(&mut self) {
+        if self.flat_view.take().is_some() {
+            return;
+        }
+        if let AppState::ViewingResults(ref root, _) = self.state {
+            self.flat_view = Some(FlatView::new(root, self.size_display.mode));
+        }
+    }
+
+    /// Count the skipped entries the scan has sent since the last call
+    fn drain_skipped(&mut self) {
+        if let Some(ref rx) = self.skipped_rx {
+            self.skipped_live += rx.try_iter().count();
+        }
+    }
+
+    /// Push a files/sec sample into the ring buffer if enough time has passed
+    fn sample_scan_rate(&mut self) {
+        let (last_time, last_files) = self.last_rate_sample;
+        let elapsed = last_time.elapsed();
+        if elapsed < RATE_SAMPLE_INTERVAL {
+            return;
+        }
+
+        let files = self.shared_progress.files_scanned.load(Ordering::Relaxed);
+        let rate = (files.saturating_sub(last_files) as f64 / elapsed.as_secs_f64()) as u64;
+
+        if self.rate_history.len() == RATE_HISTORY_LEN {
+            self.rate_history.pop_front();
+        }
+        self.rate_history.push_back(rate);
+        self.last_rate_sample = (Instant::now(), files);
+    }
+
+    /// The key `tree_sort` stands for; size follows the apparent/allocated toggle
+    fn tree_sort_key(&self) -> SortKey {
+        match (self.tree_sort.key, self.size_display.mode) {
+            (SortKey::Size, SizeMode::Allocated) => SortKey::AllocatedSize,
+            (key, _) => key,
+        }
+    }
+
+    /// Re-sort the whole tree by `tree_sort` and select the first row
+    fn apply_tree_sort(&mut self) {
+        let key = self.tree_sort_key();
+        if let AppState::ViewingResults(ref mut root, _) = self.state {
+            root.sort_by(key, self.tree_sort.ascending);
+            if let Some(ref mut nav) = self.navigation {
+                nav.rebuild_from_root(root);
+                nav.selected = 0;
+                let has_children = !nav.current().children.is_empty();
+                self.list_state.select(has_children.then_some(0));
+            }
+        }
+    }
+
+    fn show_top_files(&mut self) {
+        if let AppState::ViewingResults(ref root, _) = self.state {
+            self.top_files = Some(flatten_files(root, TOP_FILES_COUNT, self.size_display.mode).0);
+        }
+    }
+
+    fn show_extension_totals(&mut self) {
+        if let AppState::ViewingResults(ref root, _) = self.state {
+            self.extension_totals = Some(sorted_extension_totals(root));
+        }
+    }
+
+    fn toggle_global_search(&mut self) {
+        if self.global_search.take().is_some() {
+            return;
+        }
+        if let AppState::ViewingResults(ref root, _) = self.state {
+            self.global_search = Some(GlobalSearch::new(root));
+        }
+    }
+
+    fn update_global_search(&mut self) {
+        if let (Some(ref mut search), AppState::ViewingResults(ref root, _)) = (&mut self.global_search, &self.state) {
+            search.update(root);
+        }
+    }
+
+    /// Close the whole-tree search and open the selected match's directory
+    /// with the match selected
+    fn open_global_search_result(&mut self) {
+        let Some(search) = self.global_search.take() else {
+            return;
+        };
+        let (Some(target), AppState::ViewingResults(ref root, _), Some(ref mut nav)) =
+            (search.selected_path(), &self.state, &mut self.navigation)
+        else {
+            return;
+        };
+        if nav.reveal(root, target) {
+            self.list_state.select(Some(nav.selected));
+        }
+    }
+
+    fn start_search(&mut self) {
+        if self.navigation.is_some() {
+            self.search_query = Some(String::new());
+        }
+    }
+
+    /// Close the search, selecting the first match in the current directory.
+    /// With no match the selection stays where it was.
+    fn finish_search(&mut self) {
+        let Some(query) = self.search_query.take() else {
+            return;
+        };
+        if let Some(ref nav) = self.navigation {
+            if let Some(&first) = search_matches(nav.current(), &query).first() {
+                self.list_state.select(Some(first));
+            }
+        }
+    }
+
+    fn show_stale_summary(&mut self) {
+        if let AppState::ViewingResults(ref root, _) = self.state {
+            self.stale_summary = Some(stale_summary(root, SystemTime::now()));
         }
     }
 
@@ -190,7 +730,16 @@ impl App {
                 if let Some(selected) = self.list_state.selected() {
                     let current = nav.current();
                     if let Some(selected_item) = current.children.get(selected) {
-                        self.pending_deletion = Some(selected_item.path.clone());
+                        // The modal only needs the totals, so leave the subtree behind
+                        let mut target = Node::new(
+                            selected_item.name.clone(),
+                            selected_item.path.clone(),
+                            selected_item.is_dir,
+                        );
+                        target.size = selected_item.size;
+                        target.allocated_size = selected_item.allocated_size;
+                        target.file_count = selected_item.file_count;
+                        self.pending_deletion = Some(target);
                         self.show_delete_modal = true;
                     }
                 }
@@ -199,7 +748,9 @@ impl App {
     }
 
     fn confirm_deletion(&mut self) {
-        if let Some(path) = self.pending_deletion.take() {
+        if let Some(target) = self.pending_deletion.take() {
+            let path = target.path;
+            let sort_key = self.tree_sort_key();
             if let AppState::ViewingResults(ref mut root, _) = self.state {
                 // Check if we're deleting the current directory before deletion
                 let deleting_current = self.navigation
@@ -209,25 +760,32 @@ impl App {
 
                 match root.delete_node(&path) {
                     Ok(()) => {
+                        // Optionally re-read the parent so the tree matches the disk
+                        let mut message = format!("✓ Successfully deleted: {}", path.display());
+                        if let (AfterDelete::RescanParent, Some(parent)) = (self.after_delete, path.parent()) {
+                            if let Err(e) = self.scanner.rescan_subtree(root, parent) {
+                                message.push_str(&format!("\n(rescan of parent failed: {})", e));
+                            }
+                        }
+
+                        // Deletion leaves shrunken ancestors where they were
+                        root.sort_by(sort_key, self.tree_sort.ascending);
+
                         // Rebuild navigation state from the updated root
                         if let Some(ref mut nav) = self.navigation {
+                            nav.selected = self.list_state.selected().unwrap_or(0);
                             if deleting_current {
                                 nav.drill_up();
                             }
                             nav.rebuild_from_root(root);
-                            
 
-
-                            let current = nav.current();
-                            if let Some(selected) = self.list_state.selected() {
-                                if selected >= current.children.len() && !current.children.is_empty() {
-                                    self.list_state.select(Some(current.children.len() - 1));
-                                } else if current.children.is_empty() {
-                                    self.list_state.select(None);
-                                }
+                            if nav.current().children.is_empty() {
+                                self.list_state.select(None);
+                            } else {
+                                self.list_state.select(Some(nav.selected));
                             }
                         }
-                        self.show_popup(format!("✓ Successfully deleted: {}", path.display()));
+                        self.show_popup(message);
                     }
                     Err(e) => {
                         self.show_popup(format!("✗ Deletion failed: {}", e));
@@ -243,6 +801,46 @@ impl App {
         self.show_delete_modal = false;
     }
 
+    fn handle_explain(&mut self) {
+        let Some(ref nav) = self.navigation else {
+            return;
+        };
+        let Some(selected) = self.list_state.selected() else {
+            return;
+        };
+
+        if let Some(item) = nav.current().children.get(selected) {
+            let message = format_explain(item, DEFAULT_EXPLAIN_TOP_N, self.size_display.mode);
+            self.show_popup(message);
+        }
+    }
+
+    fn handle_snapshot(&mut self) {
+        let Some(ref nav) = self.navigation else {
+            self.show_popup("Please wait for scan to complete first.".to_string());
+            return;
+        };
+
+        let snapshot = format_snapshot(&nav.breadcrumb(), nav.current(), self.size_display.mode);
+        // The working directory, since the scanned tree may not be writable
+        let file_name = snapshot_file_name(nav.current());
+        let output_path = std::env::current_dir().map_or_else(|_| PathBuf::from(&file_name), |dir| dir.join(&file_name));
+        let replaced = output_path.exists();
+
+        match std::fs::write(&output_path, snapshot) {
+            Ok(()) => {
+                self.show_popup(format!(
+                    "✓ Snapshot saved!\n\nSaved to:\n{}{}",
+                    output_path.display(),
+                    if replaced { "\n\n(replaced an earlier snapshot)" } else { "" }
+                ));
+            }
+            Err(e) => {
+                self.show_popup(format!("✗ Snapshot failed:\n{}", e));
+            }
+        }
+    }
+
     fn handle_export(&mut self) {
         #[cfg(feature = "pro")]
         {
@@ -257,6 +855,9 @@ impl App {
                             output_path.display()
                         ));
                     }
+                    Err(e) if e.downcast_ref::<ExportError>() == Some(&ExportError::EmptyTree) => {
+                        self.show_popup("Nothing to export.\n\nThe scanned directory is empty.".to_string());
+                    }
                     Err(e) => {
                         self.show_popup(format!("✗ Export failed:\n{}", e));
                     }
@@ -283,12 +884,39 @@ impl App {
 // ============================================================================
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
-    let scan_path = if args.len() > 1 {
-        PathBuf::from(&args[1])
-    } else {
-        env::current_dir()?
+    let mut summary_on_exit = false;
+    let mut path_arg = None;
+    let mut export_path = None;
+    let mut export_format = None;
+    let mut load_path = None;
+    let mut args = env::args_os().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == SUMMARY_ON_EXIT_FLAG {
+            summary_on_exit = true;
+        } else if arg == AUTO_EXPORT_FLAG {
+            export_path = args.next();
+        } else if arg == EXPORT_FORMAT_FLAG {
+            export_format = args.next();
+        } else if arg == LOAD_SCAN_FLAG {
+            load_path = args.next().map(PathBuf::from);
+        } else if path_arg.is_none() {
+            path_arg = Some(arg);
+        }
+    }
+    // A bad file falls back to a live scan, with the error in a popup
+    let mut load_error = None;
+    let loaded = load_path.and_then(|path| match load_saved_scan(&path) {
+        Ok(scan) => Some(scan),
+        Err(e) => {
+            load_error = Some(format!("✗ Could not load saved scan:\n{}\n\n{:#}", path.display(), e));
+            None
+        }
+    });
+    let scan_path = match loaded {
+        Some((ref root, _)) => root.path.clone(),
+        None => resolve_scan_path(path_arg)?,
     };
+    let auto_export = resolve_auto_export(export_path, export_format)?;
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -296,20 +924,31 @@ fn main() -> Result<()> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(scan_path.clone());
-
-    let shared_progress = Arc::clone(&app.shared_progress);
-    let scan_done = Arc::new(AtomicBool::new(false));
-    let scan_done_clone = Arc::clone(&scan_done);
-
-    let scan_handle = thread::spawn(move || {
-        let scanner = Scanner::new();
-        let result = scanner.scan_with_progress(&scan_path, shared_progress);
-        scan_done_clone.store(true, Ordering::Relaxed);
-        result
-    });
+    let mut app = App::new(scan_path);
+    app.auto_export = auto_export;
+    app.after_delete = AfterDelete::from_env();
+    if let Some(size) = env::var(MIN_TERMINAL_SIZE_ENV).ok().as_deref().and_then(parse_terminal_size) {
+        app.min_terminal_size = size;
+    }
+    if let Some(fraction) = env::var(DOMINANT_FRACTION_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|f| *f > 0.0 && *f <= 1.0)
+    {
+        app.dominant_fraction = fraction;
+    }
+    if let Some(message) = load_error {
+        app.show_popup(message);
+    }
+    if let Some((root, report)) = loaded {
+        app.show_results(root, report);
+    } else if is_filesystem_root(&app.scan_path) && env::var_os(SUPPRESS_ROOT_WARNING_ENV).is_none() {
+        app.confirm_full_disk = true;
+    } else {
+        app.start_scan();
+    }
 
-    let res = run_app(&mut terminal, &mut app, scan_handle, scan_done);
+    let res = run_app(&mut terminal, &mut app);
 
     disable_raw_mode()?;
     execute!(
@@ -322,48 +961,51 @@ fn main() -> Result<()> {
     if let Err(err) = res {
         eprintln!("Error: {:?}", err);
     }
+    if let Some(ref err) = app.auto_export_error {
+        eprintln!("Error: {}", err);
+    }
+
+    // Only now that the terminal is restored, so the output isn't swallowed
+    if summary_on_exit {
+        print!("{}", format_exit_summary(&app));
+    }
 
     Ok(())
 }
 
+/// Read the file given to `--load`
+#[cfg(feature = "pro")]
+fn load_saved_scan(path: &Path) -> Result<(Node, ScanReport)> {
+    Scanner::load_scan(path)
+}
+
+#[cfg(not(feature = "pro"))]
+fn load_saved_scan(_path: &Path) -> Result<(Node, ScanReport)> {
+    anyhow::bail!(
+        "{} is only available in ferris-scan Pro.\n\
+        Build with: cargo build --release --features pro",
+        LOAD_SCAN_FLAG
+    )
+}
+
 // ============================================================================
 // EVENT LOOP
 // ============================================================================
 
-fn run_app<B: Backend>(
-    terminal: &mut Terminal<B>,
-    app: &mut App,
-    scan_handle: thread::JoinHandle<Result<(Node, ScanReport)>>,
-    scan_done: Arc<AtomicBool>,
-) -> Result<()>
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
 where
     <B as Backend>::Error: Send + Sync + 'static,
 {
     let mut last_draw = std::time::Instant::now();
-    let mut scan_handle = Some(scan_handle);
 
     loop {
-        if scan_done.load(Ordering::Relaxed) {
-            if let AppState::Scanning = app.state {
-                if let Some(handle) = scan_handle.take() {
-                    match handle.join() {
-                        Ok(Ok((root, report))) => {
-                            app.state = AppState::ViewingResults(root.clone(), report);
-                            app.navigation = Some(NavigationState::new(root));
-                            app.list_state.select(Some(0));
-                        }
-                        Ok(Err(e)) => {
-                            app.show_popup(format!("Scan error: {}", e));
-                        }
-                        Err(_) => {
-                            app.show_popup("Internal error: scan thread panicked".to_string());
-                        }
-                    }
-                }
-            }
-        }
+        app.poll_scan();
 
         if last_draw.elapsed() >= Duration::from_millis(33) {
+            if let AppState::Scanning = app.state {
+                app.sample_scan_rate();
+                app.drain_skipped();
+            }
             terminal.draw(|f| ui(f, &mut *app))?;
             last_draw = std::time::Instant::now();
         }
@@ -374,6 +1016,30 @@ where
                     continue;
                 }
 
+                // Nothing is visible to act on, so only allow quitting
+                if app.terminal_too_small {
+                    if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                        app.should_quit = true;
+                        break;
+                    }
+                    continue;
+                }
+
+                if app.confirm_full_disk {
+                    match key.code {
+                        KeyCode::Char('y') | KeyCode::Enter => {
+                            app.confirm_full_disk = false;
+                            app.start_scan();
+                        }
+                        KeyCode::Char('n') | KeyCode::Char('q') | KeyCode::Esc => {
+                            app.should_quit = true;
+                            break;
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
                 if app.show_delete_modal {
                     match key.code {
                         KeyCode::Char('y') | KeyCode::Enter => {
@@ -392,13 +1058,78 @@ where
                     continue;
                 }
 
+                if app.top_files.is_some() || app.extension_totals.is_some() || app.stale_summary.is_some() {
+                    app.top_files = None;
+                    app.extension_totals = None;
+                    app.stale_summary = None;
+                    continue;
+                }
+
+                if let Some(ref mut search) = app.global_search {
+                    match key.code {
+                        KeyCode::Enter => app.open_global_search_result(),
+                        KeyCode::Esc => app.global_search = None,
+                        KeyCode::Up => search.select_previous(),
+                        KeyCode::Down => search.select_next(),
+                        KeyCode::Backspace => {
+                            search.query.pop();
+                            app.update_global_search();
+                        }
+                        KeyCode::Char(c) => {
+                            search.query.push(c);
+                            app.update_global_search();
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                if let Some(ref mut query) = app.search_query {
+                    match key.code {
+                        KeyCode::Enter => app.finish_search(),
+                        KeyCode::Esc => app.search_query = None,
+                        KeyCode::Backspace => {
+                            query.pop();
+                        }
+                        KeyCode::Char(c) => query.push(c),
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                if let Some(ref mut flat) = app.flat_view {
+                    match key.code {
+                        KeyCode::Char('q') => {
+                            app.should_quit = true;
+                        }
+                        KeyCode::Char('f') | KeyCode::Esc => {
+                            app.flat_view = None;
+                        }
+                        KeyCode::Char('s') => {
+                            flat.toggle_sort();
+                        }
+                        KeyCode::Up | KeyCode::Char('k') => {
+                            flat.select_previous();
+                        }
+                        KeyCode::Down | KeyCode::Char('j') => {
+                            flat.select_next();
+                        }
+                        _ => {}
+                    }
+                    if app.should_quit {
+                        break;
+                    }
+                    continue;
+                }
+
                 match key.code {
+                    // While scanning, the first `q` stops the scan and keeps the partial tree
                     KeyCode::Char('q') => {
-                        app.should_quit = true;
+                        app.should_quit = !app.stop_scan();
                     }
                     KeyCode::Esc => {
                         if let Some(ref mut nav) = app.navigation {
-                            if nav.drill_up() {
+                            if nav.back() {
                                 app.list_state.select(Some(0));
                             } else {
                                 app.should_quit = true;
@@ -407,16 +1138,69 @@ where
                             app.should_quit = true;
                         }
                     }
+                    KeyCode::Char(' ') => {
+                        app.toggle_pause();
+                    }
                     KeyCode::Char('e') => {
                         app.handle_export();
                     }
                     KeyCode::Char('d') => {
                         app.handle_delete();
                     }
+                    KeyCode::Char('c') => {
+                        app.handle_snapshot();
+                    }
+                    KeyCode::Char('x') => {
+                        app.handle_explain();
+                    }
+                    KeyCode::Char('f') => {
+                        app.toggle_flat_view();
+                    }
+                    KeyCode::Char('t') => {
+                        app.show_top_files();
+                    }
+                    KeyCode::Char('b') => {
+                        app.show_extension_totals();
+                    }
+                    KeyCode::Char('o') => {
+                        app.show_stale_summary();
+                    }
+                    KeyCode::Char('/') => {
+                        app.start_search();
+                    }
+                    KeyCode::Char('F') => {
+                        app.toggle_global_search();
+                    }
+                    KeyCode::Char('s') => {
+                        app.tree_sort = app.tree_sort.next_key();
+                        app.apply_tree_sort();
+                    }
+                    KeyCode::Char('S') => {
+                        app.tree_sort = app.tree_sort.reversed();
+                        app.apply_tree_sort();
+                    }
+                    KeyCode::Char('a') => {
+                        app.size_display.mode = app.size_display.mode.toggled();
+                    }
+                    KeyCode::Char('u') => {
+                        app.size_display.tree_format = app.size_display.tree_format.next();
+                    }
+                    KeyCode::Char('U') => {
+                        app.size_display.detail_format = app.size_display.detail_format.next();
+                    }
+                    KeyCode::Char('r') => {
+                        if let AppState::ViewingResults(_, _) = app.state {
+                            app.start_scan();
+                        }
+                    }
+                    KeyCode::Char('g') => {
+                        app.follow_dominant = !app.follow_dominant;
+                    }
                     KeyCode::Enter => {
+                        let follow = app.follow_dominant.then_some(app.dominant_fraction);
                         if let Some(ref mut nav) = app.navigation {
                             if let Some(selected) = app.list_state.selected() {
-                                if nav.drill_down(selected) {
+                                if nav.drill_down_following(selected, follow) {
                                     app.list_state.select(Some(0));
                                 }
                             }
@@ -424,7 +1208,7 @@ where
                     }
                     KeyCode::Backspace => {
                         if let Some(ref mut nav) = app.navigation {
-                            nav.drill_up();
+                            nav.back();
                             app.list_state.select(Some(0));
                         }
                     }
@@ -458,14 +1242,15 @@ where
                     }
                     KeyCode::Char('h') => {
                         if let Some(ref mut nav) = app.navigation {
-                            nav.drill_up();
+                            nav.back();
                             app.list_state.select(Some(0));
                         }
                     }
                     KeyCode::Char('l') => {
+                        let follow = app.follow_dominant.then_some(app.dominant_fraction);
                         if let Some(ref mut nav) = app.navigation {
                             if let Some(selected) = app.list_state.selected() {
-                                if nav.drill_down(selected) {
+                                if nav.drill_down_following(selected, follow) {
                                     app.list_state.select(Some(0));
                                 }
                             }
@@ -489,6 +1274,12 @@ where
 // ============================================================================
 
 fn ui(f: &mut Frame, app: &mut App) {
+    app.terminal_too_small = is_too_small(f.area(), app.min_terminal_size);
+    if app.terminal_too_small {
+        render_too_small(f, app.min_terminal_size);
+        return;
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -502,31 +1293,119 @@ fn ui(f: &mut Frame, app: &mut App) {
 
     match &app.state {
         AppState::Scanning => render_scanning(f, chunks[1], app),
-        AppState::ViewingResults(root, report) => {
-            render_results(f, chunks[1], root, report, &app.navigation, &mut app.list_state)
-        }
+        AppState::ViewingResults(root, _) => match (&mut app.global_search, &mut app.flat_view) {
+            (Some(search), _) => render_global_search(f, chunks[1], search, &root.path),
+            (None, Some(flat)) => render_flat_view(f, chunks[1], flat),
+            (None, None) => {
+                let results_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(3), Constraint::Min(0)])
+                    .split(chunks[1]);
+                render_location(f, results_chunks[0], app);
+                render_results(f, results_chunks[1], app);
+            }
+        },
     }
 
     render_footer(f, chunks[2], app);
 
+    if let Some(ref files) = app.top_files {
+        draw_top_files_modal(f, files);
+    }
+
+    if let Some(ref summary) = app.stale_summary {
+        draw_stale_modal(f, summary);
+    }
+
+    if let Some(ref totals) = app.extension_totals {
+        draw_extension_totals_modal(f, totals);
+    }
+
     if let Some(ref message) = app.popup_message {
         render_popup(f, message);
     }
 
+    if app.confirm_full_disk {
+        draw_full_disk_modal(f, &app.scan_path);
+    }
+
     if app.show_delete_modal {
-        if let Some(ref path) = app.pending_deletion {
-            let filename = path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| path.display().to_string());
-            draw_delete_modal(f, &filename);
+        if let Some(ref target) = app.pending_deletion {
+            draw_delete_modal(f, &delete_prompt(target, app.size_display));
         }
     }
 }
 
+/// `key=value` lines for `--summary-on-exit`. Totals are those of the tree
+/// at exit, so they reflect deletions made during the session. `status` is
+/// `partial` for a stopped scan. Only `status` and `path` are printed if the
+/// scan never finished.
+fn format_exit_summary(app: &App) -> String {
+    let mut out = String::new();
+    match &app.state {
+        AppState::ViewingResults(root, report) => {
+            let status = if report.cancelled || report.root_lost { "partial" } else { "complete" };
+            out.push_str(&format!("status={}\n", status));
+            out.push_str(&format!("path={}\n", app.scan_path.display()));
+            out.push_str(&format!("total_bytes={}\n", root.size));
+            out.push_str(&format!("allocated_bytes={}\n", root.allocated_size));
+            out.push_str(&format!("files={}\n", root.file_count));
+            out.push_str(&format!("skipped={}\n", report.skipped.len()));
+        }
+        AppState::Scanning => {
+            out.push_str("status=incomplete\n");
+            out.push_str(&format!("path={}\n", app.scan_path.display()));
+        }
+    }
+    out
+}
+
+/// Whether `area` is narrower or shorter than `min` (columns, rows)
+fn is_too_small(area: Rect, min: (u16, u16)) -> bool {
+    area.width < min.0 || area.height < min.1
+}
+
+/// Rough time left for the scanning view: "45s", "3m 20s" or "2h 05m"
+fn format_remaining(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Parse a "WIDTHxHEIGHT" terminal size such as "80x24"
+fn parse_terminal_size(value: &str) -> Option<(u16, u16)> {
+    let (width, height) = value.trim().split_once(['x', 'X'])?;
+    Some((width.trim().parse().ok()?, height.trim().parse().ok()?))
+}
+
+/// Replaces the whole UI while the terminal is below the minimum size
+fn render_too_small(f: &mut Frame, min: (u16, u16)) {
+    let area = f.area();
+    let message = format!(
+        "Terminal too small (need at least {}x{}, have {}x{})",
+        min.0, min.1, area.width, area.height
+    );
+    let paragraph = Paragraph::new(vec![Line::from(message), Line::from("Resize the window or press q to quit")])
+        .style(Style::default().fg(Color::Yellow))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    let top = area.height.saturating_sub(2) / 2;
+    f.render_widget(paragraph, Rect { y: area.y + top, height: area.height - top, ..area });
+}
+
 fn render_header(f: &mut Frame, area: Rect, app: &App) {
-    let title = format!("ferris-scan TUI v{} | {}", env!("CARGO_PKG_VERSION"), app.scan_path.display());
+    let mut title = format!(
+        "ferris-scan TUI v{} | {} | sizes: {}",
+        env!("CARGO_PKG_VERSION"),
+        app.scan_path.display(),
+        app.size_display.mode.label()
+    );
+    if app.follow_dominant {
+        title.push_str(&format!(" | follow big: {:.0}%", app.dominant_fraction * 100.0));
+    }
     
     #[cfg(feature = "pro")]
     let version_tag = " [PRO] ";
@@ -552,6 +1431,8 @@ fn render_scanning(f: &mut Frame, area: Rect, app: &App) {
         .shared_progress
         .files_scanned
         .load(Ordering::Relaxed);
+    let bytes = app.shared_progress.bytes_scanned.load(Ordering::Relaxed);
+    let rate = app.shared_progress.rate_per_sec();
     let last_path = app
         .shared_progress
         .last_path
@@ -564,13 +1445,36 @@ fn render_scanning(f: &mut Frame, area: Rect, app: &App) {
     let text = vec![
         Line::from(""),
         Line::from(Span::styled(
-            "⟳ Scanning in progress...",
+            if app.scan_cancel.load(Ordering::Relaxed) {
+                "⟳ Stopping, partial results follow..."
+            } else if app.scan_pause.load(Ordering::Relaxed) {
+                "⏸ Paused (space to resume)"
+            } else {
+                "⟳ Scanning in progress..."
+            },
             Style::default()
                 .fg(Color::LightGreen)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
-        Line::from(format!("Files scanned: {}", files)),
+        Line::from(format!(
+            "Dirs: {} / Files: {}",
+            app.shared_progress.dirs_scanned.load(Ordering::Relaxed),
+            files
+        )),
+        Line::from(format!("Scanned: {}", format_size(bytes))),
+        Line::from(Span::styled(
+            format!("Skipped: {}", app.skipped_live),
+            if app.skipped_live > 0 {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            },
+        )),
+        Line::from(match app.shared_progress.eta() {
+            Some(eta) => format!("~{} remaining ({:.0} files/s)", format_remaining(eta), rate),
+            None => format!("{:.0} files/s", rate),
+        }),
         Line::from(""),
         Line::from(Span::styled(
             "Current path:",
@@ -579,6 +1483,15 @@ fn render_scanning(f: &mut Frame, area: Rect, app: &App) {
         Line::from(last_path),
     ];
 
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(text.len() as u16 + 2),
+            Constraint::Length(5),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
     let paragraph = Paragraph::new(text)
         .block(
             Block::default()
@@ -589,57 +1502,271 @@ fn render_scanning(f: &mut Frame, area: Rect, app: &App) {
         .alignment(Alignment::Center)
         .wrap(Wrap { trim: true });
 
-    f.render_widget(paragraph, area);
+    f.render_widget(paragraph, chunks[0]);
+
+    render_rate_sparkline(f, chunks[1], app);
+    render_partial_results(f, chunks[2], app);
 }
 
-fn render_results(f: &mut Frame, area: Rect, root: &Node, report: &ScanReport, navigation: &Option<NavigationState>, list_state: &mut ListState) {
-    let main_chunks = Layout::default()
-        .direction(Direction::Vertical)
+fn render_rate_sparkline(f: &mut Frame, area: Rect, app: &App) {
+    // Show the most recent samples that fit, right-aligned to "now"
+    let visible = area.width.saturating_sub(2) as usize;
+    let skip = app.rate_history.len().saturating_sub(visible);
+    let data: Vec<u64> = app.rate_history.iter().skip(skip).copied().collect();
+    let current = data.last().copied().unwrap_or(0);
+
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Files/sec | {}", current))
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .data(&data)
+        .style(Style::default().fg(Color::LightGreen));
+
+    f.render_widget(sparkline, area);
+}
+
+fn render_partial_results(f: &mut Frame, area: Rect, app: &App) {
+    let top_level = app
+        .shared_progress
+        .top_level
+        .lock()
+        .map(|g| g.clone())
+        .unwrap_or_default();
+
+    let available_width = area.width.saturating_sub(2) as usize;
+    let items: Vec<ListItem> = top_level
+        .iter()
+        .map(|entry| {
+            let type_indicator = if entry.is_dir { "📁" } else { "📄" };
+            let size_str = format_size(entry.size);
+            let name_width = available_width.saturating_sub(size_str.len() + 4);
+            ListItem::new(Line::from(vec![
+                Span::raw(format!(
+                    "{} {:<width$.width$} ",
+                    type_indicator,
+                    entry.name,
+                    width = name_width
+                )),
+                Span::styled(size_str, Style::default().fg(Color::Cyan)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Largest So Far (partial)")
+            .border_style(Style::default().fg(Color::LightGreen)),
+    );
+
+    f.render_widget(list, area);
+}
+
+/// Breadcrumb of the current directory, titled with the tree's sort order
+fn render_location(f: &mut Frame, area: Rect, app: &App) {
+    let breadcrumb_text = match app.search_query {
+        Some(ref query) => format!("/{}_", query),
+        None => app
+            .navigation
+            .as_ref()
+            .map(|nav| nav.breadcrumb())
+            .unwrap_or_else(|| "Root".to_string()),
+    };
+    let title = if app.search_query.is_some() {
+        "Search | Enter: select first match, Esc: clear".to_string()
+    } else {
+        format!("Location | sorted by {}", app.tree_sort.label())
+    };
+    
+    let breadcrumb = Paragraph::new(breadcrumb_text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::LightGreen))
+        )
+        .style(Style::default().fg(Color::LightCyan));
+    
+    f.render_widget(breadcrumb, area);
+}
+
+fn render_results(f: &mut Frame, area: Rect, app: &mut App) {
+    let AppState::ViewingResults(ref root, ref report) = app.state else {
+        return;
+    };
+    let size_display = app.size_display;
+    let search_query = app.search_query.as_deref();
+
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Length(3),
-            Constraint::Min(0),
+            Constraint::Percentage(40),
+            Constraint::Percentage(35),
+            Constraint::Percentage(25),
         ])
         .split(area);
 
-    let breadcrumb_text = navigation
-        .as_ref()
-        .map(|nav| nav.breadcrumb())
-        .unwrap_or_else(|| "Root".to_string());
-    
-    let breadcrumb = Paragraph::new(breadcrumb_text)
+    let current_node = app
+        .navigation
+        .as_ref()
+        .map(|nav| nav.current())
+        .unwrap_or(root);
+    
+    // While searching, the details follow the match Enter would select
+    let selected_index = match search_query {
+        Some(query) => search_matches(current_node, query).first().copied(),
+        None => Some(app.list_state.selected().unwrap_or(0)),
+    };
+    let selected_item = selected_index.and_then(|i| current_node.children.get(i));
+
+    render_tree_pane(f, panes[0], current_node, &mut app.list_state, size_display, search_query);
+    render_details_pane(f, panes[1], selected_item, current_node, root, size_display);
+    render_stats_pane(f, panes[2], root, report, current_node, size_display);
+}
+
+fn render_global_search(f: &mut Frame, area: Rect, search: &mut GlobalSearch, root: &Path) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .split(area);
+
+    let input = Paragraph::new(format!("{}_", search.query))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Find in whole tree | Enter: open, Esc: close")
+                .border_style(Style::default().fg(Color::LightGreen)),
+        )
+        .style(Style::default().fg(Color::LightCyan));
+    f.render_widget(input, chunks[0]);
+
+    let available_width = chunks[1].width.saturating_sub(4) as usize;
+    let mut items: Vec<ListItem> = search
+        .results
+        .iter()
+        .map(|(path, size)| {
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            let size_str = format_size(*size);
+            let path_width = available_width.saturating_sub(size_str.len() + 1);
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<width$.width$} ", relative.display(), width = path_width)),
+                Span::styled(size_str, Style::default().fg(Color::Cyan)),
+            ]))
+        })
+        .collect();
+    if items.is_empty() {
+        let hint = if search.query.is_empty() { "Type part of a file name" } else { "No matching files" };
+        items.push(ListItem::new(Line::from(Span::styled(
+            hint,
+            Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+        ))));
+    }
+
+    let mut title = if search.total > search.results.len() {
+        format!("Matches | showing {} of {}", search.results.len(), search.total)
+    } else {
+        format!("Matches ({})", search.total)
+    };
+    if search.unreadable > 0 {
+        title.push_str(&format!(" | {} unreadable directories not searched", search.unreadable));
+    }
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::LightGreen)),
+        )
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ");
+
+    f.render_stateful_widget(list, chunks[1], &mut search.list_state);
+}
+
+fn render_flat_view(f: &mut Frame, area: Rect, flat: &mut FlatView) {
+    let available_width = area.width.saturating_sub(4) as usize;
+    let items: Vec<ListItem> = flat
+        .files
+        .iter()
+        .map(|(path, size)| {
+            let size_str = format_size(*size);
+            let path_width = available_width.saturating_sub(size_str.len() + 1);
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<width$.width$} ", path.display(), width = path_width)),
+                Span::styled(size_str, Style::default().fg(Color::Cyan)),
+            ]))
+        })
+        .collect();
+
+    let mut title = format!("All Files ({}) | sorted by {}", flat.files.len(), flat.sort.label());
+    if flat.omitted > 0 {
+        title.push_str(&format!(" | {} smaller files not shown", flat.omitted));
+    }
+
+    let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Location")
-                .border_style(Style::default().fg(Color::LightGreen))
+                .title(title)
+                .border_style(Style::default().fg(Color::LightGreen)),
         )
-        .style(Style::default().fg(Color::LightCyan));
-    
-    f.render_widget(breadcrumb, main_chunks[0]);
+        .highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        )
+        .highlight_symbol("> ");
 
-    let panes = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(40),
-            Constraint::Percentage(35),
-            Constraint::Percentage(25),
-        ])
-        .split(main_chunks[1]);
+    f.render_stateful_widget(list, area, &mut flat.list_state);
+}
 
-    let current_node = navigation
-        .as_ref()
-        .map(|nav| nav.current())
-        .unwrap_or(root);
-    
-    let selected_index = list_state.selected().unwrap_or(0);
-    let selected_item = current_node.children.get(selected_index);
+/// Files under `root` whose name contains `query`, ignoring case, in tree
+/// order: at most `limit` (full path, size) pairs and the total number of
+/// matches. An empty query matches nothing.
+fn search_tree(root: &Node, query: &str, limit: usize) -> (Vec<(PathBuf, u64)>, usize) {
+    if query.is_empty() {
+        return (Vec::new(), 0);
+    }
+    let query = query.to_lowercase();
+    let mut results = Vec::new();
+    let mut total = 0;
+    for file in root.iter_files().filter(|n| n.name.to_lowercase().contains(&query)) {
+        if results.len() < limit {
+            results.push((file.path.clone(), file.size));
+        }
+        total += 1;
+    }
+    (results, total)
+}
 
-    render_tree_pane(f, panes[0], current_node, list_state);
-    render_details_pane(f, panes[1], selected_item, current_node);
-    render_stats_pane(f, panes[2], root, report, current_node);
+/// Indices of `node`'s children whose name contains `query`, ignoring case.
+/// An empty query matches every child.
+fn search_matches(node: &Node, query: &str) -> Vec<usize> {
+    let query = query.to_lowercase();
+    node.children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| child.name.to_lowercase().contains(&query))
+        .map(|(i, _)| i)
+        .collect()
 }
 
-fn render_tree_pane(f: &mut Frame, area: Rect, current_node: &Node, list_state: &mut ListState) {
+fn render_tree_pane(
+    f: &mut Frame,
+    area: Rect,
+    current_node: &Node,
+    list_state: &mut ListState,
+    size_display: SizeDisplay,
+    search_query: Option<&str>,
+) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -649,6 +1776,9 @@ fn render_tree_pane(f: &mut Frame, area: Rect, current_node: &Node, list_state:
         .split(area);
 
     let available_width = area.width.saturating_sub(2) as usize;
+    // Share-of-parent bars get a sixth of the row, dropped on narrow panes
+    let bar_width = if available_width >= 40 { (available_width / 6).min(20) } else { 0 };
+    let parent_size = current_node.size_in(size_display.mode);
     
     let size_column_width = 12;
     let name_column_width = available_width.saturating_sub(size_column_width + 1);
@@ -671,14 +1801,29 @@ fn render_tree_pane(f: &mut Frame, area: Rect, current_node: &Node, list_state:
     )));
     f.render_widget(header, chunks[0]);
 
+    let visible: Vec<&Node> = match search_query {
+        Some(query) => search_matches(current_node, query)
+            .into_iter()
+            .map(|i| &current_node.children[i])
+            .collect(),
+        None => current_node.children.iter().collect(),
+    };
+
     let mut items = Vec::new();
-    for child in &current_node.children {
-        let size_str = format_size(child.size);
-        let type_indicator = if child.is_dir { "📁" } else { "📄" };
+    for child in visible.iter().copied() {
+        let child_size = child.size_in(size_display.mode);
+        let size_str = format_size_as(child_size, size_display.tree_format);
+        let bar = if bar_width > 0 {
+            format!("{} ", size_bar(child_size, parent_size, bar_width))
+        } else {
+            String::new()
+        };
+        let type_indicator = if child.access_denied { "🔒" } else if child.is_dir { "📁" } else { "📄" };
         
         let size_str_len = size_str.chars().count();
         
         let max_name_len = available_width
+            .saturating_sub(bar.chars().count())
             .saturating_sub(2)
             .saturating_sub(1)
             .saturating_sub(size_str_len);
@@ -694,7 +1839,7 @@ fn render_tree_pane(f: &mut Frame, area: Rect, current_node: &Node, list_state:
         
         let name_with_emoji = format!("{} {}", type_indicator, display_name);
         
-        let max_line_len = available_width;
+        let max_line_len = available_width.saturating_sub(bar.chars().count());
         let size_str_bytes = size_str.len();
         
         let max_name_bytes = max_line_len.saturating_sub(size_str_bytes).saturating_sub(1); 
@@ -733,6 +1878,7 @@ fn render_tree_pane(f: &mut Frame, area: Rect, current_node: &Node, list_state:
             if size_part == size_str {
                 items.push(ListItem::new(Line::from(vec![
                     Span::raw(name_part),
+                    Span::styled(bar, Style::default().fg(Color::LightGreen)),
                     Span::styled(
                         size_part,
                         Style::default().fg(Color::Cyan),
@@ -746,7 +1892,29 @@ fn render_tree_pane(f: &mut Frame, area: Rect, current_node: &Node, list_state:
         }
     }
 
-    let title = format!("Tree View | {} items", current_node.children.len());
+    let title = match search_query {
+        Some(query) => format!(
+            "Tree View | {} of {} items match \"{}\"",
+            visible.len(),
+            current_node.children.len(),
+            query
+        ),
+        None => format!("Tree View | {} items", current_node.children.len()),
+    };
+
+    // The filtered list highlights the first match instead of the selection
+    let mut search_state = ListState::default();
+    if let Some(query) = search_query {
+        if visible.is_empty() {
+            items.push(ListItem::new(Line::from(Span::styled(
+                format!("No matches for \"{}\"", query),
+                Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+            ))));
+        } else {
+            search_state.select(Some(0));
+        }
+    }
+    let list_state = if search_query.is_some() { &mut search_state } else { list_state };
 
     let list = List::new(items)
         .block(
@@ -766,9 +1934,9 @@ fn render_tree_pane(f: &mut Frame, area: Rect, current_node: &Node, list_state:
     f.render_stateful_widget(list, chunks[1], list_state);
 }
 
-fn render_details_pane(f: &mut Frame, area: Rect, selected_item: Option<&Node>, _current_node: &Node) {
+fn render_details_pane(f: &mut Frame, area: Rect, selected_item: Option<&Node>, current_node: &Node, root: &Node, size_display: SizeDisplay) {
     let details_text = if let Some(item) = selected_item {
-        vec![
+        let mut lines = vec![
             Line::from(""),
             Line::from(Span::styled(
                 "Selected Item Details",
@@ -790,10 +1958,24 @@ fn render_details_pane(f: &mut Frame, area: Rect, selected_item: Option<&Node>,
             Line::from(vec![
                 Span::styled("Size: ", Style::default().add_modifier(Modifier::BOLD)),
                 Span::styled(
-                    format_size(item.size),
+                    format_size_as(item.size_in(size_display.mode), size_display.detail_format),
                     Style::default().fg(Color::Cyan),
                 ),
             ]),
+            Line::from(vec![
+                Span::styled("Of parent: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(
+                    share_label(item.size_in(size_display.mode), current_node.size_in(size_display.mode)),
+                    Style::default().fg(Color::LightGreen),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("Of total:  ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(
+                    share_label(item.size_in(size_display.mode), root.size_in(size_display.mode)),
+                    Style::default().fg(Color::LightGreen),
+                ),
+            ]),
             Line::from(""),
             Line::from(vec![
                 Span::styled("Path: ", Style::default().add_modifier(Modifier::BOLD)),
@@ -811,7 +1993,37 @@ fn render_details_pane(f: &mut Frame, area: Rect, selected_item: Option<&Node>,
             } else {
                 Line::from("")
             },
-        ]
+        ];
+        if item.access_denied {
+            lines.push(Line::from(Span::styled(
+                "🔒 Access denied: contents not counted",
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+        if let Some((path, size)) = &item.largest_file {
+            let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled("Biggest: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(format!("{} ({})", name, format_size_as(*size, size_display.detail_format))),
+            ]));
+        }
+        if item.is_dir {
+            let average = item
+                .average_file_size(size_display.mode)
+                .map_or_else(|| "no files".to_string(), |avg| format_size_as(avg, size_display.detail_format));
+            lines.push(Line::from(vec![
+                Span::styled("Avg file size: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(average),
+            ]));
+            let (direct, nested) = item.direct_and_nested_size(size_display.mode);
+            lines.push(Line::from(vec![
+                Span::styled("Direct files: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(format_size_as(direct, size_display.detail_format)),
+                Span::styled(" / Subdirectories: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(format_size_as(nested, size_display.detail_format)),
+            ]));
+        }
+        lines
     } else {
         vec![
             Line::from(""),
@@ -840,7 +2052,7 @@ fn render_details_pane(f: &mut Frame, area: Rect, selected_item: Option<&Node>,
     f.render_widget(details, area);
 }
 
-fn render_stats_pane(f: &mut Frame, area: Rect, root: &Node, report: &ScanReport, current_node: &Node) {
+fn render_stats_pane(f: &mut Frame, area: Rect, root: &Node, report: &ScanReport, current_node: &Node, size_display: SizeDisplay) {
     let stats_text = vec![
         Line::from(""),
         Line::from(Span::styled(
@@ -853,16 +2065,40 @@ fn render_stats_pane(f: &mut Frame, area: Rect, root: &Node, report: &ScanReport
         Line::from(vec![
             Span::styled("Total Size: ", Style::default().add_modifier(Modifier::BOLD)),
             Span::styled(
-                format_size(root.size),
+                format_size_as(root.size_in(size_display.mode), size_display.detail_format),
                 Style::default().fg(Color::Cyan),
             ),
         ]),
         Line::from(""),
+        Line::from(vec![
+            Span::styled("Scan time: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{:.2}s", report.elapsed.as_secs_f64())),
+        ]),
+        Line::from(vec![
+            Span::styled("Nodes: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(root.total_nodes().to_string()),
+        ]),
         Line::from(vec![
             Span::styled("Skipped: ", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(format!("{} entries", report.skipped.len())),
+            Span::raw(if report.excluded.is_empty() {
+                format!("{} entries", report.skipped.len())
+            } else {
+                format!("{} entries, {} excluded dirs", report.skipped.len(), report.excluded.len())
+            }),
         ]),
-        Line::from(""),
+        if report.root_lost {
+            Line::from(Span::styled(
+                "⚠ Scan root disappeared, results are partial",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ))
+        } else if report.cancelled {
+            Line::from(Span::styled(
+                "⚠ Scan stopped early, results are partial",
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ))
+        } else {
+            Line::from("")
+        },
         Line::from(Span::styled(
             "Current Directory",
             Style::default()
@@ -878,7 +2114,7 @@ fn render_stats_pane(f: &mut Frame, area: Rect, root: &Node, report: &ScanReport
         Line::from(vec![
             Span::styled("Size: ", Style::default().add_modifier(Modifier::BOLD)),
             Span::styled(
-                format_size(current_node.size),
+                format_size_as(current_node.size_in(size_display.mode), size_display.detail_format),
                 Style::default().fg(Color::Cyan),
             ),
         ]),
@@ -887,6 +2123,10 @@ fn render_stats_pane(f: &mut Frame, area: Rect, root: &Node, report: &ScanReport
             Span::styled("Items: ", Style::default().add_modifier(Modifier::BOLD)),
             Span::raw(format!("{}", current_node.children.len())),
         ]),
+        Line::from(vec![
+            Span::styled("Files: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{}", current_node.file_count)),
+        ]),
     ];
 
     let stats = Paragraph::new(stats_text)
@@ -904,9 +2144,23 @@ fn render_stats_pane(f: &mut Frame, area: Rect, root: &Node, report: &ScanReport
 fn render_footer(f: &mut Frame, area: Rect, app: &App) {
     let key_hints = match &app.state {
         AppState::Scanning => vec![
+            Span::styled("Space", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(if app.scan_pause.load(Ordering::Relaxed) { ": Resume | " } else { ": Pause | " }),
             Span::styled("q", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)),
+            Span::raw(": Stop and show results | "),
+            Span::styled("Esc", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)),
             Span::raw(": Quit"),
         ],
+        AppState::ViewingResults(_, _) if app.flat_view.is_some() => vec![
+            Span::styled("q", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)),
+            Span::raw(": Quit | "),
+            Span::styled("s", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Sort size/path | "),
+            Span::styled("f/Esc", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)),
+            Span::raw(": Tree view | "),
+            Span::styled("↑/↓", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Nav"),
+        ],
         AppState::ViewingResults(_, _) => vec![
             Span::styled("q", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)),
             Span::raw(": Quit | "),
@@ -914,6 +2168,32 @@ fn render_footer(f: &mut Frame, area: Rect, app: &App) {
             Span::raw(": Open | "),
             Span::styled("d", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
             Span::raw(": Delete | "),
+            Span::styled("c", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Snapshot | "),
+            Span::styled("x", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Explain | "),
+            Span::styled("f", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Flat | "),
+            Span::styled("t", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Top files | "),
+            Span::styled("b", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": By extension | "),
+            Span::styled("o", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Old files | "),
+            Span::styled("/", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Search | "),
+            Span::styled("F", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Find anywhere | "),
+            Span::styled("s/S", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Sort (key/reverse) | "),
+            Span::styled("a", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Apparent/Allocated | "),
+            Span::styled("u/U", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Units (tree/details) | "),
+            Span::styled("r", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Rescan | "),
+            Span::styled("g", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(": Follow big | "),
             Span::styled("Esc", Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)),
             Span::raw(": Back | "),
             Span::styled("↑/↓", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
@@ -923,19 +2203,21 @@ fn render_footer(f: &mut Frame, area: Rect, app: &App) {
         ],
     };
 
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::LightGreen));
+    if let AppState::ViewingResults(ref root, ref report) = app.state {
+        block = block.title(format!(" {} ", summary_line(root, report)));
+    }
     let footer = Paragraph::new(Line::from(key_hints))
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::LightGreen))
-        )
+        .block(block)
         .alignment(Alignment::Center);
 
     f.render_widget(footer, area);
 }
 
 fn render_popup(f: &mut Frame, message: &str) {
-    let area = centered_rect(60, 40, f.area());
+    let area = clamp_width(centered_rect(60, 40, f.area()), MODAL_MAX_WIDTH);
 
     let block = Block::default()
         .title(" Message ")
@@ -953,13 +2235,167 @@ fn render_popup(f: &mut Frame, message: &str) {
     f.render_widget(text, area);
 }
 
-fn draw_delete_modal(f: &mut Frame, filename: &str) {
-    let area = centered_rect(60, 30, f.area());
+/// Totals of the files under `root` older than each of `STALE_AGE_DAYS` as
+/// of `now`, and the oldest files
+fn stale_summary(root: &Node, now: SystemTime) -> StaleSummary {
+    const DAY: Duration = Duration::from_secs(86_400);
+    let buckets = STALE_AGE_DAYS
+        .iter()
+        .map(|&days| {
+            let cutoff = now.checked_sub(DAY * days as u32).unwrap_or(SystemTime::UNIX_EPOCH);
+            let (bytes, files) = root.size_older_than(cutoff);
+            (days, bytes, files)
+        })
+        .collect();
+    let oldest = root
+        .files_older_than(now)
+        .into_iter()
+        .take(OLDEST_FILES_COUNT)
+        .map(|n| {
+            let age = n.mtime.and_then(|t| now.duration_since(t).ok()).unwrap_or_default();
+            (n.path.clone(), age.as_secs() / DAY.as_secs())
+        })
+        .collect();
+    StaleSummary { buckets, oldest }
+}
 
-    let message = format!(
-        "Are you sure you want to delete\n{}\n\nThis cannot be undone.\n\n[y/Enter] Confirm  [n/Esc] Cancel",
-        filename
-    );
+fn draw_stale_modal(f: &mut Frame, summary: &StaleSummary) {
+    let area = centered_rect(80, 70, f.area());
+
+    let mut lines: Vec<Line> = summary
+        .buckets
+        .iter()
+        .map(|(days, bytes, files)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("Not modified in {:>3} days: {:>10}", days, format_size(*bytes)),
+                    Style::default().fg(Color::LightGreen),
+                ),
+                Span::raw(format!("  in {} files", files)),
+            ])
+        })
+        .collect();
+    lines.push(Line::from(""));
+    if summary.oldest.is_empty() {
+        lines.push(Line::from("No modification times recorded."));
+    } else {
+        lines.push(Line::from(Span::styled("Oldest files", Style::default().add_modifier(Modifier::BOLD))));
+        for (path, age_days) in &summary.oldest {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:>7} days  ", age_days), Style::default().fg(Color::LightGreen)),
+                Span::raw(path.display().to_string()),
+            ]));
+        }
+    }
+
+    let block = Block::default()
+        .title(" Old files | any key to close ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::LightGreen))
+        .style(Style::default().bg(Color::Black));
+
+    let text = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(Color::Cyan));
+
+    f.render_widget(Clear, area);
+    f.render_widget(text, area);
+}
+
+fn draw_top_files_modal(f: &mut Frame, files: &[(PathBuf, u64)]) {
+    let area = centered_rect(80, 70, f.area());
+
+    let lines: Vec<Line> = if files.is_empty() {
+        vec![Line::from("No files found.")]
+    } else {
+        files
+            .iter()
+            .enumerate()
+            .map(|(i, (path, size))| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:>3}. {:>10}  ", i + 1, format_size(*size)),
+                        Style::default().fg(Color::LightGreen),
+                    ),
+                    Span::raw(path.display().to_string()),
+                ])
+            })
+            .collect()
+    };
+
+    let block = Block::default()
+        .title(format!(" Largest files ({}) | any key to close ", files.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::LightGreen))
+        .style(Style::default().bg(Color::Black));
+
+    let text = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(Color::Cyan));
+
+    f.render_widget(Clear, area);
+    f.render_widget(text, area);
+}
+
+fn draw_extension_totals_modal(f: &mut Frame, totals: &[(String, u64, u64)]) {
+    let area = clamp_width(centered_rect(60, 70, f.area()), MODAL_MAX_WIDTH);
+
+    let lines: Vec<Line> = if totals.is_empty() {
+        vec![Line::from("No files found.")]
+    } else {
+        totals
+            .iter()
+            .map(|(ext, size, files)| {
+                let label = if ext.is_empty() { "(no extension)".to_string() } else { format!(".{}", ext) };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:>10}  {:>8} files  ", format_size(*size), files),
+                        Style::default().fg(Color::LightGreen),
+                    ),
+                    Span::raw(label),
+                ])
+            })
+            .collect()
+    };
+
+    let block = Block::default()
+        .title(format!(" Size by extension ({}) | any key to close ", totals.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::LightGreen))
+        .style(Style::default().bg(Color::Black));
+
+    let text = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(Color::Cyan));
+
+    f.render_widget(Clear, area);
+    f.render_widget(text, area);
+}
+
+/// Body of the delete confirmation: the name, the space it frees and, for a
+/// directory, how many files go with it.
+fn delete_prompt(target: &Node, size_display: SizeDisplay) -> String {
+    let name = target
+        .path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| target.path.display().to_string());
+    let size = format_size_as(target.size_in(size_display.mode), size_display.detail_format);
+    let reclaimed = if target.is_dir {
+        let noun = if target.file_count == 1 { "file" } else { "files" };
+        format!("{} in {} {}", size, target.file_count, noun)
+    } else {
+        size
+    };
+    format!(
+        "Are you sure you want to delete\n{}\n({})\n\nThis cannot be undone.\n\n[y/Enter] Confirm  [n/Esc] Cancel",
+        name, reclaimed
+    )
+}
+
+fn draw_delete_modal(f: &mut Frame, message: &str) {
+    let area = centered_rect_fixed(60, 11, f.area());
 
     let block = Block::default()
         .title(" Delete Confirmation ")
@@ -977,25 +2413,71 @@ fn draw_delete_modal(f: &mut Frame, filename: &str) {
     f.render_widget(text, area);
 }
 
+fn draw_full_disk_modal(f: &mut Frame, path: &std::path::Path) {
+    let area = centered_rect_fixed(MODAL_MAX_WIDTH, 13, f.area());
+
+    let message = format!(
+        "{} is the root of a disk.\n\nScanning an entire disk may take a while and require permissions.\n\n[y/Enter] Scan  [n/Esc] Quit\n\nSet {} to skip this warning.",
+        path.display(),
+        SUPPRESS_ROOT_WARNING_ENV
+    );
+
+    let block = Block::default()
+        .title(" Full Disk Scan ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow))
+        .style(Style::default().bg(Color::Black));
+
+    let text = Paragraph::new(message)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(Color::Yellow));
+
+    f.render_widget(Clear, area);
+    f.render_widget(text, area);
+}
+
 // ============================================================================
 // UTILITIES
 // ============================================================================
 
-fn format_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size = bytes as f64;
-    let mut unit_idx = 0;
-
-    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_idx += 1;
-    }
-
-    if unit_idx == 0 {
-        format!("{} {}", bytes, UNITS[unit_idx])
+/// `width` columns of block characters filled in proportion to `part` of
+/// `total`, in eighths of a column. Empty when `total` is zero.
+fn size_bar(part: u64, total: u64, width: usize) -> String {
+    const PARTIAL: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+    let eighths = if total == 0 {
+        0
     } else {
-        format!("{:.2} {}", size, UNITS[unit_idx])
+        ((part.min(total) as u128 * width as u128 * 8) / total as u128) as usize
+    };
+    let mut bar = "█".repeat(eighths / 8);
+    if eighths / 8 < width {
+        bar.push(PARTIAL[eighths % 8]);
     }
+    let filled = bar.chars().count();
+    bar.push_str(&" ".repeat(width - filled));
+    bar
+}
+
+/// Width of the bars in `share_label`
+const SHARE_BAR_WIDTH: usize = 10;
+
+/// `part` as a share of `total`, e.g. `▕████▌     ▏ 45.2%`. An empty bar and
+/// 0.0% when `total` is zero.
+fn share_label(part: u64, total: u64) -> String {
+    format!("▕{}▏ {:.1}%", size_bar(part, total, SHARE_BAR_WIDTH), percent_of(part, total))
+}
+
+/// `Node::size_by_extension` as `(extension, bytes, files)`, largest first
+fn sorted_extension_totals(root: &Node) -> Vec<(String, u64, u64)> {
+    let mut totals: Vec<(String, u64, u64)> = root
+        .size_by_extension()
+        .into_iter()
+        .map(|(ext, (size, files))| (ext, size, files))
+        .collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    totals
 }
 
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
@@ -1017,3 +2499,525 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+/// A `width` x `height` rect centered in `r`, shrunk to fit if `r` is smaller
+fn centered_rect_fixed(width: u16, height: u16, r: Rect) -> Rect {
+    let width = width.min(r.width);
+    let height = height.min(r.height);
+    Rect {
+        x: r.x + (r.width - width) / 2,
+        y: r.y + (r.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Narrow `area` to at most `max_width` columns, keeping it centered
+fn clamp_width(area: Rect, max_width: u16) -> Rect {
+    if area.width <= max_width {
+        return area;
+    }
+    Rect {
+        x: area.x + (area.width - max_width) / 2,
+        width: max_width,
+        ..area
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_modal_width_is_capped_on_wide_terminals() {
+        let wide = clamp_width(centered_rect(60, 40, Rect::new(0, 0, 300, 50)), MODAL_MAX_WIDTH);
+        assert_eq!(wide.width, MODAL_MAX_WIDTH);
+        assert_eq!(wide.x, (300 - MODAL_MAX_WIDTH) / 2);
+
+        let narrow = centered_rect(60, 40, Rect::new(0, 0, 80, 24));
+        assert_eq!(clamp_width(narrow, MODAL_MAX_WIDTH), narrow);
+    }
+
+    #[test]
+    fn test_centered_rect_fixed() {
+        assert_eq!(centered_rect_fixed(60, 10, Rect::new(0, 0, 100, 30)), Rect::new(20, 10, 60, 10));
+        assert_eq!(centered_rect_fixed(60, 10, Rect::new(5, 2, 61, 11)), Rect::new(5, 2, 60, 10));
+        // Never larger than the area it is placed in
+        assert_eq!(centered_rect_fixed(60, 10, Rect::new(0, 0, 40, 6)), Rect::new(0, 0, 40, 6));
+    }
+
+    #[test]
+    fn test_follow_dominant_drills_and_back_returns() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("opened/big/bigger")).unwrap();
+        std::fs::write(dir.path().join("opened/big/bigger/blob.bin"), vec![0u8; 1000]).unwrap();
+        std::fs::write(dir.path().join("opened/tiny.txt"), vec![0u8; 5]).unwrap();
+        std::fs::write(dir.path().join("top.bin"), vec![0u8; 2000]).unwrap();
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        let opened = root.children.iter().position(|c| c.name == "opened").unwrap();
+
+        // Off: a plain drill into the chosen directory
+        let mut nav = NavigationState::new(root.clone());
+        assert!(nav.drill_down_following(opened, None));
+        assert_eq!(nav.current().name, "opened");
+
+        // On: keeps going through the single big directories
+        let mut nav = NavigationState::new(root);
+        assert!(nav.drill_down_following(opened, Some(0.95)));
+        assert_eq!(nav.current().name, "bigger");
+        assert!(nav.back());
+        assert_eq!(nav.current().name, "opened");
+        assert!(nav.back());
+        assert_eq!(nav.path.len(), 1);
+    }
+
+    #[test]
+    fn test_format_exit_summary() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 1500]).unwrap();
+        std::fs::write(dir.path().join("b.bin"), vec![0u8; 500]).unwrap();
+
+        let mut app = App::new(dir.path().to_path_buf());
+        assert_eq!(
+            format_exit_summary(&app),
+            format!("status=incomplete\npath={}\n", dir.path().display())
+        );
+
+        let (root, report) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        app.state = AppState::ViewingResults(Box::new(root), Box::new(report));
+        let summary = format_exit_summary(&app);
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], "status=complete");
+        assert!(lines.contains(&"total_bytes=2000"));
+        assert!(lines.contains(&"files=2"));
+        assert!(lines.contains(&"skipped=0"));
+    }
+
+    #[test]
+    fn test_stop_scan_shows_results() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 10]).unwrap();
+
+        let mut app = App::new(dir.path().to_path_buf());
+        assert!(!app.stop_scan(), "nothing to stop before a scan starts");
+        app.start_scan();
+        assert!(app.stop_scan());
+        assert!(!app.stop_scan(), "a second q quits instead");
+
+        while !app.scan_done.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        app.poll_scan();
+        assert!(matches!(app.state, AppState::ViewingResults(_, _)));
+        assert!(!app.stop_scan());
+    }
+
+    #[test]
+    fn test_pause_then_stop_scan() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 10]).unwrap();
+
+        let mut app = App::new(dir.path().to_path_buf());
+        assert!(!app.toggle_pause(), "nothing to pause before a scan starts");
+        app.start_scan();
+        assert!(app.toggle_pause());
+        assert!(!app.toggle_pause());
+        assert!(app.toggle_pause());
+
+        // Stopping a paused scan still ends it with partial results
+        assert!(app.stop_scan());
+        while !app.scan_done.load(Ordering::Relaxed) {
+            thread::sleep(Duration::from_millis(5));
+        }
+        app.poll_scan();
+        assert!(matches!(app.state, AppState::ViewingResults(_, _)));
+        assert!(!app.toggle_pause());
+    }
+
+    #[test]
+    fn test_skipped_counter_drains_live_entries() {
+        let dir = tempdir().unwrap();
+        let mut app = App::new(dir.path().to_path_buf());
+        let (tx, rx) = mpsc::channel();
+        app.skipped_rx = Some(rx);
+        let entry = SkippedEntry {
+            path: Some(dir.path().join("locked")),
+            reason: ferris_scan::SkipReason::PermissionDenied,
+            message: "Permission denied".to_string(),
+        };
+        tx.send(entry.clone()).unwrap();
+        tx.send(entry.clone()).unwrap();
+        app.drain_skipped();
+        assert_eq!(app.skipped_live, 2);
+        tx.send(entry).unwrap();
+        app.drain_skipped();
+        app.drain_skipped();
+        assert_eq!(app.skipped_live, 3);
+
+        // A new scan starts counting from zero
+        app.start_scan();
+        assert_eq!(app.skipped_live, 0);
+        app.stop_scan();
+    }
+
+    #[test]
+    fn test_auto_export_after_scan() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("data")).unwrap();
+        std::fs::write(dir.path().join("data/a.bin"), vec![0u8; 100]).unwrap();
+        let output_path = dir.path().join("report.txt");
+
+        let mut app = App::new(dir.path().join("data"));
+        app.auto_export = Some((output_path.clone(), ExportFormat::Tree));
+        let (root, mut report) = Scanner::new()
+            .scan_with_progress(dir.path().join("data"), Arc::new(SharedProgress::default()))
+            .unwrap();
+        app.run_auto_export(&root, &report);
+        assert!(output_path.exists());
+        assert!(app.auto_export_error.is_none());
+
+        // Partial results are never exported
+        std::fs::remove_file(&output_path).unwrap();
+        report.root_lost = true;
+        app.run_auto_export(&root, &report);
+        assert!(!output_path.exists());
+        assert!(app.auto_export_error.is_some());
+    }
+
+    #[test]
+    fn test_delete_prompt_shows_reclaimed_space() {
+        let display = SizeDisplay::default();
+        let mut file = Node::new("big.iso".into(), "/data/big.iso".into(), false);
+        file.size = 3 * 1024 * 1024;
+        file.file_count = 1;
+        let prompt = delete_prompt(&file, display);
+        assert!(prompt.contains("big.iso\n(3.00 MB)"), "{prompt}");
+
+        let mut dir = Node::new("cache".into(), "/data/cache".into(), true);
+        dir.size = 2048;
+        dir.allocated_size = 8192;
+        dir.file_count = 12;
+        assert!(delete_prompt(&dir, display).contains("cache\n(2.00 KB in 12 files)"));
+        let allocated = SizeDisplay { mode: SizeMode::Allocated, ..display };
+        assert!(delete_prompt(&dir, allocated).contains("(8.00 KB in 12 files)"));
+        dir.file_count = 1;
+        assert!(delete_prompt(&dir, display).contains("in 1 file)"));
+    }
+
+    #[test]
+    fn test_stale_summary_buckets_by_age() {
+        let day = Duration::from_secs(86_400);
+        let now = SystemTime::now();
+        let mut root = Node::new("root".into(), "/r".into(), true);
+        for (name, size, age_days) in [("ancient.iso", 700, 1000), ("spring.log", 60, 100), ("week.txt", 3, 7)] {
+            let mut file = Node::new(name.into(), Path::new("/r").join(name), false);
+            file.size = size;
+            file.mtime = Some(now - day * age_days);
+            root.children.push(file);
+        }
+        root.children.push(Node::new("unknown".into(), "/r/unknown".into(), false));
+
+        let summary = stale_summary(&root, now);
+        assert_eq!(summary.buckets, [(30, 760, 2), (90, 760, 2), (365, 700, 1)]);
+        let oldest: Vec<(&str, u64)> = summary
+            .oldest
+            .iter()
+            .map(|(p, age)| (p.to_str().unwrap(), *age))
+            .collect();
+        assert_eq!(oldest, [("/r/ancient.iso", 1000), ("/r/spring.log", 100), ("/r/week.txt", 7)]);
+    }
+
+    #[test]
+    fn test_size_bar() {
+        assert_eq!(size_bar(50, 100, 4), "██  ");
+        assert_eq!(size_bar(100, 100, 4), "████");
+        assert_eq!(size_bar(0, 100, 4), "    ");
+        assert_eq!(size_bar(1, 8, 1), "▏");
+        assert_eq!(size_bar(3, 16, 2), "▍ ");
+        // Empty directory: nothing to compare against
+        assert_eq!(size_bar(0, 0, 3), "   ");
+        assert!(size_bar(7, 9, 10).chars().count() == 10);
+    }
+
+    #[test]
+    fn test_share_label() {
+        assert_eq!(share_label(42, 100), "▕████▏     ▏ 42.0%");
+        assert_eq!(share_label(100, 100), "▕██████████▏ 100.0%");
+        // Empty parent or empty scan: no division by zero
+        assert_eq!(share_label(0, 0), "▕          ▏ 0.0%");
+    }
+
+    #[test]
+    fn test_format_remaining() {
+        assert_eq!(format_remaining(Duration::from_millis(45_900)), "45s");
+        assert_eq!(format_remaining(Duration::from_secs(200)), "3m 20s");
+        assert_eq!(format_remaining(Duration::from_secs(2 * 3600 + 5 * 60 + 59)), "2h 05m");
+    }
+
+    #[test]
+    fn test_minimum_terminal_size() {
+        let min = DEFAULT_MIN_TERMINAL_SIZE;
+        assert!(!is_too_small(Rect::new(0, 0, 80, 24), min));
+        assert!(!is_too_small(Rect::new(0, 0, 200, 60), min));
+        assert!(is_too_small(Rect::new(0, 0, 79, 24), min));
+        assert!(is_too_small(Rect::new(0, 0, 120, 10), min));
+
+        assert_eq!(parse_terminal_size("100x30"), Some((100, 30)));
+        assert_eq!(parse_terminal_size(" 60 X 20 "), Some((60, 20)));
+        assert_eq!(parse_terminal_size("wide"), None);
+        assert_eq!(parse_terminal_size("80x"), None);
+    }
+
+    #[test]
+    fn test_rebuild_keeps_selection_after_deleting_sibling() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("large.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("medium.bin"), vec![0u8; 200]).unwrap();
+        std::fs::write(dir.path().join("small.bin"), vec![0u8; 100]).unwrap();
+
+        let mut root = Scanner::new().scan(dir.path()).unwrap();
+        let mut nav = NavigationState::new(root.clone());
+        nav.selected = 2;
+        assert_eq!(nav.current().children[nav.selected].name, "small.bin");
+
+        root.delete_node(&dir.path().join("large.bin")).unwrap();
+        nav.rebuild_from_root(&root);
+
+        assert_eq!(nav.selected, 1);
+        assert_eq!(nav.current().children[nav.selected].name, "small.bin");
+    }
+
+    #[test]
+    fn test_deletion_keeps_tree_sorted() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("big")).unwrap();
+        std::fs::write(dir.path().join("big/a.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("big/b.bin"), vec![0u8; 250]).unwrap();
+        std::fs::write(dir.path().join("other.bin"), vec![0u8; 400]).unwrap();
+
+        let (root, report) = Scanner::new().scan_with_progress(dir.path(), Arc::default()).unwrap();
+        let mut app = App::new(dir.path().to_path_buf());
+        app.show_results(root, report);
+        app.pending_deletion = Some(Node::new("a.bin".into(), dir.path().join("big/a.bin"), false));
+        app.confirm_deletion();
+
+        // big shrank below other.bin and moves down
+        let nav = app.navigation.as_ref().unwrap();
+        let names: Vec<&str> = nav.current().children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["other.bin", "big"]);
+    }
+
+    #[test]
+    fn test_flat_view_caps_and_sorts_files() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/b.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 200]).unwrap();
+        std::fs::write(dir.path().join("c.bin"), vec![0u8; 100]).unwrap();
+        let root = Scanner::new().scan(dir.path()).unwrap();
+
+        let (files, omitted) = flatten_files(&root, 2, SizeMode::Apparent);
+        assert_eq!(omitted, 1);
+        assert_eq!(files, [(dir.path().join("sub/b.bin"), 300), (dir.path().join("a.bin"), 200)]);
+
+        let mut flat = FlatView::new(&root, SizeMode::Apparent);
+        assert_eq!(flat.files.len(), 3);
+        flat.toggle_sort();
+        let names: Vec<_> = flat.files.iter().map(|(p, _)| p.strip_prefix(dir.path()).unwrap()).collect();
+        assert_eq!(names, [Path::new("a.bin"), Path::new("c.bin"), Path::new("sub/b.bin")]);
+    }
+
+    #[test]
+    fn test_show_top_files_lists_files_across_tree() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("x/y")).unwrap();
+        std::fs::write(dir.path().join("x/y/deep.bin"), vec![0u8; 400]).unwrap();
+        std::fs::write(dir.path().join("top.bin"), vec![0u8; 100]).unwrap();
+
+        let mut app = App::new(dir.path().to_path_buf());
+        app.show_top_files();
+        assert!(app.top_files.is_none());
+
+        let (root, report) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        app.state = AppState::ViewingResults(Box::new(root), Box::new(report));
+        app.show_top_files();
+        assert_eq!(
+            app.top_files.as_deref(),
+            Some(&[(dir.path().join("x/y/deep.bin"), 400), (dir.path().join("top.bin"), 100)][..])
+        );
+    }
+
+    #[test]
+    fn test_sorted_extension_totals() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("b.TXT"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("c.mp4"), vec![0u8; 15]).unwrap();
+        std::fs::write(dir.path().join("LICENSE"), vec![0u8; 15]).unwrap();
+        let root = Scanner::new().scan(dir.path()).unwrap();
+
+        assert_eq!(
+            sorted_extension_totals(&root),
+            [("txt".to_string(), 20, 2), (String::new(), 15, 1), ("mp4".to_string(), 15, 1)]
+        );
+    }
+
+    #[test]
+    fn test_tree_sort_cycles_and_reverses() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("docs/a.txt"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("docs/b.txt"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("zebra.bin"), vec![0u8; 300]).unwrap();
+        let (root, report) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+
+        let mut app = App::new(dir.path().to_path_buf());
+        app.state = AppState::ViewingResults(Box::new(root.clone()), Box::new(report));
+        app.navigation = Some(NavigationState::new(root));
+        app.list_state.select(Some(1));
+        let names = |app: &App| {
+            let nav = app.navigation.as_ref().unwrap();
+            nav.current().children.iter().map(|c| c.name.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(names(&app), ["zebra.bin", "docs"]);
+
+        app.tree_sort = app.tree_sort.next_key();
+        app.apply_tree_sort();
+        assert_eq!(app.tree_sort, TreeSort { key: SortKey::Name, ascending: true });
+        assert_eq!(names(&app), ["docs", "zebra.bin"]);
+        assert_eq!(app.list_state.selected(), Some(0));
+
+        app.tree_sort = app.tree_sort.next_key();
+        app.apply_tree_sort();
+        assert_eq!(app.tree_sort.key, SortKey::FileCount);
+        assert_eq!(names(&app), ["docs", "zebra.bin"]);
+
+        app.tree_sort = app.tree_sort.reversed();
+        app.apply_tree_sort();
+        assert_eq!(names(&app), ["zebra.bin", "docs"]);
+
+        app.tree_sort = app.tree_sort.next_key();
+        assert_eq!(app.tree_sort, TreeSort::default());
+    }
+
+    #[test]
+    fn test_search_selects_first_match() {
+        let mut root = Node::new("root".into(), "/r".into(), true);
+        for name in ["Cargo.toml", "src", "README.md", "cargo.lock"] {
+            root.children.push(Node::new(name.into(), Path::new("/r").join(name), false));
+        }
+        assert_eq!(search_matches(&root, "CARGO"), [0, 3]);
+        assert_eq!(search_matches(&root, ""), [0, 1, 2, 3]);
+        assert!(search_matches(&root, "nothing").is_empty());
+
+        let mut app = App::new("/r".into());
+        app.navigation = Some(NavigationState::new(root));
+        app.list_state.select(Some(1));
+        app.start_search();
+        app.search_query.as_mut().unwrap().push_str("read");
+        app.finish_search();
+        assert_eq!(app.search_query, None);
+        assert_eq!(app.list_state.selected(), Some(2));
+
+        // No match keeps the selection
+        app.search_query = Some("zzz".into());
+        app.finish_search();
+        assert_eq!(app.list_state.selected(), Some(2));
+    }
+
+    #[test]
+    fn test_global_search_opens_match_directory() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/deep")).unwrap();
+        std::fs::write(dir.path().join("a/deep/Report-1.txt"), vec![0u8; 30]).unwrap();
+        std::fs::write(dir.path().join("a/deep/notes.txt"), vec![0u8; 20]).unwrap();
+        std::fs::write(dir.path().join("a/report-2.txt"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("big.bin"), vec![0u8; 100]).unwrap();
+        let (root, report) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+
+        let (results, total) = search_tree(&root, "REPORT", 1);
+        assert_eq!((results.len(), total), (1, 2));
+        assert_eq!(search_tree(&root, "", 10), (Vec::new(), 0));
+
+        let mut app = App::new(dir.path().to_path_buf());
+        app.state = AppState::ViewingResults(Box::new(root.clone()), Box::new(report));
+        app.navigation = Some(NavigationState::new(root));
+        app.toggle_global_search();
+        app.global_search.as_mut().unwrap().query = "report-1".into();
+        app.update_global_search();
+        let search = app.global_search.as_ref().unwrap();
+        assert_eq!(search.results, [(dir.path().join("a/deep/Report-1.txt"), 30)]);
+        assert_eq!(search.unreadable, 0);
+
+        app.open_global_search_result();
+        assert!(app.global_search.is_none());
+        let nav = app.navigation.as_ref().unwrap();
+        assert_eq!(nav.current().path, dir.path().join("a/deep"));
+        assert_eq!(nav.breadcrumb().matches(" / ").count(), 2);
+        let selected = app.list_state.selected().unwrap();
+        assert_eq!(nav.current().children[selected].name, "Report-1.txt");
+
+        // A path no longer in the tree leaves the navigation alone
+        let root = nav.path[0].clone();
+        let mut nav = NavigationState::new(root.clone());
+        assert!(!nav.reveal(&root, &dir.path().join("gone.txt")));
+        assert_eq!(nav.path.len(), 1);
+    }
+
+    #[test]
+    fn test_rebuild_clamps_selection_when_selected_item_deleted() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("large.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("small.bin"), vec![0u8; 100]).unwrap();
+
+        let mut root = Scanner::new().scan(dir.path()).unwrap();
+        let mut nav = NavigationState::new(root.clone());
+        nav.selected = 1;
+
+        root.delete_node(&dir.path().join("small.bin")).unwrap();
+        nav.rebuild_from_root(&root);
+
+        assert_eq!(nav.selected, 0);
+        assert_eq!(nav.current().children[nav.selected].name, "large.bin");
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_load_saved_scan_shows_results() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/a.bin"), vec![0u8; 400]).unwrap();
+        let scanner = Scanner::new();
+        let (root, report) = scanner
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        let saved = dir.path().join("home.scan");
+        scanner.save_scan(&root, &report, &saved).unwrap();
+
+        let (root, report) = load_saved_scan(&saved).unwrap();
+        let mut app = App::new(root.path.clone());
+        app.show_results(root, report);
+        assert!(matches!(app.state, AppState::ViewingResults(_, _)));
+        let nav = app.navigation.as_ref().unwrap();
+        assert_eq!(nav.current().size, 400);
+        assert_eq!(nav.current().children[0].name, "sub");
+
+        assert!(load_saved_scan(&dir.path().join("missing.scan")).is_err());
+        std::fs::write(&saved, b"garbage").unwrap();
+        assert!(load_saved_scan(&saved).is_err());
+    }
+}
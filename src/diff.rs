@@ -0,0 +1,320 @@
+//! Comparison between two scans of the same directory
+//!
+//! [`diff`] pairs up the nodes of an older and a newer scan by their path
+//! relative to the scanned root and records how each entry's size moved.
+//!
+//! ```rust
+//! use ferris_scan::{diff::diff, Scanner};
+//! use std::path::Path;
+//!
+//! let scanner = Scanner::new();
+//! let before = scanner.scan(Path::new(".")).unwrap();
+//! let after = scanner.scan(Path::new(".")).unwrap();
+//! let changes = diff(&before, &after);
+//! println!("+{} / -{} bytes", changes.summary().bytes_gained, changes.summary().bytes_lost);
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::Node;
+
+/// How an entry differs between the two scans
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Only present in the new scan
+    Added,
+    /// Only present in the old scan
+    Removed,
+    /// Present in both, but the size or (for directories) the contents differ
+    Changed,
+    /// Present in both with the same size and contents
+    Unchanged,
+}
+
+/// One entry of a [`DiffTree`]
+#[derive(Debug, Clone)]
+pub struct DiffNode {
+    pub name: String,
+    /// Path relative to the scanned root. Empty for the root itself.
+    pub relative_path: PathBuf,
+    pub is_dir: bool,
+    pub kind: ChangeKind,
+    /// Apparent size in the old scan, 0 if the entry was added
+    pub old_size: u64,
+    /// Apparent size in the new scan, 0 if the entry was removed
+    pub new_size: u64,
+    /// Sorted by delta, largest growth first
+    pub children: Vec<DiffNode>,
+}
+
+impl DiffNode {
+    /// Size change in bytes; negative when the entry shrank
+    pub fn delta(&self) -> i64 {
+        self.new_size as i64 - self.old_size as i64
+    }
+
+    /// Depth-first iterator over this entry and everything below it
+    pub fn iter(&self) -> impl Iterator<Item = &DiffNode> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.children.iter().rev());
+            Some(node)
+        })
+    }
+
+    fn files(&self) -> impl Iterator<Item = &DiffNode> {
+        self.iter().filter(|node| !node.is_dir)
+    }
+}
+
+/// Totals over the files of a [`DiffTree`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DiffSummary {
+    /// Bytes added by new and grown files
+    pub bytes_gained: u64,
+    /// Bytes freed by removed and shrunk files
+    pub bytes_lost: u64,
+    pub files_added: usize,
+    pub files_removed: usize,
+    pub files_changed: usize,
+}
+
+impl DiffSummary {
+    /// `bytes_gained - bytes_lost`
+    pub fn net(&self) -> i64 {
+        self.bytes_gained as i64 - self.bytes_lost as i64
+    }
+}
+
+/// Result of [`diff`]
+#[derive(Debug, Clone)]
+pub struct DiffTree {
+    pub root: DiffNode,
+}
+
+impl DiffTree {
+    /// Bytes gained and lost, counted per file so that a directory's growth
+    /// is not counted again for each of its ancestors
+    pub fn summary(&self) -> DiffSummary {
+        let mut summary = DiffSummary::default();
+        for file in self.root.files() {
+            let delta = file.delta();
+            if delta > 0 {
+                summary.bytes_gained += delta as u64;
+            } else {
+                summary.bytes_lost += delta.unsigned_abs();
+            }
+            match file.kind {
+                ChangeKind::Added => summary.files_added += 1,
+                ChangeKind::Removed => summary.files_removed += 1,
+                ChangeKind::Changed => summary.files_changed += 1,
+                ChangeKind::Unchanged => {}
+            }
+        }
+        summary
+    }
+
+    /// The `n` files that grew the most (including new files), largest first
+    pub fn top_growers(&self, n: usize) -> Vec<&DiffNode> {
+        let mut growers: Vec<&DiffNode> =
+            self.root.files().filter(|file| file.delta() > 0).collect();
+        growers.sort_by_key(|file| std::cmp::Reverse(file.delta()));
+        growers.truncate(n);
+        growers
+    }
+
+    /// Looks up an entry by its path relative to the scanned root
+    pub fn find(&self, relative_path: &Path) -> Option<&DiffNode> {
+        self.root.iter().find(|node| node.relative_path == relative_path)
+    }
+}
+
+/// Compares two scans of the same directory.
+///
+/// The roots are always paired with each other; below them, entries are
+/// matched by their path relative to the root, so the two scans may have been
+/// taken from different mount points. Sizes are apparent sizes.
+///
+/// A path that is a file in one scan and a directory in the other is listed
+/// twice, as `Removed` and as `Added`. The summary entries of directories
+/// folded by `min_dir_size` are left out; their bytes only show in their
+/// parents' sizes.
+pub fn diff(old: &Node, new: &Node) -> DiffTree {
+    DiffTree {
+        root: diff_entry(Some(old), Some(new), PathBuf::new()),
+    }
+}
+
+fn diff_entry(old: Option<&Node>, new: Option<&Node>, relative_path: PathBuf) -> DiffNode {
+    let old_children: &[Node] = old.map_or(&[], |node| &node.children);
+    let new_children: &[Node] = new.map_or(&[], |node| &node.children);
+
+    let mut unmatched: HashMap<&str, &Node> = old_children
+        .iter()
+        .filter(|child| !child.is_summary)
+        .map(|child| (child.name.as_str(), child))
+        .collect();
+    let mut children: Vec<DiffNode> = new_children
+        .iter()
+        .filter(|child| !child.is_summary)
+        .map(|child| {
+            // An entry that changed type stays unmatched and is listed as removed
+            let previous = match unmatched.get(child.name.as_str()) {
+                Some(previous) if previous.is_dir == child.is_dir => unmatched.remove(child.name.as_str()),
+                _ => None,
+            };
+            diff_entry(previous, Some(child), relative_path.join(&child.name))
+        })
+        .collect();
+    // Iterate the old list rather than the map to keep the order stable
+    children.extend(
+        old_children
+            .iter()
+            .filter(|child| unmatched.contains_key(child.name.as_str()))
+            .map(|child| diff_entry(Some(child), None, relative_path.join(&child.name))),
+    );
+    children.sort_by(|a, b| b.delta().cmp(&a.delta()).then_with(|| a.name.cmp(&b.name)));
+
+    let (name, is_dir) = match (new, old) {
+        (Some(node), _) | (None, Some(node)) => (node.name.clone(), node.is_dir),
+        (None, None) => unreachable!("diff_entry needs at least one side"),
+    };
+    let old_size = old.map_or(0, |node| node.size);
+    let new_size = new.map_or(0, |node| node.size);
+    let kind = match (old, new) {
+        (None, _) => ChangeKind::Added,
+        (_, None) => ChangeKind::Removed,
+        _ if old_size != new_size || children.iter().any(|child| child.kind != ChangeKind::Unchanged) => {
+            ChangeKind::Changed
+        }
+        _ => ChangeKind::Unchanged,
+    };
+
+    DiffNode {
+        name,
+        relative_path,
+        is_dir,
+        kind,
+        old_size,
+        new_size,
+        children,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scanner;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_diff_added_removed_and_resized() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("logs")).unwrap();
+        std::fs::write(dir.path().join("logs/app.log"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("old.bin"), vec![0u8; 40]).unwrap();
+        std::fs::write(dir.path().join("same.txt"), vec![0u8; 10]).unwrap();
+        let before = Scanner::new().scan(dir.path()).unwrap();
+
+        std::fs::write(dir.path().join("logs/app.log"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("logs/new.log"), vec![0u8; 50]).unwrap();
+        std::fs::remove_file(dir.path().join("old.bin")).unwrap();
+        let after = Scanner::new().scan(dir.path()).unwrap();
+
+        let changes = diff(&before, &after);
+        assert_eq!(changes.root.kind, ChangeKind::Changed);
+        assert_eq!(changes.root.delta(), 200 + 50 - 40);
+
+        let resized = changes.find(Path::new("logs/app.log")).unwrap();
+        assert_eq!(resized.kind, ChangeKind::Changed);
+        assert_eq!((resized.old_size, resized.new_size), (100, 300));
+        assert_eq!(
+            changes.find(Path::new("logs/new.log")).unwrap().kind,
+            ChangeKind::Added
+        );
+        let removed = changes.find(Path::new("old.bin")).unwrap();
+        assert_eq!(removed.kind, ChangeKind::Removed);
+        assert_eq!(removed.delta(), -40);
+        assert_eq!(
+            changes.find(Path::new("same.txt")).unwrap().kind,
+            ChangeKind::Unchanged
+        );
+
+        let summary = changes.summary();
+        assert_eq!(summary.bytes_gained, 250);
+        assert_eq!(summary.bytes_lost, 40);
+        assert_eq!(summary.net(), 210);
+        assert_eq!(
+            (summary.files_added, summary.files_removed, summary.files_changed),
+            (1, 1, 1)
+        );
+
+        let growers: Vec<&Path> = changes
+            .top_growers(5)
+            .iter()
+            .map(|node| node.relative_path.as_path())
+            .collect();
+        assert_eq!(growers, vec![Path::new("logs/app.log"), Path::new("logs/new.log")]);
+        assert_eq!(changes.top_growers(1).len(), 1);
+    }
+
+    #[test]
+    fn test_diff_identical_scans_is_unchanged() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/a.txt"), vec![0u8; 20]).unwrap();
+        let scan = Scanner::new().scan(dir.path()).unwrap();
+
+        let changes = diff(&scan, &scan);
+        assert!(changes.root.iter().all(|node| node.kind == ChangeKind::Unchanged));
+        assert_eq!(changes.summary(), DiffSummary::default());
+        assert!(changes.top_growers(3).is_empty());
+    }
+
+    #[test]
+    fn test_diff_type_change_is_removal_and_addition() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("thing"), vec![0u8; 40]).unwrap();
+        let before = Scanner::new().scan(dir.path()).unwrap();
+
+        std::fs::remove_file(dir.path().join("thing")).unwrap();
+        std::fs::create_dir(dir.path().join("thing")).unwrap();
+        std::fs::write(dir.path().join("thing/inner.bin"), vec![0u8; 100]).unwrap();
+        let after = Scanner::new().scan(dir.path()).unwrap();
+
+        let changes = diff(&before, &after);
+        let kinds: Vec<(ChangeKind, bool)> = changes
+            .root
+            .children
+            .iter()
+            .map(|node| (node.kind, node.is_dir))
+            .collect();
+        assert_eq!(kinds, [(ChangeKind::Added, true), (ChangeKind::Removed, false)]);
+
+        let summary = changes.summary();
+        assert_eq!((summary.bytes_gained, summary.bytes_lost), (100, 40));
+        assert_eq!((summary.files_added, summary.files_removed), (1, 1));
+    }
+
+    #[test]
+    fn test_diff_skips_folded_summaries() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("a")).unwrap();
+        std::fs::write(dir.path().join("a/f.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("big.bin"), vec![0u8; 5000]).unwrap();
+        let scanner = Scanner::new().with_min_dir_size(1000);
+        let before = scanner.scan(dir.path()).unwrap();
+
+        std::fs::create_dir(dir.path().join("b")).unwrap();
+        std::fs::write(dir.path().join("b/g.bin"), vec![0u8; 20]).unwrap();
+        let after = scanner.scan(dir.path()).unwrap();
+        assert!(after.children.iter().any(|child| child.is_summary));
+
+        let changes = diff(&before, &after);
+        assert_eq!(changes.root.children.len(), 1);
+        assert_eq!(changes.root.delta(), 20);
+        assert_eq!(changes.summary(), DiffSummary::default());
+    }
+}
@@ -18,16 +18,44 @@
 //! let scanner = Scanner::new();
 //! let result = scanner.scan(Path::new("."));
 //! ```
+//!
+//! # Logging
+//!
+//! The scanner emits diagnostics through the [`log`](https://docs.rs/log) facade.
+//! Nothing is printed unless the embedding application installs a logger.
+//!
+//! | Level   | Events                                                    |
+//! |---------|-----------------------------------------------------------|
+//! | `info`  | Scan start (root path) and scan end (files, bytes, time)  |
+//! | `info`  | Rescan start and end, once per `Scanner::rescan`          |
+//! | `info`  | Scan cancelled through a cancel token                     |
+//! | `info`  | Scan paused and resumed through a pause flag              |
+//! | `info`  | Overlay filesystems mounted under the scan root           |
+//! | `warn`  | Entries skipped due to permission or network errors       |
+//! | `warn`  | Scan root disappeared mid-scan                            |
+//! | `warn`  | Unreadable or invalid `.gitignore` files                  |
+//! | `debug` | Phase transitions (walk finished, size rollup, sorting)   |
+//! | `debug` | Entries skipped by excludes, `.gitignore` or mount points |
+//! | `debug` | Symlinks, special files, repeat hardlinks, slow metadata  |
+//! | `debug` | Directories found changed during an incremental rescan    |
+//! | `trace` | Partial top-level results published to shared progress    |
 //! 
 
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
-use std::sync::{atomic::AtomicU64, atomic::Ordering, mpsc, Arc, Mutex};
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
-use jwalk::WalkDir;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use jwalk::WalkDirGeneric;
 
 #[cfg(feature = "pro")]
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+pub mod diff;
 
 // ============================================================================
 // TYPES
@@ -35,20 +63,220 @@ use serde::Serialize;
 
 /// Represents a file or directory node in the filesystem tree
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "pro", derive(Serialize))]
+#[cfg_attr(feature = "pro", derive(Serialize, Deserialize))]
 pub struct Node {
     pub name: String,
     pub size: u64,
+    /// Space this subtree occupies on disk, rounded up to whole blocks (like
+    /// `du`). Equal to `size` on platforms without block counts.
+    pub allocated_size: u64,
     pub is_dir: bool,
-    #[cfg_attr(feature = "pro", serde(skip_serializing_if = "Vec::is_empty"))]
+    /// Symbolic link, junction, or other reparse point. Not descended into
+    /// unless link following is enabled.
+    pub is_symlink: bool,
+    /// Number of files in this subtree (1 for a file, 0 for an empty directory)
+    pub file_count: u64,
+    /// The directory could not be listed because access was denied, so its
+    /// contents are missing from the totals. Also recorded in
+    /// `ScanReport::skipped`.
+    #[cfg_attr(feature = "pro", serde(default, skip_serializing_if = "std::ops::Not::not"))]
+    pub access_denied: bool,
+    /// Path and size of the largest file anywhere under this directory.
+    /// Only filled in when `ScanConfig::track_largest_file` is set.
+    #[cfg_attr(feature = "pro", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub largest_file: Option<(PathBuf, u64)>,
+    /// Placeholder for the directories folded away by
+    /// `ScanConfig::min_dir_size`. Its totals and `file_count` are theirs,
+    /// but it is not a file, so `iter_files` and everything built on it
+    /// leave it out.
+    #[cfg_attr(feature = "pro", serde(default, skip_serializing_if = "std::ops::Not::not"))]
+    pub is_summary: bool,
+    #[cfg_attr(feature = "pro", serde(default, skip_serializing_if = "Vec::is_empty"))]
     pub children: Vec<Node>,
     pub path: PathBuf,
+    /// Modification time read during the scan, used by `Scanner::rescan` to
+    /// tell which directories changed. Not exported.
+    #[cfg_attr(feature = "pro", serde(skip))]
+    pub mtime: Option<SystemTime>,
+}
+
+/// Which size metric to report for a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeMode {
+    /// Logical file length, as shown by `ls -l`
+    #[default]
+    Apparent,
+    /// Space allocated on disk, as shown by `du`
+    Allocated,
+}
+
+/// What `Node::sort_by` orders siblings by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// Apparent size (`Node::size`)
+    #[default]
+    Size,
+    /// On-disk size (`Node::allocated_size`)
+    AllocatedSize,
+    /// Name, ignoring case
+    Name,
+    /// Files in the subtree (`Node::file_count`)
+    FileCount,
+}
+
+/// What the scanner does with symbolic links
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// List each link as a zero-size leaf and record it in
+    /// `ScanReport::symlinks_skipped`. Nothing is counted twice.
+    #[default]
+    Skip,
+    /// Count links to files at their target's size and descend into links
+    /// to directories. A directory reachable through several paths (cycles
+    /// included) is only descended the first time it is reached; later links
+    /// to it become empty leaves listed in `ScanReport::symlinks_skipped`.
+    /// Outside Unix only loops back to an ancestor are caught.
+    Follow,
+    /// List each link as a leaf sized by the link itself (the length of the
+    /// target path), without following it.
+    CountAsFile,
+}
+
+/// Order of a flat file list from `flatten_files`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlatSort {
+    /// Largest first, ties by path
+    #[default]
+    Size,
+    /// By full path
+    Path,
+}
+
+/// How frontends render a size
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeFormat {
+    /// Largest fitting unit with two decimals, e.g. `1.50 MB`
+    #[default]
+    Auto,
+    /// Exact byte count with thousands separators, e.g. `1,572,864 B`
+    Bytes,
+}
+
+/// Unit system for `format_size_with_units`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeUnits {
+    /// Powers of 1024 with IEC labels: `KiB`, `MiB`, `GiB`, `TiB`
+    #[default]
+    Binary,
+    /// Powers of 1000 with SI labels: `KB`, `MB`, `GB`, `TB`, as drive
+    /// manufacturers and macOS count
+    Decimal,
 }
 
+/// Broad kind of file, derived from its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FileCategory {
+    Image,
+    Video,
+    Audio,
+    Document,
+    Archive,
+    Code,
+    /// Unknown extension or no extension
+    Other,
+}
+
+/// Built-in extension to category map used by `FileCategory::from_extension`.
+/// Extensions are lowercase and without the leading dot.
+pub const EXTENSION_CATEGORIES: &[(&str, FileCategory)] = &[
+    ("jpg", FileCategory::Image),
+    ("jpeg", FileCategory::Image),
+    ("png", FileCategory::Image),
+    ("gif", FileCategory::Image),
+    ("bmp", FileCategory::Image),
+    ("webp", FileCategory::Image),
+    ("svg", FileCategory::Image),
+    ("tif", FileCategory::Image),
+    ("tiff", FileCategory::Image),
+    ("heic", FileCategory::Image),
+    ("raw", FileCategory::Image),
+    ("psd", FileCategory::Image),
+    ("mp4", FileCategory::Video),
+    ("mkv", FileCategory::Video),
+    ("avi", FileCategory::Video),
+    ("mov", FileCategory::Video),
+    ("wmv", FileCategory::Video),
+    ("webm", FileCategory::Video),
+    ("m4v", FileCategory::Video),
+    ("flv", FileCategory::Video),
+    ("mp3", FileCategory::Audio),
+    ("wav", FileCategory::Audio),
+    ("flac", FileCategory::Audio),
+    ("aac", FileCategory::Audio),
+    ("ogg", FileCategory::Audio),
+    ("m4a", FileCategory::Audio),
+    ("wma", FileCategory::Audio),
+    ("opus", FileCategory::Audio),
+    ("pdf", FileCategory::Document),
+    ("doc", FileCategory::Document),
+    ("docx", FileCategory::Document),
+    ("xls", FileCategory::Document),
+    ("xlsx", FileCategory::Document),
+    ("ppt", FileCategory::Document),
+    ("pptx", FileCategory::Document),
+    ("odt", FileCategory::Document),
+    ("ods", FileCategory::Document),
+    ("txt", FileCategory::Document),
+    ("md", FileCategory::Document),
+    ("rtf", FileCategory::Document),
+    ("epub", FileCategory::Document),
+    ("csv", FileCategory::Document),
+    ("zip", FileCategory::Archive),
+    ("tar", FileCategory::Archive),
+    ("gz", FileCategory::Archive),
+    ("tgz", FileCategory::Archive),
+    ("bz2", FileCategory::Archive),
+    ("xz", FileCategory::Archive),
+    ("zst", FileCategory::Archive),
+    ("7z", FileCategory::Archive),
+    ("rar", FileCategory::Archive),
+    ("iso", FileCategory::Archive),
+    ("dmg", FileCategory::Archive),
+    ("rs", FileCategory::Code),
+    ("c", FileCategory::Code),
+    ("h", FileCategory::Code),
+    ("cpp", FileCategory::Code),
+    ("hpp", FileCategory::Code),
+    ("cs", FileCategory::Code),
+    ("go", FileCategory::Code),
+    ("java", FileCategory::Code),
+    ("kt", FileCategory::Code),
+    ("py", FileCategory::Code),
+    ("js", FileCategory::Code),
+    ("ts", FileCategory::Code),
+    ("rb", FileCategory::Code),
+    ("php", FileCategory::Code),
+    ("swift", FileCategory::Code),
+    ("sh", FileCategory::Code),
+    ("ps1", FileCategory::Code),
+    ("html", FileCategory::Code),
+    ("css", FileCategory::Code),
+    ("json", FileCategory::Code),
+    ("toml", FileCategory::Code),
+    ("yaml", FileCategory::Code),
+    ("yml", FileCategory::Code),
+    ("xml", FileCategory::Code),
+    ("sql", FileCategory::Code),
+];
+
 /// Progress update sent during scanning
 #[derive(Debug, Clone)]
 pub struct ScanProgress {
     pub files_scanned: usize,
+    /// Directories below the root reached before `current_path`
+    pub dirs_scanned: usize,
+    /// Bytes (apparent size) of the files processed before `current_path`
+    pub bytes_scanned: u64,
     pub current_path: PathBuf,
     pub elapsed: std::time::Duration,
 }
@@ -61,29 +289,575 @@ pub struct ScanProgress {
 pub struct SharedProgress {
     /// Number of files processed
     pub files_scanned: AtomicU64,
+    /// Number of directories reached below the root, including those
+    /// walked but not materialized because of `ScanConfig::max_depth`
+    pub dirs_scanned: AtomicU64,
+    /// Bytes (apparent size) of the files processed so far, hardlinks counted
+    /// once when deduplication is on
+    pub bytes_scanned: AtomicU64,
     /// Last path the scanner touched 
     pub last_path: Mutex<Option<PathBuf>>,
+    /// Running totals for the root's immediate children, largest first.
+    ///
+    /// Republished a few times per second so the UI can show where space is
+    /// going before the scan finishes.
+    pub top_level: Mutex<Vec<PartialEntry>>,
+    /// Files the scan is expected to find, from the background count started
+    /// by `ScanConfig::estimate_total`. 0 until that count finishes.
+    pub total_files_estimate: AtomicU64,
+    /// `(time, files_scanned)` samples covering the last `RATE_WINDOW`
+    rate_samples: Mutex<VecDeque<(Instant, u64)>>,
+}
+
+impl SharedProgress {
+    /// Files per second over the last few seconds of the scan, or 0 before
+    /// there are two samples to compare
+    pub fn rate_per_sec(&self) -> f64 {
+        let Ok(samples) = self.rate_samples.lock() else {
+            return 0.0;
+        };
+        match (samples.front(), samples.back()) {
+            (Some(&(t0, files0)), Some(&(t1, files1))) if t1 > t0 => {
+                files1.saturating_sub(files0) as f64 / (t1 - t0).as_secs_f64()
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Estimated time until the scan finishes, from `total_files_estimate`
+    /// and `rate_per_sec`. `None` while either is unknown.
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total_files_estimate.load(Ordering::Relaxed);
+        let rate = self.rate_per_sec();
+        if total == 0 || rate <= 0.0 {
+            return None;
+        }
+        let remaining = total.saturating_sub(self.files_scanned.load(Ordering::Relaxed));
+        Some(Duration::from_secs_f64(remaining as f64 / rate))
+    }
+
+    /// Add a rate sample taken at `at`, dropping those that fell out of the window
+    fn push_rate_sample(&self, at: Instant, files_scanned: u64) {
+        let Ok(mut samples) = self.rate_samples.lock() else {
+            return;
+        };
+        samples.push_back((at, files_scanned));
+        while samples.len() > 2 && at.duration_since(samples[1].0) >= RATE_WINDOW {
+            samples.pop_front();
+        }
+    }
 }
 
-/// Entry that was skipped during scanning (permissions)
+/// Running total for one immediate child of the scan root
 #[derive(Debug, Clone, PartialEq)]
+pub struct PartialEntry {
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// Why an entry was skipped during scanning
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "pro", derive(Serialize, Deserialize))]
+pub enum SkipReason {
+    /// The operating system denied access
+    PermissionDenied,
+    /// A transient network error, e.g. a dropped SMB/UNC share connection
+    Network,
+    /// A socket, FIFO, or block/character device (see
+    /// `ScanConfig::skip_special_files`)
+    SpecialFile,
+    /// The path exceeds the Windows path-length limit and could not be read
+    /// even through an extended-length path (see
+    /// `ScanConfig::retry_long_paths`)
+    PathTooLong,
+}
+
+/// What a scan does when the operating system denies access to an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SkipOrFail {
+    /// Record the entry in `ScanReport::skipped` and keep going
+    #[default]
+    Skip,
+    /// Abort the scan with an error on the first denial, for callers that
+    /// need a complete result
+    Fail,
+}
+
+/// Entry that was skipped during scanning
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "pro", derive(Serialize, Deserialize))]
 pub struct SkippedEntry {
     pub path: Option<PathBuf>,
+    pub reason: SkipReason,
     pub message: String,
 }
 
+/// Depth-first iterator over a `Node` tree, created by `Node::iter`
+pub struct NodeIter<'a> {
+    stack: Vec<&'a Node>,
+}
+
+/// Event emitted by `Node::stream_events`
+#[derive(Debug, Clone, Copy)]
+pub enum TreeEvent<'a> {
+    /// A node is visited; its children (if any) follow before the matching `Leave`
+    Enter(&'a Node),
+    /// All of the node's children have been visited
+    Leave(&'a Node),
+}
+
 /// Additional information gathered during a scan.
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "pro", derive(Serialize, Deserialize), serde(default))]
 pub struct ScanReport {
     pub skipped: Vec<SkippedEntry>,
+    /// Windows junctions and other reparse points found during the walk.
+    /// Always empty on other platforms.
+    pub reparse_points: Vec<PathBuf>,
+    /// Directories not descended into because they are on another filesystem
+    /// (only populated when `ScanConfig::one_filesystem` is set).
+    pub mount_points: Vec<PathBuf>,
+    /// Directories matched by `ScanConfig::excludes` and not descended into.
+    /// Excluded files are left out without being listed.
+    pub excluded: Vec<PathBuf>,
+    /// Files and directories left out because a `.gitignore` ignores them
+    /// (only counted when `ScanConfig::respect_gitignore` is set). An ignored
+    /// directory counts once, whatever it contains.
+    pub gitignored: u64,
+    /// Overlay filesystem mounts at, above, or below the scan root (Linux only).
+    ///
+    /// Sizes under an overlay are the merged view: files shadowed in lower
+    /// layers are not counted, and files shared between containers built from
+    /// the same image are counted once per container. Totals therefore may not
+    /// match the disk usage of the backing layers.
+    pub overlay_mounts: Vec<PathBuf>,
+    /// Files counted as size 0 because their inode was already seen
+    /// (only populated when `ScanConfig::dedup_hardlinks` is set).
+    pub hardlinks_deduplicated: u64,
+    /// Bytes not double counted thanks to hardlink deduplication
+    pub hardlink_bytes_saved: u64,
+    /// The scan was stopped early through a cancel token; the tree is partial.
+    pub cancelled: bool,
+    /// Wall-clock time from the start of the walk until the finished tree
+    /// (rolled up and sorted) was ready.
+    pub elapsed: Duration,
+    /// The scan root disappeared mid-scan (deleted, unmounted, or a dropped
+    /// network share), so the walk was stopped and the tree is partial.
+    pub root_lost: bool,
+    /// Symlinks (and Windows reparse points) left out because
+    /// `ScanConfig::exclude_symlinks` is set.
+    pub symlinks_excluded: u64,
+    /// Symlinks listed without being counted: every link under
+    /// `SymlinkPolicy::Skip`, and links whose target was already walked
+    /// under `SymlinkPolicy::Follow`.
+    pub symlinks_skipped: Vec<PathBuf>,
+    /// Paths that hit the Windows path-length limit, whether or not a retry
+    /// recovered them. Unrecovered ones are also in `skipped`. Always empty on
+    /// other platforms.
+    pub long_paths: Vec<PathBuf>,
+    /// Space the filesystem reports as used (what `df` shows) minus the
+    /// scanned allocated total, when `ScanConfig::measure_unaccounted` is
+    /// set. `None` if not requested, if the root is not a filesystem root
+    /// (the filesystem's usage would include everything outside the scan),
+    /// if the scan was partial, or if the platform query failed.
+    ///
+    /// A positive value is space the walk never saw:
+    /// - filesystem metadata and journal blocks
+    /// - files deleted while a process still holds them open
+    /// - directories skipped for permissions (see `skipped`)
+    /// - snapshots, reserved or hidden areas
+    ///
+    /// A negative value means the walk counted more than the filesystem
+    /// holds, typically hardlinks counted once per link (see
+    /// `ScanConfig::dedup_hardlinks`) or other filesystems mounted below
+    /// the root (see `ScanConfig::one_filesystem`).
+    pub unaccounted_bytes: Option<i64>,
+    /// Entries whose metadata read took at least
+    /// `ScanConfig::slow_entry_threshold`, with how long it took.
+    #[cfg(feature = "profile")]
+    pub slow_entries: Vec<(PathBuf, Duration)>,
+}
+
+/// Options for tabular exports such as CSV
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Write at most this many node rows (header excluded).
+    ///
+    /// When the tree is larger, the largest entries are kept: nodes are picked
+    /// best-first by size starting from the root, so every kept row's parent is
+    /// also kept. Rows are still written in depth-first order with children by
+    /// descending size, followed by one summary row for what was left out.
+    pub max_rows: Option<usize>,
+    /// Add a "File Count" column with the number of files under each row
+    /// (see `Node::file_count`).
+    pub include_file_count: bool,
+    /// Add an "Avg File Size (bytes)" column (see
+    /// `Node::average_file_size`). Empty for rows without files.
+    pub include_average_file_size: bool,
+    /// Write only file rows. Directories are still walked, and their files
+    /// keep their full relative paths.
+    pub files_only: bool,
+    /// Add a "Size" column with the size in human-readable units (`4.20 MB`)
+    /// next to the byte count.
+    pub include_human_size: bool,
+    /// Add a "Depth" column: 0 for the scan root, 1 for its children, and so on.
+    pub include_depth: bool,
+}
+
+/// Options for `Scanner::export_json_with_options`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonOptions {
+    /// Write the whole tree on one line instead of indenting it. Much
+    /// smaller for large trees, but not meant to be read by people.
+    pub compact: bool,
+}
+
+/// Options for `Scanner::export_tree_text_with_options`
+#[derive(Debug, Clone, Copy)]
+pub struct TreeTextOptions {
+    /// Print at most this many levels below the root (`Some(0)` prints only
+    /// the root line). `None` prints the whole tree.
+    pub max_depth: Option<usize>,
+    /// Columns per level, connector included. Values below 2 are treated
+    /// as 2.
+    pub indent_width: usize,
+}
+
+impl Default for TreeTextOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: None,
+            indent_width: 4,
+        }
+    }
+}
+
+/// Options for `Scanner::export_du_style`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DuStyleOptions {
+    /// Print sizes like `du -h` (`4.0K`, `12M`) instead of 1024-byte blocks
+    pub human_readable: bool,
+    /// Line order
+    pub sort: DuSort,
+}
+
+/// Line order of a du-style listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuSort {
+    /// Alphabetical by path, so each directory precedes its contents
+    #[default]
+    Path,
+    /// Largest first, like `du | sort -rn`
+    Size,
+}
+
+/// File format for `Scanner::export` and `ScanConfig::auto_export`
+///
+/// Every format is always listed so command lines parse the same in every
+/// build; exporting one whose feature is disabled fails with
+/// `ExportError::FormatUnavailable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// CSV with default `ExportOptions` (`pro` feature)
+    Csv,
+    /// `du`-style listing with default `DuStyleOptions` (`pro` feature)
+    Du,
+    /// Pretty-printed JSON tree (`pro` feature)
+    Json,
+    /// Indented plain-text tree, see `Scanner::export_tree_text`
+    Tree,
+    /// Summary card, see `Scanner::export_summary_svg` (`svg` feature)
+    Svg,
+}
+
+/// Errors specific to exporting scan results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportError {
+    /// The root has no children, so the export would contain only a header
+    EmptyTree,
+    /// The format's feature was not compiled into this build
+    FormatUnavailable(ExportFormat),
+}
+
+impl SortKey {
+    /// Short lowercase name for status lines
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Size => "size",
+            SortKey::AllocatedSize => "allocated size",
+            SortKey::Name => "name",
+            SortKey::FileCount => "file count",
+        }
+    }
+
+    /// Compare two siblings by this key, smallest first
+    fn compare(self, a: &Node, b: &Node) -> std::cmp::Ordering {
+        match self {
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::AllocatedSize => a.allocated_size.cmp(&b.allocated_size),
+            SortKey::Name => a
+                .name
+                .chars()
+                .flat_map(char::to_lowercase)
+                .cmp(b.name.chars().flat_map(char::to_lowercase)),
+            SortKey::FileCount => a.file_count.cmp(&b.file_count),
+        }
+    }
+}
+
+impl SizeMode {
+    /// Short lowercase name for status lines
+    pub fn label(self) -> &'static str {
+        match self {
+            SizeMode::Apparent => "apparent",
+            SizeMode::Allocated => "allocated",
+        }
+    }
+
+    /// The other mode
+    pub fn toggled(self) -> Self {
+        match self {
+            SizeMode::Apparent => SizeMode::Allocated,
+            SizeMode::Allocated => SizeMode::Apparent,
+        }
+    }
+}
+
+impl FileCategory {
+    /// Category for an extension (without the dot), ignoring case.
+    /// Unknown extensions are `Other`.
+    pub fn from_extension(ext: &str) -> Self {
+        let ext = ext.to_ascii_lowercase();
+        EXTENSION_CATEGORIES
+            .iter()
+            .find(|(known, _)| *known == ext)
+            .map_or(FileCategory::Other, |(_, category)| *category)
+    }
+
+    /// Display name, e.g. "Images"
+    pub fn label(self) -> &'static str {
+        match self {
+            FileCategory::Image => "Images",
+            FileCategory::Video => "Video",
+            FileCategory::Audio => "Audio",
+            FileCategory::Document => "Documents",
+            FileCategory::Archive => "Archives",
+            FileCategory::Code => "Code",
+            FileCategory::Other => "Other",
+        }
+    }
+}
+
+impl FlatSort {
+    /// Short lowercase name for titles and status lines
+    pub fn label(self) -> &'static str {
+        match self {
+            FlatSort::Size => "size",
+            FlatSort::Path => "path",
+        }
+    }
+
+    /// The other order, for switching with a single key
+    pub fn toggled(self) -> Self {
+        match self {
+            FlatSort::Size => FlatSort::Path,
+            FlatSort::Path => FlatSort::Size,
+        }
+    }
+}
+
+impl SizeFormat {
+    /// Short lowercase name for status lines
+    pub fn label(self) -> &'static str {
+        match self {
+            SizeFormat::Auto => "auto",
+            SizeFormat::Bytes => "bytes",
+        }
+    }
+
+    /// The next format, for cycling through them with a single key
+    pub fn next(self) -> Self {
+        match self {
+            SizeFormat::Auto => SizeFormat::Bytes,
+            SizeFormat::Bytes => SizeFormat::Auto,
+        }
+    }
+}
+
+impl ExportFormat {
+    /// Parse a format name as given on the command line: `csv`, `du`,
+    /// `json`, `tree` (or `txt`) and `svg`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "du" => Some(ExportFormat::Du),
+            "json" => Some(ExportFormat::Json),
+            "tree" | "txt" => Some(ExportFormat::Tree),
+            "svg" => Some(ExportFormat::Svg),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from an output path's extension (`.csv`, `.json`,
+    /// `.txt`, `.svg`). A du listing has no conventional extension, so it
+    /// must be named explicitly.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "txt" => Some(ExportFormat::Tree),
+            "svg" => Some(ExportFormat::Svg),
+            _ => None,
+        }
+    }
+
+    /// Short lowercase name, the inverse of `from_name`
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Du => "du",
+            ExportFormat::Json => "json",
+            ExportFormat::Tree => "tree",
+            ExportFormat::Svg => "svg",
+        }
+    }
+
+    /// Cargo feature the format needs, if any
+    fn required_feature(self) -> Option<&'static str> {
+        match self {
+            ExportFormat::Csv | ExportFormat::Du | ExportFormat::Json => Some("pro"),
+            ExportFormat::Tree => None,
+            ExportFormat::Svg => Some("svg"),
+        }
+    }
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::EmptyTree => write!(f, "Nothing to export: the scanned directory is empty"),
+            ExportError::FormatUnavailable(format) => write!(
+                f,
+                "{} export is not available in this build (enable the `{}` feature)",
+                format.name(),
+                format.required_feature().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// Why `Node::delete_node` failed. The tree is left untouched in every case.
+#[derive(Debug)]
+pub enum DeleteError {
+    /// No node below this one has that path
+    NotFound(PathBuf),
+    /// The filesystem refused the removal for lack of permissions
+    PermissionDenied(PathBuf),
+    /// Any other error from the filesystem removal
+    Io { path: PathBuf, source: std::io::Error },
+}
+
+impl DeleteError {
+    fn from_io(path: &Path, source: std::io::Error) -> Self {
+        if source.kind() == std::io::ErrorKind::PermissionDenied {
+            DeleteError::PermissionDenied(path.to_path_buf())
+        } else {
+            DeleteError::Io { path: path.to_path_buf(), source }
+        }
+    }
+}
+
+impl std::fmt::Display for DeleteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeleteError::NotFound(path) => write!(f, "Node not found: {}", path.display()),
+            DeleteError::PermissionDenied(path) => write!(f, "Permission denied: {}", path.display()),
+            DeleteError::Io { path, source } => write!(f, "Could not delete {}: {}", path.display(), source),
+        }
+    }
+}
+
+impl std::error::Error for DeleteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DeleteError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Why a scan failed. Partial results (a cancel token, a root that
+/// disappeared mid-scan) are not errors for the functions that return a
+/// `ScanReport`; check `ScanReport::cancelled` and `ScanReport::root_lost`.
+#[derive(Debug)]
+pub enum ScanError {
+    /// The scan root does not exist
+    RootNotFound(PathBuf),
+    /// The scan root is a file or another non-directory
+    RootNotADirectory(PathBuf),
+    /// Access was denied and `ScanConfig::on_permission_error` is
+    /// `SkipOrFail::Fail`
+    PermissionDenied { path: Option<PathBuf>, message: String },
+    /// One of `ScanConfig::excludes` is not a valid glob
+    InvalidExclude { pattern: String, message: String },
+    /// The scan root could not be read for another reason
+    Io(std::io::Error),
+    /// The directory given to `Scanner::rescan_subtree` is not in the tree
+    NotInTree(PathBuf),
+    /// The scan stopped early (cancel token or lost root) and the caller,
+    /// e.g. `Scanner::scan`, has no `ScanReport` to mark the tree as partial
+    Interrupted,
+}
+
+impl ScanError {
+    /// Classify an error from reading the scan root itself
+    fn from_root_io(path: &Path, source: std::io::Error) -> Self {
+        if source.kind() == std::io::ErrorKind::NotFound {
+            ScanError::RootNotFound(path.to_path_buf())
+        } else {
+            ScanError::Io(source)
+        }
+    }
+}
+
+impl std::fmt::Display for ScanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanError::RootNotFound(path) => write!(f, "Path does not exist: {}", path.display()),
+            ScanError::RootNotADirectory(path) => write!(f, "Not a directory: {}", path.display()),
+            ScanError::PermissionDenied { path, message } => {
+                let path = path.as_ref().map_or_else(|| "<unknown path>".to_string(), |p| p.display().to_string());
+                write!(f, "Permission denied: {} ({})", path, message)
+            }
+            ScanError::InvalidExclude { pattern, message } => {
+                write!(f, "invalid exclude pattern '{}': {}", pattern, message)
+            }
+            ScanError::Io(source) => write!(f, "Could not read the scan root: {}", source),
+            ScanError::NotInTree(path) => write!(f, "Directory not found in tree: {}", path.display()),
+            ScanError::Interrupted => write!(f, "Scan was interrupted before it finished"),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ScanError::Io(source) => Some(source),
+            _ => None,
+        }
+    }
 }
 
 /// Represents the current state of a scan operation.
 /// 
 /// Frontends (TUI/GUI) can poll this to update their UI accordingly.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub enum ScanState {
     /// No scan is currently running
+    #[default]
     Idle,
     /// Scan is in progress with current statistics
     Scanning {
@@ -92,13 +866,167 @@ pub enum ScanState {
     },
     /// Scan completed successfully with results
     Done {
-        root: Node,
-        report: ScanReport,
+        root: Box<Node>,
+        report: Box<ScanReport>,
     },
     /// Scan failed with error message
     Error(String),
 }
 
+/// Options controlling how a scan is performed and post-processed.
+///
+/// Build one directly or use the `Scanner::with_*` builder methods.
+#[derive(Debug, Clone, Default)]
+pub struct ScanConfig {
+    /// Directories whose total size is below this many bytes are folded into a
+    /// single summary entry in their parent. The root is never pruned.
+    pub min_dir_size: Option<u64>,
+    /// Build `Node`s at most this many levels below the root: `Some(0)` is
+    /// the root only, `Some(1)` its immediate children, and so on. Deeper
+    /// entries are still walked and their bytes and file counts credited to
+    /// their ancestor at the cutoff, so every total stays exact; directories
+    /// at the cutoff just have no `children`.
+    pub max_depth: Option<usize>,
+    /// Metric the tree is ordered by and `min_dir_size` is compared against.
+    /// Both are always recorded (`Node::size` and `Node::allocated_size`),
+    /// so frontends can still switch between them afterwards.
+    pub size_mode: SizeMode,
+    /// Descend into Windows junctions and directory symlinks instead of
+    /// recording them as leaves. Loops are detected and skipped. No effect on
+    /// other platforms.
+    pub follow_reparse_points: bool,
+    /// Stay on the filesystem of the scan root (like `du -x`). Directories on
+    /// another device are pruned and listed in `ScanReport::mount_points`.
+    /// Unix only; a no-op on other platforms.
+    ///
+    /// Only directories are compared against the root device. Overlay
+    /// filesystems (container rootfs, Docker layers) may report the lower
+    /// layer's device for regular files, so comparing files would wrongly drop
+    /// them.
+    pub one_filesystem: bool,
+    /// Count hardlinked files once. Later links to an already-seen inode still
+    /// appear in the tree but with size 0. With `Scanner::scan_many` the
+    /// inode set is shared across all roots, which also catches bind mounts.
+    /// Unix only; a no-op on other platforms.
+    pub dedup_hardlinks: bool,
+    /// Record the largest descendant file of every directory in
+    /// `Node::largest_file`.
+    pub track_largest_file: bool,
+    /// Leave out sockets, FIFOs and block/character devices, recording them in
+    /// `ScanReport::skipped` with `SkipReason::SpecialFile`. Their reported
+    /// sizes are meaningless for disk usage. `None` (the default) enables this
+    /// only when the scan root is a filesystem root (see `is_filesystem_root`).
+    /// Unix only.
+    pub skip_special_files: Option<bool>,
+    /// Metadata reads taking at least this long are listed in
+    /// `ScanReport::slow_entries`. `None` uses `DEFAULT_SLOW_ENTRY_THRESHOLD`.
+    #[cfg(feature = "profile")]
+    pub slow_entry_threshold: Option<Duration>,
+    /// Extension to category overrides for `Scanner::category_breakdown`,
+    /// consulted before `EXTENSION_CATEGORIES`. Keys are lowercase, without
+    /// the leading dot.
+    pub category_overrides: HashMap<String, FileCategory>,
+    /// Whether permission errors are skipped (the default) or end the scan.
+    /// Network errors are always skipped.
+    pub on_permission_error: SkipOrFail,
+    /// Custom include predicate, see `EntryFilter`
+    pub entry_filter: Option<EntryFilter>,
+    /// Glob patterns for entries to leave out, like `.gitignore` lines:
+    /// - without a `/` they match a file or directory name at any depth
+    ///   (`node_modules`, `*.tmp`)
+    /// - with a `/` they match the path relative to the root
+    ///   (`src/generated`, `**/build`), where `*` does not cross `/`
+    /// - a trailing `/` matches directories only (`target/`)
+    ///
+    /// Matching directories are not descended into and are listed in
+    /// `ScanReport::excluded`. An invalid pattern fails the scan.
+    pub excludes: Vec<String>,
+    /// Leave out what `.gitignore` files inside the scan root ignore, to see
+    /// only the files a repository actually keeps.
+    ///
+    /// Every directory's `.gitignore` applies to its subtree, deeper files
+    /// win over shallower ones, and `!pattern` re-includes an entry, as in
+    /// git. An ignored directory is not descended into, so nothing below it
+    /// can be re-included. `.gitignore` files above the root,
+    /// `.git/info/exclude` and global excludes are not read. Ignored entries
+    /// are counted in `ScanReport::gitignored`.
+    pub respect_gitignore: bool,
+    /// Break size ties by name so siblings always come out in the same
+    /// order. Repeated exports of an unchanged tree are then byte-identical,
+    /// which keeps them diffable in version control.
+    pub deterministic: bool,
+    /// Count the files under the root on a background thread while scanning,
+    /// filling in `SharedProgress::total_files_estimate` so frontends can
+    /// show a time estimate. The count only lists directories (no metadata
+    /// reads) but still adds a second pass over the tree, and ignores
+    /// excludes and filters. Only used with shared progress.
+    pub estimate_total: bool,
+    /// How symlinks are counted when `exclude_symlinks` is not set
+    pub symlink_policy: SymlinkPolicy,
+    /// Leave symlinks (and Windows reparse points) out of the tree entirely
+    /// instead of listing them as leaves. Followed links are not descended
+    /// into either. Counted in `ScanReport::symlinks_excluded`.
+    pub exclude_symlinks: bool,
+    /// When reading an entry fails because its path is too long, retry
+    /// through the extended-length (`\\?\`) form of the path. Directories
+    /// whose listing fails this way are still skipped. Windows only.
+    pub retry_long_paths: bool,
+    /// Where and how `Scanner::auto_export` writes the results of a finished
+    /// scan. The scan itself never exports; frontends call `auto_export`
+    /// once they have a complete tree.
+    pub auto_export: Option<(PathBuf, ExportFormat)>,
+    /// After scanning a filesystem root, compare the total with the
+    /// filesystem's own used-space figure and fill in
+    /// `ScanReport::unaccounted_bytes`.
+    pub measure_unaccounted: bool,
+    /// Threads listing directories during the walk. `None` (or `Some(0)`)
+    /// uses rayon's shared pool with one thread per core, `Some(1)` walks
+    /// on the scanning thread alone, and larger values use a dedicated pool
+    /// of that size.
+    ///
+    /// Many threads suit SSDs and network filesystems, which serve parallel
+    /// requests well. On a spinning disk they make the head seek between
+    /// directories, so one or two threads are usually faster there.
+    pub threads: Option<usize>,
+}
+
+/// Caller-supplied predicate deciding which entries a scan includes.
+///
+/// Called with the path and (non-followed) metadata of every file and
+/// directory below the root; entries for which it returns `false` are left
+/// out, and a rejected directory is not descended into. It runs on the
+/// scanning threads, possibly several at once, so it must be cheap and must
+/// not block.
+#[derive(Clone)]
+pub struct EntryFilter(Arc<EntryPredicate>);
+
+type EntryPredicate = dyn Fn(&Path, &std::fs::Metadata) -> bool + Send + Sync;
+
+impl EntryFilter {
+    /// Wrap a predicate; `Scanner::with_entry_filter` does this for you
+    pub fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&Path, &std::fs::Metadata) -> bool + Send + Sync + 'static,
+    {
+        Self(Arc::new(predicate))
+    }
+
+    /// Whether the entry at `path` should be included
+    pub fn matches(&self, path: &Path, metadata: &std::fs::Metadata) -> bool {
+        (self.0)(path, metadata)
+    }
+}
+
+impl std::fmt::Debug for EntryFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EntryFilter(..)")
+    }
+}
+
+/// Default for `ScanConfig::slow_entry_threshold`
+#[cfg(feature = "profile")]
+pub const DEFAULT_SLOW_ENTRY_THRESHOLD: Duration = Duration::from_millis(100);
+
 /// High-performance disk usage scanner
 /// 
 /// This is the main interface for scanning directories. Use this instead of
@@ -108,9 +1036,12 @@ pub enum ScanState {
 /// 
 /// This Scanner is designed to be used by multiple frontends (TUI, GUI, etc.).
 /// It provides both blocking and progress-based scanning methods.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct Scanner {
-    // TODO: Future: Add configuration options here (filters, exclusions, etc.)
+    config: ScanConfig,
+    cancel: Option<Arc<AtomicBool>>,
+    pause: Option<Arc<AtomicBool>>,
+    skipped_tx: Option<mpsc::Sender<SkippedEntry>>,
 }
 
 // ============================================================================
@@ -123,422 +1054,6038 @@ impl Node {
             name,
             path,
             is_dir,
+            is_symlink: false,
+            file_count: 0,
+            access_denied: false,
+            largest_file: None,
+            is_summary: false,
             size: 0,
+            allocated_size: 0,
             children: Vec::new(),
+            mtime: None,
         }
     }
 
+    /// The nodes from `self` down to and including the node at `path`, for
+    /// breadcrumbs and similar.
+    ///
+    /// Returns `None` if `path` is not under this node or no such entry was
+    /// scanned. Matching is by name, one path component at a time.
+    ///
+    /// Frontends can turn the chain into a navigation stack: for a file,
+    /// drop the last element and select it in its parent.
+    pub fn ancestors_of(&self, path: &Path) -> Option<Vec<&Node>> {
+        let mut chain = vec![self];
+        for name in self.names_to(path)? {
+            let current = chain[chain.len() - 1];
+            chain.push(current.children.iter().find(|c| c.name == name)?);
+        }
+        Some(chain)
+    }
 
+    /// The node at `path` in this subtree (`self` for its own path).
+    ///
+    /// Returns `None` under the same conditions as `ancestors_of`, without
+    /// building the chain.
+    pub fn find(&self, path: &Path) -> Option<&Node> {
+        self.names_to(path)?
+            .iter()
+            .try_fold(self, |node, name| node.children.iter().find(|c| c.name == *name))
+    }
 
-    /// Delete a node from the tree by path and remove it from disk.
-    /// 
-    /// This method:
-    /// 1. Finds the node in the tree by matching its path
-    /// 2. Deletes it from disk
-    /// 3. Removes it from the parents children vector
-    /// 4. Updates parent sizes by subtracting the deleted node's size
-    /// 
-    /// # Arguments
-    /// * `target_path` - The path of the node to delete
-    /// 
-    /// # Returns
-    /// * `Ok(())` - If deletion succeeded
-    /// * `Err(std::io::Error)` - If deletion failed
-    pub fn delete_node(&mut self, target_path: &Path) -> Result<(), std::io::Error> {
-        if let Some((deleted_size, deleted_is_dir)) = self.remove_child_by_path(target_path)? {
-            if deleted_is_dir {
-                std::fs::remove_dir_all(target_path)?;
-            } else {
-                std::fs::remove_file(target_path)?;
-            }
-            
-            // Update this node's size by subtract the deleted nodes size
-            self.size = self.size.saturating_sub(deleted_size);
-            
-            Ok(())
-        } else {
-            Err(std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                format!("Node not found: {}", target_path.display()),
-            ))
-        }
+    /// Mutable version of `find`. Changing a node's size does not update
+    /// its ancestors' totals.
+    pub fn find_mut(&mut self, path: &Path) -> Option<&mut Node> {
+        self.names_to(path)?
+            .iter()
+            .try_fold(self, |node, name| node.children.iter_mut().find(|c| c.name == *name))
     }
 
-    /// Recursively search for and remove a child node by path.
-    /// Returns the size and is_dir flag of the deleted node if found.
-    fn remove_child_by_path(&mut self, target_path: &Path) -> Result<Option<(u64, bool)>, std::io::Error> {
-        for (index, child) in self.children.iter().enumerate() {
-            if child.path == target_path {
-                let deleted_size = child.size;
-                let deleted_is_dir = child.is_dir;
-                self.children.remove(index);
-                return Ok(Some((deleted_size, deleted_is_dir)));
-            }
+    /// The child names leading from this node down to `path`, or `None` if
+    /// `path` is not under it
+    fn names_to(&self, path: &Path) -> Option<Vec<String>> {
+        let relative = path.strip_prefix(&self.path).ok()?;
+        Some(
+            relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect(),
+        )
+    }
+
+    /// Index of the child directory holding at least `fraction` of this
+    /// node's size, if any. Frontends use it to follow a chain of single big
+    /// directories. Always `None` for an empty node.
+    pub fn dominant_child(&self, fraction: f64) -> Option<usize> {
+        if self.size == 0 {
+            return None;
         }
+        self.children
+            .iter()
+            .position(|c| c.is_dir && c.size as f64 >= self.size as f64 * fraction)
+    }
 
-        for child in &mut self.children {
-            if target_path.starts_with(&child.path) {
-                if let Some((deleted_size, deleted_is_dir)) = child.remove_child_by_path(target_path)? {
-                    self.size = self.size.saturating_sub(deleted_size);
-                    return Ok(Some((deleted_size, deleted_is_dir)));
-                }
-            }
+    /// Size of this subtree in the given metric
+    pub fn size_in(&self, mode: SizeMode) -> u64 {
+        match mode {
+            SizeMode::Apparent => self.size,
+            SizeMode::Allocated => self.allocated_size,
         }
+    }
 
-        Ok(None)
+    /// Mean size of the files in this subtree (`size_in(mode) / file_count`),
+    /// or `None` if it contains no files.
+    pub fn average_file_size(&self, mode: SizeMode) -> Option<u64> {
+        self.size_in(mode).checked_div(self.file_count)
     }
-}
 
-impl Ord for Node {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.size.cmp(&self.size)
+    /// Split this directory's size into `(direct, nested)`: the total of its
+    /// immediate file children, and everything held in its subdirectories.
+    pub fn direct_and_nested_size(&self, mode: SizeMode) -> (u64, u64) {
+        let direct = self
+            .children
+            .iter()
+            .filter(|c| c.is_file())
+            .fold(0u64, |total, c| total.saturating_add(c.size_in(mode)));
+        (direct, self.size_in(mode).saturating_sub(direct))
     }
-}
 
-impl PartialOrd for Node {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+    /// Iterate over this node and all of its descendants, depth-first.
+    ///
+    /// Parents come before their children and siblings keep their stored
+    /// (size-descending after a scan) order.
+    pub fn iter(&self) -> NodeIter<'_> {
+        NodeIter { stack: vec![self] }
     }
-}
 
-impl PartialEq for Node {
-    fn eq(&self, other: &Self) -> bool {
-        self.size == other.size && self.name == other.name
+    /// Whether this node is a file: neither a directory nor an
+    /// `is_summary` placeholder
+    pub fn is_file(&self) -> bool {
+        !self.is_dir && !self.is_summary
     }
-}
 
-impl Eq for Node {}
+    /// Iterate over the files (see `is_file`) in this subtree, in the same
+    /// order as `iter`. Empty directories are not yielded.
+    pub fn iter_files(&self) -> impl Iterator<Item = &Node> {
+        self.iter().filter(|n| n.is_file())
+    }
 
-impl Default for ScanState {
-    fn default() -> Self {
-        Self::Idle
+    /// Number of nodes in this subtree, counting this node itself.
+    pub fn total_nodes(&self) -> usize {
+        self.iter().count()
     }
-}
 
-impl Scanner {
-    /// Create a new Scanner instance
-    pub fn new() -> Self {
-        Self::default()
+    /// Length of the longest path from this node down to a leaf. A file or an
+    /// empty directory has depth 0.
+    pub fn depth(&self) -> usize {
+        let mut max = 0;
+        let mut stack = vec![(self, 0usize)];
+        while let Some((node, d)) = stack.pop() {
+            max = max.max(d);
+            stack.extend(node.children.iter().map(|c| (c, d + 1)));
+        }
+        max
     }
 
-    /// Scan a directory and return the root node with all children
-    /// 
-    /// # Arguments
-    /// * `path` - The directory path to scan
-    /// 
-    /// # Returns
-    /// * `Ok(Node)` - The root node containing the entire tree
-    /// * `Err(anyhow::Error)` - If scanning fails
-    /// 
-    /// # Example
-    /// ```no_run
-    /// use ferris_scan::Scanner;
-    /// use std::path::Path;
-    /// 
-    /// let scanner = Scanner::new();
-    /// let result = scanner.scan(Path::new("C:/")).unwrap();
-    /// println!("Total size: {} bytes", result.size);
-    /// ```
-    pub fn scan<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<Node> {
-        let (root, _report) = scan_directory_with_report_shared(path, None, None)?;
-        Ok(root)
+    /// Total bytes and file count per extension for the files in this
+    /// subtree, keyed by the lowercased extension without the dot. Files with
+    /// no extension (including dotfiles like `.bashrc`) are grouped under `""`.
+    pub fn size_by_extension(&self) -> HashMap<String, (u64, u64)> {
+        let mut totals: HashMap<String, (u64, u64)> = HashMap::new();
+        for file in self.iter_files() {
+            let ext = Path::new(&file.name)
+                .extension()
+                .map(|e| e.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let entry = totals.entry(ext).or_default();
+            entry.0 = entry.0.saturating_add(file.size);
+            entry.1 += 1;
+        }
+        totals
     }
 
-    /// Scan with progress reporting
-    pub fn scan_with_progress<P: AsRef<Path>>(
-        &self,
-        path: P,
-        shared_progress: Arc<SharedProgress>,
-    ) -> anyhow::Result<(Node, ScanReport)> {
-        scan_directory_with_report_shared(path, None, Some(shared_progress))
+    /// Total bytes and file count per `FileCategory` for the files in this
+    /// subtree, classified with the built-in `EXTENSION_CATEGORIES` map.
+    /// Files without a known extension count as `FileCategory::Other`. Use
+    /// `Scanner::category_breakdown` to apply `ScanConfig::category_overrides`.
+    pub fn size_by_category(&self) -> HashMap<FileCategory, (u64, u64)> {
+        self.size_by_category_in(SizeMode::Apparent)
     }
 
-    /// Export scan results to CSV format (Pro feature only)
-    /// 
-    /// This function is only available when compiled with `--features pro`.
-    /// 
-    /// # Arguments
-    /// * `root` - The root node to export
-    /// * `output_path` - Path where the CSV file will be written
-    /// 
-    /// # Returns
-    /// * `Ok(())` - If export succeeds
-    /// * `Err(anyhow::Error)` - If export fails
-    /// 
-    /// # Pro Feature
-    /// This method is only available in the Pro version.
-    /// 
-    /// # Example
-    /// ```no_run
-    /// # #[cfg(feature = "pro")]
-    /// # {
-    /// use ferris_scan::Scanner;
-    /// use std::path::Path;
-    /// 
-    /// let scanner = Scanner::new();
-    /// let result = scanner.scan(Path::new("C:/")).unwrap();
-    /// scanner.export_csv(&result, Path::new("output.csv")).unwrap();
-    /// # }
-    /// ```
-    #[cfg(feature = "pro")]
-    pub fn export_csv<P: AsRef<Path>>(&self, root: &Node, output_path: P) -> anyhow::Result<()> {
-        use std::fs::File;
+    /// `size_by_category`, totalling sizes in `mode`
+    pub fn size_by_category_in(&self, mode: SizeMode) -> HashMap<FileCategory, (u64, u64)> {
+        let mut totals: HashMap<FileCategory, (u64, u64)> = HashMap::new();
+        for file in self.iter_files() {
+            let category = Path::new(&file.name)
+                .extension()
+                .map_or(FileCategory::Other, |ext| {
+                    FileCategory::from_extension(&ext.to_string_lossy())
+                });
+            let entry = totals.entry(category).or_default();
+            entry.0 = entry.0.saturating_add(file.size_in(mode));
+            entry.1 += 1;
+        }
+        totals
+    }
 
-        let file = File::create(output_path)?;
-        let mut writer = csv::Writer::from_writer(file);
+    /// Remove the directories below this node that hold no files at any
+    /// depth and have a size of 0, returning how many were removed. A
+    /// directory with a non-empty subdirectory is kept, as are directories
+    /// that could not be listed (`access_denied`), whose contents are unknown.
+    /// Totals are unaffected since nothing with a size is removed.
+    pub fn prune_empty_dirs(&mut self) -> usize {
+        let mut removed = 0;
+        for child in &mut self.children {
+            removed += child.prune_empty_dirs();
+        }
+        let before = self.children.len();
+        self.children
+            .retain(|c| !(c.is_dir && c.children.is_empty() && c.size == 0 && !c.access_denied));
+        removed + before - self.children.len()
+    }
 
-        writer.write_record(["Path", "Name", "Type", "Size (bytes)"])?;
-        self.write_node_csv(&mut writer, root, &PathBuf::new())?;
+    /// Files in this subtree last modified before `cutoff`, oldest first.
+    /// Files without a recorded modification time are left out.
+    pub fn files_older_than(&self, cutoff: SystemTime) -> Vec<&Node> {
+        let mut files: Vec<&Node> = self
+            .iter_files()
+            .filter(|n| n.mtime.is_some_and(|t| t < cutoff))
+            .collect();
+        files.sort_by(|a, b| a.mtime.cmp(&b.mtime).then_with(|| a.path.cmp(&b.path)));
+        files
+    }
 
-        writer.flush()?;
-        Ok(())
+    /// Total bytes and number of the files in this subtree last modified
+    /// before `cutoff`, without collecting them like `files_older_than`.
+    pub fn size_older_than(&self, cutoff: SystemTime) -> (u64, u64) {
+        self.iter_files()
+            .filter(|n| n.mtime.is_some_and(|t| t < cutoff))
+            .fold((0, 0), |(bytes, count), n| (bytes.saturating_add(n.size), count + 1))
     }
 
-    #[cfg(feature = "pro")]
-    fn write_node_csv(
-        &self,
-        writer: &mut csv::Writer<std::fs::File>,
-        node: &Node,
-        parent_path: &Path,
-    ) -> anyhow::Result<()> {
-        let current_path = parent_path.join(&node.name);
-        let node_type = if node.is_dir { "Directory" } else { "File" };
+    /// The `n` largest files anywhere in this subtree, largest first, with
+    /// ties broken by path. Directories are never included; fewer than `n`
+    /// entries come back if the subtree has fewer files.
+    ///
+    /// Keeps a min-heap of at most `n` candidates during the walk instead of
+    /// collecting and sorting every file.
+    pub fn largest_files(&self, n: usize) -> Vec<&Node> {
+        self.largest_files_in(n, SizeMode::Apparent)
+    }
 
-        writer.write_record(&[
-            current_path.display().to_string(),
-            node.name.clone(),
-            node_type.to_string(),
-            node.size.to_string(),
-        ])?;
+    /// `largest_files`, ranked by size in `mode`
+    pub fn largest_files_in(&self, n: usize, mode: SizeMode) -> Vec<&Node> {
+        use std::cmp::{Ordering, Reverse};
+        use std::collections::BinaryHeap;
 
-        for child in &node.children {
-            self.write_node_csv(writer, child, &current_path)?;
+        // Orders files by size, then by path reversed, so "greater" means
+        // "ranks higher" and the heap top (via `Reverse`) is the next to evict.
+        struct Ranked<'a>(&'a Node, SizeMode);
+
+        impl Ranked<'_> {
+            fn key(&self) -> (u64, Reverse<&Path>) {
+                (self.0.size_in(self.1), Reverse(self.0.path.as_path()))
+            }
+        }
+        impl PartialEq for Ranked<'_> {
+            fn eq(&self, other: &Self) -> bool {
+                self.key() == other.key()
+            }
+        }
+        impl Eq for Ranked<'_> {}
+        impl PartialOrd for Ranked<'_> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Ranked<'_> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.key().cmp(&other.key())
+            }
         }
 
-        Ok(())
+        if n == 0 {
+            return Vec::new();
+        }
+        let mut heap: BinaryHeap<Reverse<Ranked<'_>>> = BinaryHeap::with_capacity(n + 1);
+        for file in self.iter_files() {
+            let candidate = Ranked(file, mode);
+            if heap.len() == n && heap.peek().is_some_and(|Reverse(worst)| candidate <= *worst) {
+                continue;
+            }
+            heap.push(Reverse(candidate));
+            if heap.len() > n {
+                heap.pop();
+            }
+        }
+        heap.into_sorted_vec().into_iter().map(|Reverse(r)| r.0).collect()
     }
-}
-
-// ============================================================================
-// PUBLIC API
-// ============================================================================
 
-/// Scan a directory and build a tree structure of disk usage
-pub fn scan_directory<P: AsRef<Path>>(
-    root: P,
-    progress_tx: Option<mpsc::Sender<ScanProgress>>,
-) -> anyhow::Result<Node> {
-    Ok(scan_directory_with_report(root, progress_tx)?.0)
-}
+    /// Re-sort the children of every directory in this subtree by `key`.
+    /// Ties are broken by name so repeated sorts give the same order.
+    ///
+    /// Scans already come back sorted by size, largest first; this is for
+    /// frontends that let the user pick another order, and for restoring an
+    /// order after `delete_node`, which leaves siblings where they were.
+    pub fn sort_by(&mut self, key: SortKey, ascending: bool) {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            node.children.sort_by(|a, b| {
+                let order = key.compare(a, b);
+                let order = if ascending { order } else { order.reverse() };
+                order.then_with(|| a.name.cmp(&b.name))
+            });
+            stack.extend(node.children.iter_mut().filter(|c| c.is_dir));
+        }
+    }
 
-/// Scan a directory and return both the tree and a report
-pub fn scan_directory_with_report<P: AsRef<Path>>(
-    root: P,
-    progress_tx: Option<mpsc::Sender<ScanProgress>>,
-) -> anyhow::Result<(Node, ScanReport)> {
-    scan_directory_with_report_shared(root, progress_tx, None)
-}
+    /// Total size of the files (leaves) in this subtree for which `pred`
+    /// returns true. Directories are never passed to `pred`.
+    pub fn total_matching(&self, pred: impl Fn(&Node) -> bool) -> u64 {
+        self.iter_files()
+            .filter(|n| pred(n))
+            .fold(0u64, |total, n| total.saturating_add(n.size))
+    }
 
-/// Scan a directory and return both the tree and a report, while optionally updating shared progress.
-pub fn scan_directory_with_report_shared<P: AsRef<Path>>(
-    root: P,
-    progress_tx: Option<mpsc::Sender<ScanProgress>>,
-    shared_progress: Option<Arc<SharedProgress>>,
-) -> anyhow::Result<(Node, ScanReport)> {
-    let start = Instant::now();
-    let root_path = root.as_ref().to_path_buf();
-    let mut report = ScanReport::default();
+    /// Walk the tree SAX-style, calling `f` with an `Enter` and a matching
+    /// `Leave` event for every node (files included), depth-first.
+    ///
+    /// Uses an explicit stack rather than recursion, so arbitrarily deep trees
+    /// are safe. Useful for streaming exports that write as they go.
+    pub fn stream_events<F: FnMut(TreeEvent<'_>)>(&self, mut f: F) {
+        // (node, index of the next child to visit)
+        let mut stack: Vec<(&Node, usize)> = vec![(self, 0)];
+        f(TreeEvent::Enter(self));
 
-    let mut root_node = Node::new(
-        root_path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or(".")
-            .to_string(),
-        root_path.clone(),
-        true,
-    );
+        while let Some((node, next_child)) = stack.last_mut() {
+            if let Some(child) = node.children.get(*next_child) {
+                *next_child += 1;
+                f(TreeEvent::Enter(child));
+                stack.push((child, 0));
+            } else {
+                f(TreeEvent::Leave(node));
+                stack.pop();
+            }
+        }
+    }
 
-    let mut files_scanned: usize = 0;
-    for entry in WalkDir::new(&root_path).sort(true) {
-        match entry {
-            Ok(entry) => {
-                let path = entry.path();
-                if path == root_path {
-                    continue;
-                }
+    /// Delete a node from the tree by path and remove it from disk.
+    /// 
+    /// This method:
+    /// 1. Finds the node in the tree by matching its path
+    /// 2. Deletes it from disk, recursively for a directory
+    /// 3. Removes it from the parents children vector
+    /// 4. Updates parent sizes by subtracting the deleted node's size
+    /// 
+    /// The tree is only changed once the filesystem removal succeeded, so a
+    /// failed delete leaves it matching the disk. Sibling order is not kept:
+    /// an ancestor that shrank stays where it was among its siblings, so
+    /// re-sort with `sort_by` if the order matters.
+    /// 
+    /// # Arguments
+    /// * `target_path` - The path of the node to delete (not this node itself)
+    /// 
+    /// # Returns
+    /// * `Ok(())` - If deletion succeeded
+    /// * `Err(DeleteError)` - If the node is not in the tree or removal failed
+    pub fn delete_node(&mut self, target_path: &Path) -> Result<(), DeleteError> {
+        let is_dir = match self.find(target_path) {
+            Some(node) if target_path != self.path => node.is_dir,
+            _ => return Err(DeleteError::NotFound(target_path.to_path_buf())),
+        };
 
-                if let Some(ref sp) = shared_progress {
-                    if let Ok(mut lp) = sp.last_path.lock() {
-                        *lp = Some(path.to_path_buf());
-                    }
-                }
+        let removed = if is_dir {
+            std::fs::remove_dir_all(target_path)
+        } else {
+            std::fs::remove_file(target_path)
+        };
+        removed.map_err(|e| DeleteError::from_io(target_path, e))?;
 
-                if let Some(ref tx) = progress_tx {
-                    let _ = tx.send(ScanProgress {
-                        files_scanned,
-                        current_path: path.to_path_buf(),
-                        elapsed: start.elapsed(),
-                    });
-                }
+        self.remove_child_by_path(target_path);
+        Ok(())
+    }
 
-                let Ok(relative) = path.strip_prefix(&root_path) else {
-                    continue;
-                };
+    /// Swap the totals and children of the directory at `target_path` for
+    /// those of `fresh`, adjusting every ancestor by the difference.
+    /// Returns false if no such directory exists in this tree.
+    ///
+    /// Ancestors are re-sorted as `sort_tree` would with `config`, and their
+    /// `largest_file` is recomputed when `config` tracks it.
+    fn replace_subtree(&mut self, target_path: &Path, fresh: Node, config: &ScanConfig) -> bool {
+        if self.path == target_path {
+            self.size = fresh.size;
+            self.allocated_size = fresh.allocated_size;
+            self.file_count = fresh.file_count;
+            self.largest_file = fresh.largest_file;
+            self.children = fresh.children;
+            self.mtime = fresh.mtime;
+            return true;
+        }
 
-                let is_dir = entry.file_type().is_dir();
-                if is_dir {
-                    ensure_dir_path(&mut root_node, relative);
-                    continue;
+        for child in &mut self.children {
+            if child.is_dir && target_path.starts_with(&child.path) {
+                let old = (child.size, child.allocated_size, child.file_count);
+                if child.replace_subtree(target_path, fresh, config) {
+                    self.size = self.size.saturating_sub(old.0).saturating_add(child.size);
+                    self.allocated_size = self
+                        .allocated_size
+                        .saturating_sub(old.1)
+                        .saturating_add(child.allocated_size);
+                    self.file_count = self.file_count.saturating_sub(old.2).saturating_add(child.file_count);
+                    if config.track_largest_file {
+                        self.largest_file = self.largest_file_of_children();
+                    }
+                    self.children.sort_by(|a, b| sibling_order(a, b, config.deterministic, config.size_mode));
+                    return true;
                 }
+                return false;
+            }
+        }
 
-                let md = match entry.metadata() {
-                    Ok(md) => md,
-                    Err(e) => {
-                        if is_permission_denied(&e) {
-                            report.skipped.push(SkippedEntry {
-                                path: Some(path.to_path_buf()),
-                                message: e.to_string(),
-                            });
-                        }
-                        continue;
+        false
+    }
+
+    /// Recursively search for and remove a child node by path, subtracting
+    /// its totals from every node on the way down (this one included).
+    /// A `largest_file` that was the removed node or lay inside it is
+    /// recomputed from the remaining children. Returns the removed node if
+    /// found.
+    fn remove_child_by_path(&mut self, target_path: &Path) -> Option<Node> {
+        let deleted = if let Some(index) = self.children.iter().position(|c| c.path == target_path) {
+            Some(self.children.remove(index))
+        } else {
+            let mut found = None;
+            for child in &mut self.children {
+                if target_path.starts_with(&child.path) {
+                    found = child.remove_child_by_path(target_path);
+                    if found.is_some() {
+                        break;
                     }
-                };
-                files_scanned += 1;
-                if let Some(ref sp) = shared_progress {
-                    sp.files_scanned.store(files_scanned as u64, Ordering::Relaxed);
                 }
-                add_file_to_tree(&mut root_node, relative, md.len());
             }
-            Err(e) => {
-                if is_permission_denied(&e) {
-                    report.skipped.push(SkippedEntry {
-                        path: None,
-                        message: e.to_string(),
-                    });
-                }
-                continue;
+            found
+        };
+
+        if let Some(ref deleted) = deleted {
+            self.size = self.size.saturating_sub(deleted.size);
+            self.allocated_size = self.allocated_size.saturating_sub(deleted.allocated_size);
+            self.file_count = self.file_count.saturating_sub(deleted.file_count);
+            if self.largest_file.as_ref().is_some_and(|(path, _)| path.starts_with(&deleted.path)) {
+                self.largest_file = self.largest_file_of_children();
             }
         }
+        deleted
     }
 
-    calculate_dir_sizes(&mut root_node);
-    sort_tree(&mut root_node);
-    
-    Ok((root_node, report))
+    /// The largest of this directory's files and of its subdirectories'
+    /// `largest_file`
+    fn largest_file_of_children(&self) -> Option<(PathBuf, u64)> {
+        self.children
+            .iter()
+            .filter_map(|c| match c.is_file() {
+                true => Some((c.path.clone(), c.size)),
+                false => c.largest_file.clone(),
+            })
+            .max_by_key(|(_, size)| *size)
+    }
 }
 
-// ============================================================================
-// INTERNAL HELPERS
-// ============================================================================
+impl<'a> Iterator for NodeIter<'a> {
+    type Item = &'a Node;
 
-fn is_permission_denied(e: &jwalk::Error) -> bool {
-    use std::io::ErrorKind;
-    e.io_error()
-        .is_some_and(|io| io.kind() == ErrorKind::PermissionDenied)
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter().rev());
+        Some(node)
+    }
 }
 
-fn ensure_dir_path(root: &mut Node, path: &Path) {
-    let mut current = root;
-    for component in path.components() {
-        let name = component.as_os_str().to_string_lossy().to_string();
-        let existing_idx = current.children.iter().position(|c| c.name == name);
-        let idx = match existing_idx {
-            Some(i) => i,
-            None => {
-                current.children.push(Node::new(
-                    name.clone(),
-                    current.path.join(&name),
-                    true,
-                ));
-                current.children.len() - 1
-            }
-        };
-        current = &mut current.children[idx];
-        current.is_dir = true;
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.size.cmp(&self.size)
     }
 }
 
-fn add_file_to_tree(root: &mut Node, path: &Path, size: u64) {
-    let mut current = root;
-    let mut components = path.components().peekable();
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-    while let Some(component) = components.next() {
-        let name = component.as_os_str().to_string_lossy().to_string();
-        let is_leaf = components.peek().is_none();
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.name == other.name
+    }
+}
 
-        let existing_idx = current.children.iter().position(|c| c.name == name);
-        let idx = match existing_idx {
-            Some(i) => i,
-            None => {
-                current.children.push(Node::new(
-                    name.clone(),
-                    current.path.join(&name),
-                    !is_leaf,
-                ));
-                current.children.len() - 1
-            }
-        };
+impl Eq for Node {}
 
-        current = &mut current.children[idx];
+impl Scanner {
+    /// Create a new Scanner instance
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        if is_leaf {
-            current.is_dir = false;
-            current.size = current.size.saturating_add(size);
-        } else {
-            current.is_dir = true;
-        }
+    /// Create a Scanner with an explicit configuration
+    pub fn with_config(config: ScanConfig) -> Self {
+        Self { config, cancel: None, pause: None, skipped_tx: None }
     }
-}
 
-fn calculate_dir_sizes(node: &mut Node) -> u64 {
-    if !node.is_dir {
-        return node.size;
+    /// Fold directories smaller than `bytes` into a summary entry in their parent
+    pub fn with_min_dir_size(mut self, bytes: u64) -> Self {
+        self.config.min_dir_size = Some(bytes);
+        self
     }
 
-    let mut total = 0u64;
-    for child in &mut node.children {
-        total = total.saturating_add(calculate_dir_sizes(child));
+    /// Order and prune the tree by apparent or allocated size (default:
+    /// apparent), see `ScanConfig::size_mode`
+    pub fn with_size_mode(mut self, mode: SizeMode) -> Self {
+        self.config.size_mode = mode;
+        self
     }
-    node.size = total;
-    total
-}
 
-fn sort_tree(node: &mut Node) {
-    node.children.sort();
-    for child in &mut node.children {
-        sort_tree(child);
+    /// Only build the tree `depth` levels deep (0 = root only), see
+    /// `ScanConfig::max_depth`
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.config.max_depth = Some(depth);
+        self
     }
-}
 
-// ============================================================================
-// TESTS
-// ============================================================================
+    /// Follow Windows junctions and other reparse points (Windows only)
+    pub fn with_follow_reparse_points(mut self, follow: bool) -> Self {
+        self.config.follow_reparse_points = follow;
+        self
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::tempdir;
+    /// Walk with `n` threads; 1 walks serially (see `ScanConfig::threads`)
+    pub fn with_threads(mut self, n: usize) -> Self {
+        self.config.threads = Some(n);
+        self
+    }
 
-    #[test]
-    fn test_scan_empty_directory() {
-        let dir = tempdir().unwrap();
-        let (tx, _rx) = mpsc::channel();
-        let result = scan_directory(dir.path(), Some(tx));
-        assert!(result.is_ok());
+    /// Count the files up front in the background for a progress estimate
+    pub fn with_estimate_total(mut self, estimate: bool) -> Self {
+        self.config.estimate_total = estimate;
+        self
     }
 
-    #[test]
-    fn test_scanner_api() {
-        let dir = tempdir().unwrap();
-        let scanner = Scanner::new();
-        let result = scanner.scan(dir.path());
-        assert!(result.is_ok());
+    /// Choose how symlinks are counted (see `SymlinkPolicy`)
+    pub fn with_symlink_policy(mut self, policy: SymlinkPolicy) -> Self {
+        self.config.symlink_policy = policy;
+        self
+    }
+
+    /// Do not cross into other filesystems (Unix only, like `du -x`)
+    pub fn with_one_filesystem(mut self, one_filesystem: bool) -> Self {
+        self.config.one_filesystem = one_filesystem;
+        self
+    }
+
+    /// Count hardlinked files (same device and inode) only once (Unix only)
+    pub fn with_dedup_hardlinks(mut self, dedup: bool) -> Self {
+        self.config.dedup_hardlinks = dedup;
+        self
+    }
+
+    /// Skip sockets, FIFOs and device files (Unix only). Overrides the
+    /// default of skipping them only for full-disk scans.
+    pub fn with_skip_special_files(mut self, skip: bool) -> Self {
+        self.config.skip_special_files = Some(skip);
+        self
+    }
+
+    /// Retry over-long paths with the `\\?\` prefix (Windows only)
+    pub fn with_long_path_retry(mut self, retry: bool) -> Self {
+        self.config.retry_long_paths = retry;
+        self
+    }
+
+    /// Omit symlinks from the tree
+    pub fn with_exclude_symlinks(mut self, exclude: bool) -> Self {
+        self.config.exclude_symlinks = exclude;
+        self
+    }
+
+    /// Report the difference from `df`-style used space, see
+    /// `ScanReport::unaccounted_bytes`
+    pub fn with_measure_unaccounted(mut self, measure: bool) -> Self {
+        self.config.measure_unaccounted = measure;
+        self
+    }
+
+    /// Export finished scans to `path` in `format`, see `auto_export`
+    pub fn with_auto_export(mut self, path: impl Into<PathBuf>, format: ExportFormat) -> Self {
+        self.config.auto_export = Some((path.into(), format));
+        self
+    }
+
+    /// Order equal-sized siblings by name for reproducible output
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.config.deterministic = deterministic;
+        self
+    }
+
+    /// Only include entries for which `predicate` returns true (see
+    /// `EntryFilter` for when it runs)
+    pub fn with_entry_filter<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Path, &std::fs::Metadata) -> bool + Send + Sync + 'static,
+    {
+        self.config.entry_filter = Some(EntryFilter::new(predicate));
+        self
+    }
+
+    /// Leave out entries matching any of the glob `patterns`, see
+    /// `ScanConfig::excludes`
+    pub fn with_excludes<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.excludes = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Leave out entries ignored by `.gitignore` files, see
+    /// `ScanConfig::respect_gitignore`
+    pub fn with_respect_gitignore(mut self, respect: bool) -> Self {
+        self.config.respect_gitignore = respect;
+        self
+    }
+
+    /// Fail the scan on the first permission error instead of skipping it
+    pub fn with_on_permission_error(mut self, mode: SkipOrFail) -> Self {
+        self.config.on_permission_error = mode;
+        self
+    }
+
+    /// Record metadata reads slower than `threshold` in `ScanReport::slow_entries`
+    #[cfg(feature = "profile")]
+    pub fn with_slow_entry_threshold(mut self, threshold: Duration) -> Self {
+        self.config.slow_entry_threshold = Some(threshold);
+        self
+    }
+
+    /// Classify files with extension `ext` as `category` in
+    /// `category_breakdown`, overriding the built-in map. Case-insensitive.
+    pub fn with_category(mut self, ext: &str, category: FileCategory) -> Self {
+        self.config
+            .category_overrides
+            .insert(ext.trim_start_matches('.').to_ascii_lowercase(), category);
+        self
+    }
+
+    /// Record each directory's largest descendant file in `Node::largest_file`
+    pub fn with_largest_file(mut self, track: bool) -> Self {
+        self.config.track_largest_file = track;
+        self
+    }
+
+    /// Stop scans early once `cancel` is set.
+    ///
+    /// The walk checks the flag before each entry; a cancelled scan returns
+    /// the partial tree built so far with `ScanReport::cancelled` set.
+    pub fn with_cancel_token(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Hold scans while `pause` is set.
+    ///
+    /// The walk waits before the next entry until the flag is cleared,
+    /// keeping everything found so far, and the file-count estimate (see
+    /// `with_estimate_total`) waits with it. Setting the cancel token ends a
+    /// paused scan.
+    pub fn with_pause_token(mut self, pause: Arc<AtomicBool>) -> Self {
+        self.pause = Some(pause);
+        self
+    }
+
+    /// Send each skipped entry on `tx` as soon as it is found, so a UI can
+    /// show permission problems while the scan is still running. The same
+    /// entries still end up in `ScanReport::skipped`. A disconnected
+    /// receiver is ignored.
+    pub fn with_skipped_sender(mut self, tx: mpsc::Sender<SkippedEntry>) -> Self {
+        self.skipped_tx = Some(tx);
+        self
+    }
+
+    /// Get the configuration this Scanner will use
+    pub fn config(&self) -> &ScanConfig {
+        &self.config
+    }
+
+    /// Scan a directory and return the root node with all children
+    /// 
+    /// # Arguments
+    /// * `path` - The directory path to scan
+    /// 
+    /// # Returns
+    /// * `Ok(Node)` - The root node containing the entire tree
+    /// * `Err(ScanError)` - If scanning fails, including
+    ///   `ScanError::Interrupted` when a cancel token stopped it early
+    /// 
+    /// # Example
+    /// ```no_run
+    /// use ferris_scan::Scanner;
+    /// use std::path::Path;
+    /// 
+    /// let scanner = Scanner::new();
+    /// let result = scanner.scan(Path::new("C:/")).unwrap();
+    /// println!("Total size: {} bytes", result.size);
+    /// ```
+    pub fn scan<P: AsRef<Path>>(&self, path: P) -> Result<Node, ScanError> {
+        let (root, report) = self.scan_impl(path.as_ref(), None, None)?;
+        // Without the report the caller could not tell the tree is partial
+        if report.cancelled || report.root_lost {
+            return Err(ScanError::Interrupted);
+        }
+        Ok(root)
+    }
+
+    /// Scan with progress reporting
+    pub fn scan_with_progress<P: AsRef<Path>>(
+        &self,
+        path: P,
+        shared_progress: Arc<SharedProgress>,
+    ) -> Result<(Node, ScanReport), ScanError> {
+        self.scan_impl(path.as_ref(), None, Some(shared_progress))
+    }
+
+    /// Scan until `cancel` is set, then return the partial tree
+    ///
+    /// A one-off alternative to `with_cancel_token`: the token only applies
+    /// to this call. A cancelled scan is not an error; check
+    /// `ScanReport::cancelled`.
+    pub fn scan_with_cancel<P: AsRef<Path>>(
+        &self,
+        path: P,
+        cancel: Arc<AtomicBool>,
+    ) -> Result<(Node, ScanReport), ScanError> {
+        let control = ScanControl { cancel: Some(&cancel), ..self.control() };
+        scan_impl(path.as_ref(), &self.config, WalkScope::default(), None, None, control, None)
+    }
+
+    /// Scan with one-off pause and cancel flags, like `scan_with_cancel`
+    ///
+    /// While `pause` is set the walk waits before the next entry (see
+    /// `with_pause_token`); once `cancel` is set it stops and returns the
+    /// partial tree with `ScanReport::cancelled` set.
+    pub fn scan_with_control<P: AsRef<Path>>(
+        &self,
+        path: P,
+        pause: Arc<AtomicBool>,
+        cancel: Arc<AtomicBool>,
+    ) -> Result<(Node, ScanReport), ScanError> {
+        let control = ScanControl { cancel: Some(&cancel), pause: Some(&pause), ..self.control() };
+        scan_impl(path.as_ref(), &self.config, WalkScope::default(), None, None, control, None)
+    }
+
+    /// Quick overview scan: totals for the root's immediate children only.
+    ///
+    /// The whole tree is still walked so sizes are exact, but no `Node`s are
+    /// created below the first level. Use `scan`/`scan_with_progress` on a
+    /// chosen child afterwards for the full breakdown.
+    pub fn scan_top_level<P: AsRef<Path>>(
+        &self,
+        path: P,
+        shared_progress: Option<Arc<SharedProgress>>,
+    ) -> Result<(Node, ScanReport), ScanError> {
+        self.scan_impl(path.as_ref(), Some(1), shared_progress)
+    }
+
+    /// Scan several roots, returning one tree and report per root in order.
+    ///
+    /// With `dedup_hardlinks` enabled, roots are canonicalized and file
+    /// identity (device + inode) is tracked across all of them, so data
+    /// reachable from more than one root through hardlinks or bind mounts is
+    /// counted only under the first root that reaches it. Each report's
+    /// `hardlink_bytes_saved` says how much was skipped in that root.
+    pub fn scan_many<P: AsRef<Path>>(&self, paths: &[P]) -> Result<Vec<(Node, ScanReport)>, ScanError> {
+        let mut seen = HashSet::new();
+        paths
+            .iter()
+            .map(|path| {
+                let path = path.as_ref();
+                if self.config.dedup_hardlinks {
+                    let canonical =
+                        std::fs::canonicalize(path).map_err(|e| ScanError::from_root_io(path, e))?;
+                    scan_impl(&canonical, &self.config, WalkScope::default(), None, None, self.control(), Some(&mut seen))
+                } else {
+                    self.scan_impl(path, None, None)
+                }
+            })
+            .collect()
+    }
+
+    fn scan_impl(
+        &self,
+        path: &Path,
+        materialize_depth: Option<usize>,
+        shared_progress: Option<Arc<SharedProgress>>,
+    ) -> Result<(Node, ScanReport), ScanError> {
+        let scope = WalkScope { materialize_depth, ..Default::default() };
+        scan_impl(path, &self.config, scope, None, shared_progress, self.control(), None)
+    }
+
+    fn control(&self) -> ScanControl<'_> {
+        ScanControl {
+            cancel: self.cancel.as_deref(),
+            pause: self.pause.as_ref(),
+            skipped_tx: self.skipped_tx.as_ref(),
+        }
+    }
+
+    /// Rescan the directory at `dir` from disk and splice the result into
+    /// `root` in place of its current subtree, correcting the totals and
+    /// `largest_file` of every ancestor. Used after a deletion (see
+    /// `AfterDelete::RescanParent`) when the in-memory tree may have drifted
+    /// from the filesystem.
+    ///
+    /// `dir` is walked as part of the scan of `root`: exclude patterns,
+    /// `.gitignore` files above `dir` and `max_depth` all count from
+    /// `root.path`, so this should be the `Scanner` that produced `root`.
+    pub fn rescan_subtree(&self, root: &mut Node, dir: &Path) -> Result<(), ScanError> {
+        self.rescan_subtree_impl(root, dir, None)
+    }
+
+    /// `rescan_subtree` with progress reporting, e.g. to fill in one
+    /// directory of a `scan_top_level` overview. `root` is left as it was if
+    /// the walk is interrupted.
+    pub fn rescan_subtree_with_progress(
+        &self,
+        root: &mut Node,
+        dir: &Path,
+        shared_progress: Arc<SharedProgress>,
+    ) -> Result<(), ScanError> {
+        self.rescan_subtree_impl(root, dir, Some(shared_progress))
+    }
+
+    fn rescan_subtree_impl(
+        &self,
+        root: &mut Node,
+        dir: &Path,
+        shared_progress: Option<Arc<SharedProgress>>,
+    ) -> Result<(), ScanError> {
+        validate_root(dir)?;
+        let setup = WalkSetup::prepare(&root.path, &self.config)?;
+        let depth = dir.strip_prefix(&root.path).map_or(0, |relative| relative.components().count());
+        let mut gitignores = GitignoreStack::default();
+        if self.config.respect_gitignore {
+            let ancestors: Vec<&Path> = dir.ancestors().skip(1).take(depth).collect();
+            for ancestor in ancestors.into_iter().rev() {
+                gitignores.enter(ancestor);
+            }
+        }
+        let scope = WalkScope {
+            scan_root: Some(&root.path),
+            depth,
+            gitignores,
+            setup: Some(&setup),
+            ..Default::default()
+        };
+        let (fresh, report) = scan_impl(dir, &self.config, scope, None, shared_progress, self.control(), None)?;
+        if report.cancelled || report.root_lost {
+            return Err(ScanError::Interrupted);
+        }
+        if root.replace_subtree(dir, fresh, &self.config) {
+            Ok(())
+        } else {
+            Err(ScanError::NotInTree(dir.to_path_buf()))
+        }
+    }
+
+    /// Bring `old`, a tree from an earlier scan with the same configuration,
+    /// up to date with the disk, listing only directories whose modification
+    /// time changed since. A changed directory gets its files read again and
+    /// its existing subdirectories checked the same way; only subdirectories
+    /// that are new are walked in full. Unchanged directories keep their
+    /// files from `old` and cost one `stat` each.
+    ///
+    /// A directory's mtime only changes when entries are added, removed or
+    /// renamed in it, so a file rewritten in place keeps its old size until
+    /// something else changes in its directory. Directories without an
+    /// `mtime` are always listed, so a tree imported or loaded with
+    /// `Scanner::load_scan` is read again in full. Directories cut off by
+    /// `max_depth` are reused whole while their own mtime holds, and
+    /// hardlinks are only deduplicated within each walk.
+    pub fn rescan(&self, old: &Node) -> Result<Node, ScanError> {
+        validate_root(&old.path)?;
+        let start = Instant::now();
+        log::info!("rescan started: {}", old.path.display());
+        let setup = WalkSetup::prepare(&old.path, &self.config)?;
+        #[cfg(target_os = "linux")]
+        {
+            let overlay_mounts = find_overlay_mounts(&old.path);
+            if !overlay_mounts.is_empty() {
+                log::info!("overlay mounts under scan root: {:?}", overlay_mounts);
+            }
+        }
+
+        let scope = WalkScope { scan_root: Some(&old.path), setup: Some(&setup), ..Default::default() };
+        let mut root = self.rescan_dir(old, &scope)?;
+        if self.config.track_largest_file {
+            find_largest_files(&mut root);
+        }
+        if let Some(min_dir_size) = self.config.min_dir_size {
+            prune_small_dirs(&mut root, min_dir_size, self.config.size_mode);
+        }
+        sort_tree(&mut root, self.config.deterministic, self.config.size_mode);
+        log::info!(
+            "rescan finished: {} in {:?}",
+            summary_line(&root, &ScanReport::default()),
+            start.elapsed()
+        );
+        Ok(root)
+    }
+
+    /// `rescan` for one directory of the old tree, walked in `scope`
+    fn rescan_dir(&self, old: &Node, scope: &WalkScope<'_>) -> Result<Node, ScanError> {
+        let mtime = std::fs::metadata(&old.path).and_then(|md| md.modified()).ok();
+        let changed = mtime.is_none() || mtime != old.mtime;
+        if !changed && old.children.is_empty() {
+            return Ok(old.clone());
+        }
+        // Nothing below the depth limit was kept, so there is nothing to reuse
+        if changed && self.config.max_depth.is_some_and(|max| scope.depth >= max) {
+            log::debug!("changed since last scan: {}", old.path.display());
+            return self.walk_scoped(&old.path, scope.clone());
+        }
+
+        let mut inner = WalkScope { depth: scope.depth + 1, ..scope.clone() };
+        if self.config.respect_gitignore {
+            inner.gitignores.enter(&old.path);
+        }
+        let mut node = Node::new(old.name.clone(), old.path.clone(), true);
+        node.is_symlink = old.is_symlink;
+        node.access_denied = old.access_denied;
+        node.mtime = old.mtime;
+        let mut children = Vec::new();
+        if changed {
+            log::debug!("changed since last scan: {}", old.path.display());
+            let listing = self.walk_scoped(&old.path, WalkScope { list_only: true, ..scope.clone() })?;
+            node.access_denied = listing.access_denied;
+            node.mtime = listing.mtime;
+            for child in listing.children {
+                if !child.is_dir {
+                    children.push(child);
+                    continue;
+                }
+                let previous = old.children.iter().find(|c| c.is_dir && c.name == child.name);
+                children.push(match previous {
+                    Some(previous) if previous.is_symlink => previous.clone(),
+                    Some(previous) => self.rescan_dir(previous, &inner)?,
+                    None => self.walk_scoped(&child.path, inner.clone())?,
+                });
+            }
+        } else {
+            for child in &old.children {
+                children.push(if child.is_dir && !child.is_symlink {
+                    self.rescan_dir(child, &inner)?
+                } else {
+                    child.clone()
+                });
+            }
+        }
+
+        for child in children {
+            node.size = node.size.saturating_add(child.size);
+            node.allocated_size = node.allocated_size.saturating_add(child.allocated_size);
+            node.file_count += child.file_count;
+            node.children.push(child);
+        }
+        Ok(node)
+    }
+
+    /// Walk `dir` as part of a larger tree for `rescan`; an interrupted walk
+    /// is an error, as in `scan`
+    fn walk_scoped(&self, dir: &Path, scope: WalkScope<'_>) -> Result<Node, ScanError> {
+        let (node, report) = scan_impl(dir, &self.config, scope, None, None, self.control(), None)?;
+        if report.cancelled || report.root_lost {
+            return Err(ScanError::Interrupted);
+        }
+        Ok(node)
+    }
+
+    /// How files are spread over depths: `(depth, file_count, total_bytes)`
+    /// for every level from 1 (the root's direct children) down to the
+    /// deepest file, including levels without files. Computed in a single
+    /// traversal.
+    ///
+    /// The summary entry of directories folded by `min_dir_size` counts all
+    /// of their files at its own level. Files below a `max_depth` cutoff
+    /// have no nodes and are left out, so the counts then add up to less
+    /// than `root.file_count`.
+    pub fn depth_histogram(&self, root: &Node) -> Vec<(usize, u64, u64)> {
+        let mut levels: Vec<(usize, u64, u64)> = Vec::new();
+        let mut stack: Vec<(&Node, usize)> = root.children.iter().map(|c| (c, 1)).collect();
+        while let Some((node, depth)) = stack.pop() {
+            // A file, or a summary entry whose `file_count` covers everything
+            // it folded
+            if !node.is_dir {
+                if levels.len() < depth {
+                    levels.extend((levels.len() + 1..=depth).map(|d| (d, 0, 0)));
+                }
+                let level = &mut levels[depth - 1];
+                level.1 += node.file_count;
+                level.2 = level.2.saturating_add(node.size);
+                continue;
+            }
+            stack.extend(node.children.iter().map(|c| (c, depth + 1)));
+        }
+        levels
+    }
+
+    /// Total bytes and file count per `FileCategory` under `root`, largest
+    /// first. Categories without files are left out.
+    ///
+    /// Extensions are matched case-insensitively, using the configured
+    /// overrides before the built-in `EXTENSION_CATEGORIES` map.
+    pub fn category_breakdown(&self, root: &Node) -> Vec<(FileCategory, u64, usize)> {
+        let mut totals: HashMap<FileCategory, (u64, usize)> = HashMap::new();
+        for file in root.iter_files() {
+            let category = Path::new(&file.name)
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+                .map_or(FileCategory::Other, |ext| {
+                    self.config
+                        .category_overrides
+                        .get(&ext)
+                        .copied()
+                        .unwrap_or_else(|| FileCategory::from_extension(&ext))
+                });
+            let entry = totals.entry(category).or_default();
+            entry.0 = entry.0.saturating_add(file.size);
+            entry.1 += 1;
+        }
+
+        let mut breakdown: Vec<_> = totals
+            .into_iter()
+            .map(|(category, (bytes, files))| (category, bytes, files))
+            .collect();
+        breakdown.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        breakdown
+    }
+
+    /// Export scan results to CSV format (Pro feature only)
+    /// 
+    /// This function is only available when compiled with `--features pro`.
+    /// 
+    /// # Arguments
+    /// * `root` - The root node to export
+    /// * `output_path` - Path where the CSV file will be written
+    /// 
+    /// # Returns
+    /// * `Ok(())` - If export succeeds
+    /// * `Err(anyhow::Error)` - If export fails. Wraps `ExportError::EmptyTree`
+    ///   when the root has no children; no file is written in that case.
+    /// 
+    /// # Pro Feature
+    /// This method is only available in the Pro version.
+    /// 
+    /// # Example
+    /// ```no_run
+    /// # #[cfg(feature = "pro")]
+    /// # {
+    /// use ferris_scan::Scanner;
+    /// use std::path::Path;
+    /// 
+    /// let scanner = Scanner::new();
+    /// let result = scanner.scan(Path::new("C:/")).unwrap();
+    /// scanner.export_csv(&result, Path::new("output.csv")).unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "pro")]
+    pub fn export_csv<P: AsRef<Path>>(&self, root: &Node, output_path: P) -> anyhow::Result<()> {
+        self.export_csv_with_options(root, output_path, &ExportOptions::default())
+    }
+
+    /// Export scan results to CSV with explicit options (Pro feature only)
+    ///
+    /// See `ExportOptions::max_rows` for the ordering guarantee when a row cap
+    /// is set.
+    #[cfg(feature = "pro")]
+    pub fn export_csv_with_options<P: AsRef<Path>>(
+        &self,
+        root: &Node,
+        output_path: P,
+        options: &ExportOptions,
+    ) -> anyhow::Result<()> {
+        use std::fs::File;
+
+        if root.children.is_empty() {
+            return Err(ExportError::EmptyTree.into());
+        }
+
+        let selection = options.max_rows.map(|max| select_largest_nodes(root, max));
+
+        let file = File::create(output_path)?;
+        let mut writer = csv::Writer::from_writer(file);
+
+        let mut header = vec!["Path", "Name", "Type", "Size (bytes)"];
+        if options.include_human_size {
+            header.push("Size");
+        }
+        if options.include_depth {
+            header.push("Depth");
+        }
+        if options.include_file_count {
+            header.push("File Count");
+        }
+        if options.include_average_file_size {
+            header.push("Avg File Size (bytes)");
+        }
+        writer.write_record(&header)?;
+        self.write_node_csv(&mut writer, root, &PathBuf::new(), selection.as_ref(), options, 0)?;
+
+        if let Some(selection) = selection.filter(|s| s.omitted_rows > 0) {
+            let mut record = vec![
+                String::new(),
+                format!("({} more entries truncated)", selection.omitted_rows),
+                "Truncated".to_string(),
+                selection.omitted_bytes.to_string(),
+            ];
+            if options.include_human_size {
+                record.push(format_size(selection.omitted_bytes));
+            }
+            if options.include_depth {
+                record.push(String::new());
+            }
+            if options.include_file_count {
+                record.push(selection.omitted_files.to_string());
+            }
+            if options.include_average_file_size {
+                let average = selection.omitted_bytes.checked_div(selection.omitted_files);
+                record.push(average.map(|a| a.to_string()).unwrap_or_default());
+            }
+            writer.write_record(&record)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Export per-extension totals to CSV (Pro feature only)
+    ///
+    /// One `Extension,Size (bytes),Files` row per extension from
+    /// `Node::size_by_extension`, largest first. Files without an extension
+    /// have an empty `Extension` column.
+    #[cfg(feature = "pro")]
+    pub fn export_extensions_csv<P: AsRef<Path>>(&self, root: &Node, output_path: P) -> anyhow::Result<()> {
+        if root.children.is_empty() {
+            return Err(ExportError::EmptyTree.into());
+        }
+
+        let mut writer = csv::Writer::from_path(output_path)?;
+        writer.write_record(["Extension", "Size (bytes)", "Files"])?;
+        for (ext, size, files) in sorted_extensions(root) {
+            writer.write_record([ext, size.to_string(), files.to_string()])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Export directory totals as a `du`-style listing (Pro feature only)
+    ///
+    /// Writes one `size<TAB>path` line per directory, using the allocated
+    /// (on-disk) size like `du` does. Paths are printed as scanned.
+    #[cfg(feature = "pro")]
+    pub fn export_du_style<P: AsRef<Path>>(
+        &self,
+        root: &Node,
+        output_path: P,
+        options: &DuStyleOptions,
+    ) -> anyhow::Result<()> {
+        use std::io::Write;
+
+        if root.children.is_empty() {
+            return Err(ExportError::EmptyTree.into());
+        }
+
+        let mut dirs: Vec<&Node> = root.iter().filter(|n| n.is_dir).collect();
+        match options.sort {
+            DuSort::Path => dirs.sort_by(|a, b| a.path.cmp(&b.path)),
+            DuSort::Size => dirs.sort_by(|a, b| {
+                b.allocated_size
+                    .cmp(&a.allocated_size)
+                    .then_with(|| a.path.cmp(&b.path))
+            }),
+        }
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(output_path)?);
+        for dir in dirs {
+            let size = if options.human_readable {
+                format_du_human(dir.allocated_size)
+            } else {
+                dir.allocated_size.div_ceil(1024).to_string()
+            };
+            writeln!(writer, "{}\t{}", size, dir.path.display())?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Export scan results in `format` with that format's default options
+    ///
+    /// # Errors
+    /// - `ExportError::FormatUnavailable` if the format's feature is not
+    ///   compiled in.
+    /// - `ExportError::EmptyTree` if the root has no children.
+    /// - Any I/O error from creating or writing the file.
+    pub fn export<P: AsRef<Path>>(&self, root: &Node, output_path: P, format: ExportFormat) -> anyhow::Result<()> {
+        let output_path = output_path.as_ref();
+        match format {
+            #[cfg(feature = "pro")]
+            ExportFormat::Csv => self.export_csv(root, output_path),
+            #[cfg(feature = "pro")]
+            ExportFormat::Du => self.export_du_style(root, output_path, &DuStyleOptions::default()),
+            #[cfg(feature = "pro")]
+            ExportFormat::Json => self.export_json(root, output_path),
+            ExportFormat::Tree => {
+                if root.children.is_empty() {
+                    return Err(ExportError::EmptyTree.into());
+                }
+                let mut writer = std::io::BufWriter::new(std::fs::File::create(output_path)?);
+                self.export_tree_text(root, &mut writer, None)?;
+                std::io::Write::flush(&mut writer)?;
+                Ok(())
+            }
+            #[cfg(feature = "svg")]
+            ExportFormat::Svg => self.export_summary_svg(root, output_path),
+            #[allow(unreachable_patterns)]
+            unavailable => Err(ExportError::FormatUnavailable(unavailable).into()),
+        }
+    }
+
+    /// Run the export configured with `with_auto_export`, if any
+    ///
+    /// Returns the path written, or `None` when no auto-export is set up.
+    /// Frontends call this after a successful, complete scan so unattended
+    /// runs leave a report behind.
+    pub fn auto_export(&self, root: &Node) -> anyhow::Result<Option<PathBuf>> {
+        let Some((path, format)) = &self.config.auto_export else {
+            return Ok(None);
+        };
+        self.export(root, path, *format)
+            .map_err(|e| e.context(format!("auto-export to {} failed", path.display())))?;
+        Ok(Some(path.clone()))
+    }
+
+    /// Export the tree as pretty-printed JSON (Pro feature only)
+    ///
+    /// Unlike CSV the nesting is kept: every node is an object with its
+    /// fields and a `children` array (left out for files and empty
+    /// directories). Read it back with `serde_json::from_reader::<_, Node>`.
+    /// Fails with `ExportError::EmptyTree` when the root has no children.
+    #[cfg(feature = "pro")]
+    pub fn export_json<P: AsRef<Path>>(&self, root: &Node, output_path: P) -> anyhow::Result<()> {
+        self.export_json_with_options(root, output_path, &JsonOptions::default())
+    }
+
+    /// Export the tree as JSON with explicit options (Pro feature only)
+    #[cfg(feature = "pro")]
+    pub fn export_json_with_options<P: AsRef<Path>>(
+        &self,
+        root: &Node,
+        output_path: P,
+        options: &JsonOptions,
+    ) -> anyhow::Result<()> {
+        use std::io::Write;
+
+        if root.children.is_empty() {
+            return Err(ExportError::EmptyTree.into());
+        }
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(output_path)?);
+        if options.compact {
+            serde_json::to_writer(&mut writer, root)?;
+        } else {
+            serde_json::to_writer_pretty(&mut writer, root)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Export the tree as JSON lines (Pro feature only)
+    ///
+    /// One compact object per node with `path`, `name`, `size`, `is_dir` and
+    /// `depth` (0 for the root), parents before their children. Each line
+    /// is written as soon as it is built, so memory stays flat however big
+    /// the tree is, and the output can be piped through `jq` line by line.
+    /// Fails with `ExportError::EmptyTree` when the root has no children.
+    #[cfg(feature = "pro")]
+    pub fn export_ndjson<P: AsRef<Path>>(&self, root: &Node, output_path: P) -> anyhow::Result<()> {
+        use std::io::Write;
+
+        #[derive(Serialize)]
+        struct Line<'a> {
+            path: std::borrow::Cow<'a, str>,
+            name: &'a str,
+            size: u64,
+            is_dir: bool,
+            depth: usize,
+        }
+
+        if root.children.is_empty() {
+            return Err(ExportError::EmptyTree.into());
+        }
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(output_path)?);
+        let mut stack = vec![(root, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let line = Line {
+                path: node.path.to_string_lossy(),
+                name: &node.name,
+                size: node.size,
+                is_dir: node.is_dir,
+                depth,
+            };
+            serde_json::to_writer(&mut writer, &line)?;
+            writer.write_all(b"\n")?;
+            stack.extend(node.children.iter().rev().map(|c| (c, depth + 1)));
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Export the tree as a self-contained HTML report (Pro feature only)
+    ///
+    /// One file with inline styles and no scripts or external assets, so it
+    /// can be mailed or dropped on a share as is. Directories are collapsible
+    /// (`<details>`), the top level starts expanded, and every entry shows a
+    /// bar and percentage relative to its parent. Siblings are ordered by
+    /// size, largest first. Every node is written, so very large trees make
+    /// large files. Fails with `ExportError::EmptyTree` when the root has no
+    /// children.
+    #[cfg(feature = "pro")]
+    pub fn export_html<P: AsRef<Path>>(&self, root: &Node, output_path: P) -> anyhow::Result<()> {
+        if root.children.is_empty() {
+            return Err(ExportError::EmptyTree.into());
+        }
+        std::fs::write(output_path, render_html_report(root))?;
+        Ok(())
+    }
+
+    /// Save a finished scan so it can be reopened later with
+    /// `Scanner::load_scan` instead of walking the disk again (Pro feature only)
+    ///
+    /// The file is MessagePack: much smaller and faster to read back than
+    /// JSON for big trees. Modification times are not stored, so
+    /// `Scanner::rescan` of a loaded tree reads every directory again, like
+    /// a fresh scan.
+    #[cfg(feature = "pro")]
+    pub fn save_scan<P: AsRef<Path>>(
+        &self,
+        root: &Node,
+        report: &ScanReport,
+        output_path: P,
+    ) -> anyhow::Result<()> {
+        use std::io::Write;
+
+        #[derive(Serialize)]
+        struct SavedScan<'a> {
+            version: u32,
+            root: &'a Node,
+            report: &'a ScanReport,
+        }
+
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(output_path)?);
+        let saved = SavedScan {
+            version: SCAN_FILE_VERSION,
+            root,
+            report,
+        };
+        rmp_serde::encode::write_named(&mut writer, &saved)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Read back a scan written by `Scanner::save_scan` (Pro feature only)
+    #[cfg(feature = "pro")]
+    pub fn load_scan<P: AsRef<Path>>(path: P) -> anyhow::Result<(Node, ScanReport)> {
+        #[derive(Deserialize)]
+        struct SavedScan {
+            version: u32,
+            root: Node,
+            report: ScanReport,
+        }
+
+        let path = path.as_ref();
+        let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+        let saved: SavedScan = rmp_serde::from_read(reader)
+            .map_err(|e| anyhow::anyhow!("{} is not a saved scan: {}", path.display(), e))?;
+        if saved.version != SCAN_FILE_VERSION {
+            anyhow::bail!(
+                "{} was saved in scan file version {}, expected {}",
+                path.display(),
+                saved.version,
+                SCAN_FILE_VERSION
+            );
+        }
+        Ok((saved.root, saved.report))
+    }
+
+    /// Write the tree as indented plain text, like the `tree` command
+    ///
+    /// Each line is `name [size]`, children in their stored order with
+    /// `├──`/`└──` connectors. Available without the `pro` feature.
+    pub fn export_tree_text(
+        &self,
+        root: &Node,
+        output: &mut impl std::io::Write,
+        max_depth: Option<usize>,
+    ) -> anyhow::Result<()> {
+        let options = TreeTextOptions {
+            max_depth,
+            ..Default::default()
+        };
+        self.export_tree_text_with_options(root, output, &options)
+    }
+
+    /// Write the tree as indented plain text with explicit options
+    pub fn export_tree_text_with_options(
+        &self,
+        root: &Node,
+        output: &mut impl std::io::Write,
+        options: &TreeTextOptions,
+    ) -> anyhow::Result<()> {
+        writeln!(output, "{} [{}]", root.name, format_size(root.size))?;
+        write_tree_text(output, root, "", 1, options)?;
+        Ok(())
+    }
+
+    /// Export a one-page summary card as SVG (`svg` feature only)
+    ///
+    /// The card shows the total size and file count, bars for the five
+    /// largest top-level directories and the five extensions using the most
+    /// space. Rendered with plain string templating; convert it with any SVG
+    /// tool if a PNG is needed.
+    #[cfg(feature = "svg")]
+    pub fn export_summary_svg<P: AsRef<Path>>(&self, root: &Node, output_path: P) -> anyhow::Result<()> {
+        if root.children.is_empty() {
+            return Err(ExportError::EmptyTree.into());
+        }
+        std::fs::write(output_path, render_summary_svg(root))?;
+        Ok(())
+    }
+
+    #[cfg(feature = "pro")]
+    fn write_node_csv(
+        &self,
+        writer: &mut csv::Writer<std::fs::File>,
+        node: &Node,
+        parent_path: &Path,
+        selection: Option<&NodeSelection>,
+        options: &ExportOptions,
+        depth: usize,
+    ) -> anyhow::Result<()> {
+        if selection.is_some_and(|s| !s.contains(node)) {
+            return Ok(());
+        }
+
+        let current_path = parent_path.join(&node.name);
+        let node_type = if node.is_dir { "Directory" } else { "File" };
+
+        if !options.files_only || node.is_file() {
+            let mut record = vec![
+                current_path.display().to_string(),
+                node.name.clone(),
+                node_type.to_string(),
+                node.size.to_string(),
+            ];
+            if options.include_human_size {
+                record.push(format_size(node.size));
+            }
+            if options.include_depth {
+                record.push(depth.to_string());
+            }
+            if options.include_file_count {
+                record.push(node.file_count.to_string());
+            }
+            if options.include_average_file_size {
+                let average = node.average_file_size(SizeMode::Apparent);
+                record.push(average.map(|a| a.to_string()).unwrap_or_default());
+            }
+            writer.write_record(&record)?;
+        }
+
+        for child in &node.children {
+            self.write_node_csv(writer, child, &current_path, selection, options, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// PUBLIC API
+// ============================================================================
+
+/// Scan a directory and build a tree structure of disk usage
+pub fn scan_directory<P: AsRef<Path>>(
+    root: P,
+    progress_tx: Option<mpsc::Sender<ScanProgress>>,
+) -> Result<Node, ScanError> {
+    Ok(scan_directory_with_report(root, progress_tx)?.0)
+}
+
+/// Scan a directory and return both the tree and a report
+pub fn scan_directory_with_report<P: AsRef<Path>>(
+    root: P,
+    progress_tx: Option<mpsc::Sender<ScanProgress>>,
+) -> Result<(Node, ScanReport), ScanError> {
+    scan_directory_with_report_shared(root, progress_tx, None, None)
+}
+
+/// Scan a directory and return both the tree and a report, while optionally updating shared progress.
+///
+/// Once `cancel` is set the walk stops and the partial tree built so far is
+/// returned (sizes rolled up and sorted as usual) with
+/// `ScanReport::cancelled` set, rather than an error.
+///
+/// Fails with `ScanError::RootNotFound` or `ScanError::RootNotADirectory`
+/// before walking anything if `root` is missing or not a directory.
+pub fn scan_directory_with_report_shared<P: AsRef<Path>>(
+    root: P,
+    progress_tx: Option<mpsc::Sender<ScanProgress>>,
+    shared_progress: Option<Arc<SharedProgress>>,
+    cancel: Option<Arc<AtomicBool>>,
+) -> Result<(Node, ScanReport), ScanError> {
+    let config = ScanConfig::default();
+    let control = ScanControl { cancel: cancel.as_deref(), ..Default::default() };
+    scan_impl(root.as_ref(), &config, WalkScope::default(), progress_tx, shared_progress, control, None)
+}
+
+/// Scan a directory using the given configuration, returning the tree and a report.
+pub fn scan_directory_with_config<P: AsRef<Path>>(
+    root: P,
+    config: &ScanConfig,
+    progress_tx: Option<mpsc::Sender<ScanProgress>>,
+    shared_progress: Option<Arc<SharedProgress>>,
+) -> Result<(Node, ScanReport), ScanError> {
+    scan_impl(root.as_ref(), config, WalkScope::default(), progress_tx, shared_progress, ScanControl::default(), None)
+}
+
+/// Resolve the path a frontend should scan.
+///
+/// Uses `arg` (typically the first command-line argument) when it is given
+/// and non-empty, otherwise the current working directory. Fails instead of
+/// guessing when the current directory cannot be determined (e.g. it was
+/// deleted), so every binary reports the same error.
+///
+/// # Example
+/// ```no_run
+/// let path = ferris_scan::resolve_scan_path(std::env::args_os().nth(1)).unwrap();
+/// ```
+pub fn resolve_scan_path<S: Into<std::ffi::OsString>>(arg: Option<S>) -> anyhow::Result<PathBuf> {
+    match arg.map(Into::into) {
+        Some(arg) if !arg.is_empty() => Ok(PathBuf::from(arg)),
+        _ => std::env::current_dir().map_err(|e| {
+            anyhow::anyhow!("could not determine the current directory ({}); pass a path to scan", e)
+        }),
+    }
+}
+
+/// One-line summary of a finished scan, e.g.
+/// `"1.50 GB across 1,203 files in 45 dirs (12 skipped)"`.
+///
+/// Directories are counted below the root, and the skipped clause is left
+/// out when nothing was skipped. Shared by every frontend and the scan log
+/// so totals read the same everywhere.
+pub fn summary_line(root: &Node, report: &ScanReport) -> String {
+    let dirs = root.iter().skip(1).filter(|n| n.is_dir).count() as u64;
+    let mut line = format!(
+        "{} across {} {} in {} {}",
+        format_size(root.size),
+        group_digits(root.file_count),
+        if root.file_count == 1 { "file" } else { "files" },
+        group_digits(dirs),
+        if dirs == 1 { "dir" } else { "dirs" }
+    );
+    if !report.skipped.is_empty() {
+        line.push_str(&format!(" ({} skipped)", group_digits(report.skipped.len() as u64)));
+    }
+    line
+}
+
+/// `part` as a percentage of `total`, or 0 when `total` is zero
+pub fn percent_of(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64 * 100.0
+    }
+}
+
+/// The immediate children of `node` as a small markdown table under a
+/// `breadcrumb` heading, for the frontends' snapshot export. Sizes and order
+/// follow `mode`. `|` in names is escaped so it cannot split a row.
+pub fn format_snapshot(breadcrumb: &str, node: &Node, mode: SizeMode) -> String {
+    let total = node.size_in(mode);
+    let mut out = format!(
+        "## {}\n\nTotal: {} ({} items)\n\n| Name | Size | % |\n|------|-----:|--:|\n",
+        breadcrumb,
+        format_size(total),
+        node.children.len()
+    );
+
+    let mut children: Vec<&Node> = node.children.iter().collect();
+    children.sort_by_key(|c| std::cmp::Reverse(c.size_in(mode)));
+    for child in children {
+        let suffix = if child.is_dir { "/" } else { "" };
+        out.push_str(&format!(
+            "| {}{} | {} | {:.1}% |\n",
+            child.name.replace('|', "\\|"),
+            suffix,
+            format_size(child.size_in(mode)),
+            percent_of(child.size_in(mode), total)
+        ));
+    }
+
+    out
+}
+
+/// File name the frontends save a `format_snapshot` of `node` under, e.g.
+/// `ferris-scan-snapshot-src.md` for a directory named `src`. Characters
+/// other than letters, digits, `-`, `_` and `.` become `_`; a name with
+/// nothing else left (such as `/`) becomes `root`.
+pub fn snapshot_file_name(node: &Node) -> String {
+    let name: String = node
+        .name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    let name = name.trim_matches(|c| c == '_' || c == '.');
+    format!("ferris-scan-snapshot-{}.md", if name.is_empty() { "root" } else { name })
+}
+
+/// How many children `format_explain` names individually unless a
+/// frontend asks for another number
+pub const DEFAULT_EXPLAIN_TOP_N: usize = 3;
+
+/// Explain what makes up `node`'s size in `mode`: its `top_n` largest
+/// children, each with its share, then one line totalling the rest.
+pub fn format_explain(node: &Node, top_n: usize, mode: SizeMode) -> String {
+    let total = node.size_in(mode);
+    let mut out = format!("Why is {} {}?\n\n", node.name, format_size(total));
+
+    if node.children.is_empty() {
+        out.push_str("No children to break down.");
+        return out;
+    }
+
+    let mut children: Vec<&Node> = node.children.iter().collect();
+    children.sort_by_key(|c| std::cmp::Reverse(c.size_in(mode)));
+    let (top, others) = children.split_at(top_n.min(children.len()));
+    for child in top {
+        let suffix = if child.is_dir { "/" } else { "" };
+        out.push_str(&format!(
+            "{:>5.1}%  {}{} ({})\n",
+            percent_of(child.size_in(mode), total),
+            child.name,
+            suffix,
+            format_size(child.size_in(mode))
+        ));
+    }
+
+    if !others.is_empty() {
+        let others_size = others.iter().fold(0u64, |sum, c| sum.saturating_add(c.size_in(mode)));
+        out.push_str(&format!(
+            "{:>5.1}%  {} other items ({})\n",
+            percent_of(others_size, total),
+            others.len(),
+            format_size(others_size)
+        ));
+    }
+
+    out
+}
+
+/// Most rows the frontends' flat file views hold; the largest files are kept
+pub const FLAT_VIEW_LIMIT: usize = 10_000;
+
+/// The `limit` largest files under `root` as `(path, size)` with sizes in
+/// `mode`, in `FlatSort::Size` order, and how many files were left out.
+pub fn flatten_files(root: &Node, limit: usize, mode: SizeMode) -> (Vec<(PathBuf, u64)>, usize) {
+    let files: Vec<(PathBuf, u64)> = root
+        .largest_files_in(limit, mode)
+        .into_iter()
+        .map(|n| (n.path.clone(), n.size_in(mode)))
+        .collect();
+    let omitted = root.iter_files().count() - files.len();
+    (files, omitted)
+}
+
+/// Re-sort a list from `flatten_files`
+pub fn sort_flat_files(files: &mut [(PathBuf, u64)], sort: FlatSort) {
+    match sort {
+        FlatSort::Size => files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0))),
+        FlatSort::Path => files.sort_by(|a, b| a.0.cmp(&b.0)),
+    }
+}
+
+/// `n` with comma thousands separators, e.g. `1,203,456`
+pub fn group_digits(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Format version written by `Scanner::save_scan`; bumped whenever saved
+/// scans from older versions can no longer be read
+#[cfg(feature = "pro")]
+const SCAN_FILE_VERSION: u32 = 1;
+
+/// Command-line flag naming the file a finished scan is exported to
+pub const AUTO_EXPORT_FLAG: &str = "--auto-export";
+
+/// Command-line flag choosing the auto-export format (see
+/// `ExportFormat::from_name`)
+pub const EXPORT_FORMAT_FLAG: &str = "--export-format";
+
+/// Command-line flag opening a scan saved with `Scanner::save_scan` instead
+/// of scanning (loading needs the `pro` feature)
+pub const LOAD_SCAN_FLAG: &str = "--load";
+
+/// Turn the values of `--auto-export` and `--export-format` into a
+/// `ScanConfig::auto_export` setting.
+///
+/// Without a format the output path's extension decides. Fails on an
+/// unknown format, a format without a path, or a path whose format cannot
+/// be guessed, so a misconfigured scheduled run stops before scanning.
+pub fn resolve_auto_export<S: Into<std::ffi::OsString>>(
+    path: Option<S>,
+    format: Option<S>,
+) -> anyhow::Result<Option<(PathBuf, ExportFormat)>> {
+    let format = match format.map(Into::into) {
+        Some(name) => {
+            let name = name.to_string_lossy().into_owned();
+            Some(ExportFormat::from_name(&name).ok_or_else(|| {
+                anyhow::anyhow!("unknown export format '{}' (expected csv, du, json, tree or svg)", name)
+            })?)
+        }
+        None => None,
+    };
+    let Some(path) = path.map(|p| PathBuf::from(p.into())) else {
+        return match format {
+            Some(_) => Err(anyhow::anyhow!("{} needs {} <path>", EXPORT_FORMAT_FLAG, AUTO_EXPORT_FLAG)),
+            None => Ok(None),
+        };
+    };
+    let format = format.or_else(|| ExportFormat::from_path(&path)).ok_or_else(|| {
+        anyhow::anyhow!(
+            "cannot tell the export format of {}; pass {} csv|du|json|tree|svg",
+            path.display(),
+            EXPORT_FORMAT_FLAG
+        )
+    })?;
+    Ok(Some((path, format)))
+}
+
+/// Share of a directory's size a single child needs before frontends treat
+/// it as dominant (see `Node::dominant_child`)
+pub const DEFAULT_DOMINANT_FRACTION: f64 = 0.95;
+
+/// Environment variable that, when set, makes frontends skip their
+/// confirmation before scanning an entire disk.
+pub const SUPPRESS_ROOT_WARNING_ENV: &str = "FERRIS_SCAN_NO_ROOT_WARNING";
+
+/// Environment variable that, when set, makes frontends follow a deletion
+/// with `AfterDelete::RescanParent` instead of updating sizes in place.
+pub const RESCAN_AFTER_DELETE_ENV: &str = "FERRIS_SCAN_RESCAN_AFTER_DELETE";
+
+/// How a frontend refreshes the tree after deleting an entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AfterDelete {
+    /// Subtract the deleted entry's totals from its ancestors
+    /// (`Node::delete_node` alone). Instant, but trusts the in-memory tree.
+    #[default]
+    UpdateInPlace,
+    /// Also rescan the deleted entry's parent directory with
+    /// `Scanner::rescan_subtree`, so the tree matches what is on disk.
+    RescanParent,
+}
+
+impl AfterDelete {
+    /// `RescanParent` if `RESCAN_AFTER_DELETE_ENV` is set, else the default
+    pub fn from_env() -> Self {
+        if std::env::var_os(RESCAN_AFTER_DELETE_ENV).is_some() {
+            AfterDelete::RescanParent
+        } else {
+            AfterDelete::UpdateInPlace
+        }
+    }
+}
+
+/// Whether `path` is the root of a filesystem, such as `/`, `C:\`, or a
+/// mount point.
+///
+/// A path is a filesystem root when it has no parent, or (on Unix) when its
+/// parent is on a different device. Frontends use this to warn before a
+/// full-disk scan. Paths that cannot be resolved are not roots.
+pub fn is_filesystem_root(path: &Path) -> bool {
+    let Ok(path) = std::fs::canonicalize(path) else {
+        return false;
+    };
+    let Some(parent) = path.parent() else {
+        return true;
+    };
+    let device = |p: &Path| std::fs::metadata(p).ok().and_then(|md| device_id(&md));
+    match (device(&path), device(parent)) {
+        (Some(dev), Some(parent_dev)) => dev != parent_dev,
+        _ => false,
+    }
+}
+
+// ============================================================================
+// INTERNAL HELPERS
+// ============================================================================
+
+/// The scan walk shared by all public entry points.
+///
+/// `scope.materialize_depth` limits how deep `Node`s are created (root is
+/// depth 0). Entries below the limit are still walked and their bytes are
+/// credited to the deepest materialized ancestor, so totals are unaffected.
+/// The shallower of it and `ScanConfig::max_depth` applies. The rest of
+/// `scope` lets `Scanner::rescan` walk one directory of an existing tree;
+/// with `scope.setup` given, the root is not validated again and the walk
+/// is logged at debug level only.
+///
+/// `control.cancel` is checked before each entry; once set, the walk stops
+/// and the partial tree is finished (sizes rolled up, sorted) as usual.
+/// While `control.pause` is set the walk waits before the next entry.
+///
+/// `shared_inodes` carries file identities across several scans (see
+/// `Scanner::scan_many`); when given, every file is tracked rather than only
+/// those with more than one link.
+fn scan_impl(
+    root: &Path,
+    config: &ScanConfig,
+    scope: WalkScope<'_>,
+    progress_tx: Option<mpsc::Sender<ScanProgress>>,
+    shared_progress: Option<Arc<SharedProgress>>,
+    control: ScanControl<'_>,
+    shared_inodes: Option<&mut HashSet<FileId>>,
+) -> Result<(Node, ScanReport), ScanError> {
+    let prepared;
+    let setup = match scope.setup {
+        Some(setup) => setup,
+        None => {
+            validate_root(root)?;
+            prepared = WalkSetup::prepare(root, config)?;
+            &prepared
+        }
+    };
+    let cancel = control.cancel;
+    let start = Instant::now();
+    let root_path = root.to_path_buf();
+    if scope.setup.is_some() {
+        log::debug!("walking {}", root_path.display());
+    } else {
+        log::info!("scan started: {}", root_path.display());
+    }
+    // `max_depth` counts from the scan root, which may be above `root`
+    let max_depth = config.max_depth.map(|max| max.saturating_sub(scope.depth));
+    let materialize_depth = match (scope.materialize_depth, max_depth) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+    let mut report = ScanReport::default();
+
+    let mut root_node = Node::new(root_display_name(&root_path), root_path.clone(), true);
+
+    let track_all_inodes = shared_inodes.is_some();
+    let mut local_inodes = HashSet::new();
+    let seen_inodes = shared_inodes.unwrap_or(&mut local_inodes);
+
+    let mut files_scanned: usize = 0;
+    let mut bytes_scanned: u64 = 0;
+    let mut dirs_scanned: usize = 0;
+    let mut top_level: Vec<PartialEntry> = Vec::new();
+    let mut last_publish = Instant::now();
+    #[cfg(target_os = "linux")]
+    if scope.setup.is_none() {
+        report.overlay_mounts = find_overlay_mounts(&root_path);
+        if !report.overlay_mounts.is_empty() {
+            log::info!("overlay mounts under scan root: {:?}", report.overlay_mounts);
+        }
+    }
+
+    let follow_links = config.symlink_policy == SymlinkPolicy::Follow;
+    let mut walker = Walker::new(&root_path)
+        .sort(true)
+        .follow_links(follow_links)
+        .root_read_dir_state(scope.gitignores);
+    if scope.list_only {
+        walker = walker.max_depth(1);
+    }
+    if let Some(parallelism) = walk_parallelism(config.threads) {
+        walker = walker.parallelism(parallelism);
+    }
+    #[cfg(windows)]
+    {
+        walker = walker.follow_links(follow_links || config.follow_reparse_points);
+    }
+
+    let root_dev = if config.one_filesystem {
+        std::fs::metadata(&root_path).ok().and_then(|md| device_id(&md))
+    } else {
+        None
+    };
+    let dir_filter = config.entry_filter.clone();
+    let exclude_symlinks = config.exclude_symlinks;
+    let excludes = setup.excludes.clone();
+    let respect_gitignore = config.respect_gitignore;
+    // Directories already walked, so followed links cannot loop or recount
+    let visited_dirs = if follow_links {
+        let root_id = std::fs::metadata(&root_path).ok().and_then(|md| file_id(&md, true));
+        Some(Mutex::new(root_id.into_iter().collect::<HashSet<FileId>>()))
+    } else {
+        None
+    };
+    if root_dev.is_some()
+        || dir_filter.is_some()
+        || exclude_symlinks
+        || excludes.is_some()
+        || respect_gitignore
+        || visited_dirs.is_some()
+    {
+        // Exclude patterns are relative to the scan root
+        let walk_root = scope.scan_root.unwrap_or(&root_path).to_path_buf();
+        walker = walker.process_read_dir(move |depth, dir, gitignores, children| {
+            // No depth: the listing holding the root itself, read from the
+            // root's parent, whose .gitignore does not apply
+            if respect_gitignore && depth.is_some() {
+                gitignores.enter(dir);
+            }
+            for entry in children.iter_mut().flatten() {
+                // The root itself is listed at depth 0 and is never filtered
+                if entry.depth == 0 {
+                    continue;
+                }
+                if exclude_symlinks && entry.path_is_symlink() {
+                    entry.read_children_path = None;
+                    continue;
+                }
+                let is_dir = entry.file_type().is_dir();
+                if let Some(ref excludes) = excludes {
+                    let path = entry.path();
+                    let relative = path.strip_prefix(&walk_root).unwrap_or(&path);
+                    if excludes.is_excluded(relative, is_dir) {
+                        entry.read_children_path = None;
+                        entry.client_state.excluded = true;
+                        continue;
+                    }
+                }
+                if respect_gitignore && gitignores.is_ignored(&entry.path(), is_dir) {
+                    entry.read_children_path = None;
+                    entry.client_state.gitignored = true;
+                    continue;
+                }
+                if !is_dir {
+                    continue;
+                }
+                let Ok(md) = entry.metadata() else {
+                    continue;
+                };
+                if let Some(ref filter) = dir_filter {
+                    if !filter.matches(&entry.path(), &md) {
+                        entry.read_children_path = None;
+                        entry.client_state.filtered_out = true;
+                        continue;
+                    }
+                }
+                if let Some(root_dev) = root_dev {
+                    if device_id(&md).is_some_and(|dev| is_mount_boundary(root_dev, dev, true)) {
+                        entry.read_children_path = None;
+                        entry.client_state.crossed_mount = true;
+                    }
+                }
+            }
+
+            let Some(Ok(mut visited)) = visited_dirs.as_ref().map(Mutex::lock) else {
+                return;
+            };
+            // Real directories claim their ids before links in the same
+            // listing, so a link to a sibling is the one left undescended
+            for links in [false, true] {
+                for entry in children.iter_mut().flatten() {
+                    if entry.depth == 0 || entry.read_children_path.is_none() || entry.path_is_symlink() != links {
+                        continue;
+                    }
+                    let Some(id) = entry.metadata().ok().and_then(|md| file_id(&md, true)) else {
+                        continue;
+                    };
+                    if !visited.insert(id) {
+                        entry.read_children_path = None;
+                        entry.client_state.revisited = true;
+                    }
+                }
+            }
+        });
+    }
+
+    let estimate_stop = StopOnDrop(Arc::new(AtomicBool::new(false)));
+    if let (Some(sp), true) = (&shared_progress, config.estimate_total) {
+        let (sp, stop, root) = (Arc::clone(sp), Arc::clone(&estimate_stop.0), root_path.clone());
+        let pause = control.pause.cloned();
+        let threads = config.threads;
+        std::thread::spawn(move || {
+            if let Some(total) = count_files(&root, &stop, pause.as_deref(), threads) {
+                log::debug!("estimated {} files under {}", total, root.display());
+                sp.total_files_estimate.store(total, Ordering::Relaxed);
+            }
+        });
+    }
+    if let Some(ref sp) = shared_progress {
+        sp.push_rate_sample(Instant::now(), 0);
+    }
+
+    for entry in walker {
+        if control.is_paused() {
+            log::info!("scan paused after {} files", files_scanned);
+            wait_while_paused(control.pause.map(|p| p.as_ref()), cancel);
+            log::info!("scan resumed");
+        }
+        if cancel.is_some_and(|c| c.load(Ordering::Relaxed)) {
+            log::info!("scan cancelled after {} files", files_scanned);
+            report.cancelled = true;
+            break;
+        }
+
+        match entry {
+            Ok(entry) => {
+                let path = entry.path();
+                // jwalk attaches a failed listing to the directory entry itself
+                let mut access_denied = false;
+                if let Some(ref e) = entry.read_children_error {
+                    if root_vanished(&root_path) {
+                        log::warn!("scan root disappeared: {}", root_path.display());
+                        report.root_lost = true;
+                        break;
+                    }
+                    record_walk_error(config, control, &mut report, e, Some(&path))?;
+                    access_denied = classify_error(e) == Some(SkipReason::PermissionDenied);
+                }
+                if path == root_path {
+                    root_node.access_denied = access_denied;
+                    continue;
+                }
+
+                if let Some(ref sp) = shared_progress {
+                    if let Ok(mut lp) = sp.last_path.lock() {
+                        *lp = Some(path.to_path_buf());
+                    }
+                }
+
+                if let Some(ref tx) = progress_tx {
+                    let _ = tx.send(ScanProgress {
+                        files_scanned,
+                        dirs_scanned,
+                        bytes_scanned,
+                        current_path: path.to_path_buf(),
+                        elapsed: start.elapsed(),
+                    });
+                }
+
+                let Ok(relative) = path.strip_prefix(&root_path) else {
+                    continue;
+                };
+
+                if entry.client_state.filtered_out {
+                    continue;
+                }
+
+                if entry.client_state.excluded {
+                    log::debug!("excluded: {}", path.display());
+                    if entry.file_type().is_dir() {
+                        report.excluded.push(path.to_path_buf());
+                    }
+                    continue;
+                }
+
+                if entry.client_state.gitignored {
+                    log::debug!("ignored by .gitignore: {}", path.display());
+                    report.gitignored += 1;
+                    continue;
+                }
+
+                if entry.client_state.crossed_mount {
+                    log::debug!("not crossing into {}", path.display());
+                    report.mount_points.push(path.to_path_buf());
+                    continue;
+                }
+
+                let is_link = entry.path_is_symlink();
+                if is_link && config.exclude_symlinks {
+                    log::debug!("excluded symlink: {}", path.display());
+                    report.symlinks_excluded += 1;
+                    continue;
+                }
+                #[cfg(windows)]
+                if is_link {
+                    log::debug!("reparse point: {}", path.display());
+                    report.reparse_points.push(path.to_path_buf());
+                }
+
+                let beyond_depth = materialize_depth
+                    .filter(|&depth| relative.components().count() > depth);
+
+                let is_dir = entry.file_type().is_dir();
+                if entry.client_state.revisited {
+                    log::debug!("already walked, not descending: {}", path.display());
+                    if is_link {
+                        report.symlinks_skipped.push(path.to_path_buf());
+                    }
+                }
+                if is_link && !is_dir && config.symlink_policy == SymlinkPolicy::Skip {
+                    log::debug!("not counting symlink: {}", path.display());
+                    report.symlinks_skipped.push(path.to_path_buf());
+                    if beyond_depth.is_none() {
+                        add_file_to_tree(&mut root_node, relative, 0).is_symlink = true;
+                    }
+                    continue;
+                }
+                if is_dir {
+                    dirs_scanned += 1;
+                    if let Some(ref sp) = shared_progress {
+                        sp.dirs_scanned.store(dirs_scanned as u64, Ordering::Relaxed);
+                    }
+                    if beyond_depth.is_none() {
+                        let node = ensure_dir_path(&mut root_node, relative);
+                        node.is_symlink = is_link;
+                        node.access_denied = access_denied;
+                        node.mtime = entry.metadata().ok().and_then(|md| md.modified().ok());
+                    }
+                    continue;
+                }
+
+                if setup.skip_special {
+                    if let Some(kind) = special_file_kind(&entry.file_type()) {
+                        log::debug!("skipped {}: {}", kind, path.display());
+                        control.record_skipped(&mut report, SkippedEntry {
+                            path: Some(path.to_path_buf()),
+                            reason: SkipReason::SpecialFile,
+                            message: format!("{} not counted", kind),
+                        });
+                        continue;
+                    }
+                }
+
+                #[cfg(feature = "profile")]
+                let metadata_start = Instant::now();
+                let md = entry.metadata();
+                #[cfg(feature = "profile")]
+                {
+                    let took = metadata_start.elapsed();
+                    if took >= config.slow_entry_threshold.unwrap_or(DEFAULT_SLOW_ENTRY_THRESHOLD) {
+                        log::debug!("slow metadata read ({:?}): {}", took, path.display());
+                        report.slow_entries.push((path.to_path_buf(), took));
+                    }
+                }
+                let md = match md {
+                    Ok(md) => md,
+                    Err(e) => {
+                        if root_vanished(&root_path) {
+                            log::warn!("scan root disappeared: {}", root_path.display());
+                            report.root_lost = true;
+                            break;
+                        }
+                        let reason = classify_error(&e);
+                        if reason == Some(SkipReason::PathTooLong) {
+                            report.long_paths.push(path.to_path_buf());
+                        }
+                        match retry_long_path(config, reason, &path) {
+                            Some(md) => md,
+                            None => {
+                                if let Some(reason) = reason {
+                                    check_skip_allowed(config, reason, Some(&path), &e)?;
+                                    log::warn!("skipped {} ({:?}): {}", path.display(), reason, e);
+                                    control.record_skipped(&mut report, SkippedEntry {
+                                        path: Some(path.to_path_buf()),
+                                        reason,
+                                        message: e.to_string(),
+                                    });
+                                }
+                                continue;
+                            }
+                        }
+                    }
+                };
+                if config.entry_filter.as_ref().is_some_and(|f| !f.matches(&path, &md)) {
+                    continue;
+                }
+                files_scanned += 1;
+                if let Some(ref sp) = shared_progress {
+                    sp.files_scanned.store(files_scanned as u64, Ordering::Relaxed);
+                }
+                #[cfg(windows)]
+                if !is_link && is_reparse_point(&md) {
+                    log::debug!("reparse point: {}", path.display());
+                    report.reparse_points.push(path.to_path_buf());
+                }
+                let mut size = md.len();
+                let mut allocated = allocated_size(&md, setup.cluster_size);
+                if config.dedup_hardlinks {
+                    if let Some(id) = file_id(&md, track_all_inodes) {
+                        if !seen_inodes.insert(id) {
+                            log::debug!("already counted hardlink: {}", path.display());
+                            report.hardlinks_deduplicated += 1;
+                            report.hardlink_bytes_saved = report.hardlink_bytes_saved.saturating_add(size);
+                            size = 0;
+                            allocated = 0;
+                        }
+                    }
+                }
+                bytes_scanned = bytes_scanned.saturating_add(size);
+                if let Some(ref sp) = shared_progress {
+                    sp.bytes_scanned.store(bytes_scanned, Ordering::Relaxed);
+                }
+
+                if let Some(depth) = beyond_depth {
+                    let ancestor: PathBuf = relative.components().take(depth).collect();
+                    let node = ensure_dir_path(&mut root_node, &ancestor);
+                    node.size = node.size.saturating_add(size);
+                    node.allocated_size = node.allocated_size.saturating_add(allocated);
+                    node.file_count += 1;
+                    if config.track_largest_file && node.largest_file.as_ref().is_none_or(|(_, s)| size > *s) {
+                        node.largest_file = Some((path.to_path_buf(), size));
+                    }
+                } else {
+                    let node = add_file_to_tree(&mut root_node, relative, size);
+                    node.is_symlink = is_link;
+                    node.allocated_size = allocated;
+                    node.mtime = md.modified().ok();
+                }
+
+                if let Some(ref sp) = shared_progress {
+                    record_top_level(&mut top_level, relative, size);
+                    if last_publish.elapsed() >= PARTIAL_PUBLISH_INTERVAL {
+                        publish_top_level(sp, &mut top_level);
+                        last_publish = Instant::now();
+                        sp.push_rate_sample(last_publish, files_scanned as u64);
+                    }
+                }
+            }
+            Err(e) => {
+                if root_vanished(&root_path) {
+                    log::warn!("scan root disappeared: {}", root_path.display());
+                    report.root_lost = true;
+                    break;
+                }
+                // The walker's own check for a followed link back to an ancestor
+                if let (Some(ancestor), Some(path)) = (e.loop_ancestor(), e.path()) {
+                    log::debug!("symlink loop to {}: {}", ancestor.display(), path.display());
+                    report.symlinks_skipped.push(path.to_path_buf());
+                    if let Ok(relative) = path.strip_prefix(&root_path) {
+                        if materialize_depth.is_none_or(|depth| relative.components().count() <= depth) {
+                            add_file_to_tree(&mut root_node, relative, 0).is_symlink = true;
+                        }
+                    }
+                    continue;
+                }
+                record_walk_error(config, control, &mut report, &e, e.path())?;
+                continue;
+            }
+        }
+    }
+
+    log::debug!("walk finished after {} files in {} directories", files_scanned, dirs_scanned);
+    root_node.mtime = std::fs::metadata(&root_path).and_then(|md| md.modified()).ok();
+    if let Some(ref sp) = shared_progress {
+        publish_top_level(sp, &mut top_level);
+    }
+
+    log::debug!("rolling up directory sizes");
+    calculate_dir_sizes(&mut root_node);
+    if config.track_largest_file {
+        log::debug!("finding largest file per directory");
+        find_largest_files(&mut root_node);
+    }
+    // A listing's subdirectories are still empty; `Scanner::rescan` folds
+    // once the tree is complete
+    if let (Some(min_dir_size), false) = (config.min_dir_size, scope.list_only) {
+        log::debug!("folding directories smaller than {} bytes", min_dir_size);
+        prune_small_dirs(&mut root_node, min_dir_size, config.size_mode);
+    }
+    log::debug!("sorting tree");
+    sort_tree(&mut root_node, config.deterministic, config.size_mode);
+
+    if config.measure_unaccounted && scope.scan_root.is_none() && !report.cancelled && !report.root_lost {
+        report.unaccounted_bytes = measure_unaccounted(&root_path, root_node.allocated_size);
+    }
+
+    report.elapsed = start.elapsed();
+    if scope.setup.is_some() {
+        log::debug!("walked {} in {:?}", root_path.display(), report.elapsed);
+    } else {
+        log::info!(
+            "scan finished: {} in {:?}",
+            summary_line(&root_node, &report),
+            report.elapsed
+        );
+    }
+    Ok((root_node, report))
+}
+
+/// Print the children of `node` for `export_tree_text_with_options`.
+/// `prefix` holds the guide lines of the levels above; `depth` is the level
+/// of the children.
+fn write_tree_text(
+    output: &mut impl std::io::Write,
+    node: &Node,
+    prefix: &str,
+    depth: usize,
+    options: &TreeTextOptions,
+) -> std::io::Result<()> {
+    if options.max_depth.is_some_and(|max| depth > max) {
+        return Ok(());
+    }
+
+    let width = options.indent_width.max(2);
+    let dashes = "─".repeat(width - 2);
+    let padding = " ".repeat(width - 1);
+    for (i, child) in node.children.iter().enumerate() {
+        let last = i + 1 == node.children.len();
+        let (connector, guide) = if last { ('└', ' ') } else { ('├', '│') };
+        writeln!(
+            output,
+            "{}{}{} {} [{}]",
+            prefix,
+            connector,
+            dashes,
+            child.name,
+            format_size(child.size)
+        )?;
+        if !child.children.is_empty() {
+            let child_prefix = format!("{}{}{}", prefix, guide, padding);
+            write_tree_text(output, child, &child_prefix, depth + 1, options)?;
+        }
+    }
+    Ok(())
+}
+
+/// Human-readable size in powers of 1024 with the familiar `KB`/`MB`
+/// labels (as Windows Explorer shows them), e.g. `4.20 MB`.
+///
+/// Bytes are shown exactly (`1023 B`); larger sizes get two decimals. Use
+/// `format_size_with_units` for strict binary or decimal units.
+pub fn format_size(bytes: u64) -> String {
+    scale_size(bytes, 1024.0, &["B", "KB", "MB", "GB", "TB"])
+}
+
+/// Human-readable size in the given unit system, e.g. `4.20 MiB` or
+/// `4.40 MB` for the same 4,404,019 bytes
+pub fn format_size_with_units(bytes: u64, units: SizeUnits) -> String {
+    match units {
+        SizeUnits::Binary => scale_size(bytes, 1024.0, &["B", "KiB", "MiB", "GiB", "TiB"]),
+        SizeUnits::Decimal => scale_size(bytes, 1000.0, &["B", "KB", "MB", "GB", "TB"]),
+    }
+}
+
+/// `bytes` in the given frontend format: `format_size` for
+/// `SizeFormat::Auto`, the exact count for `SizeFormat::Bytes`
+pub fn format_size_as(bytes: u64, format: SizeFormat) -> String {
+    match format {
+        SizeFormat::Auto => format_size(bytes),
+        SizeFormat::Bytes => format!("{} B", group_digits(bytes)),
+    }
+}
+
+fn scale_size(bytes: u64, base: f64, units: &[&str]) -> String {
+    let mut size = bytes as f64;
+    let mut unit_idx = 0;
+
+    while size >= base && unit_idx < units.len() - 1 {
+        size /= base;
+        unit_idx += 1;
+    }
+
+    if unit_idx == 0 {
+        format!("{} {}", bytes, units[unit_idx])
+    } else {
+        format!("{:.2} {}", size, units[unit_idx])
+    }
+}
+
+/// Format a size the way `du -h` does: powers of 1024, rounded up, with one
+/// decimal below 10.
+#[cfg(any(feature = "pro", feature = "svg"))]
+fn format_du_human(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+    if bytes < 1024 {
+        return bytes.to_string();
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 10.0 {
+        let tenths = (value * 10.0).ceil() / 10.0;
+        if tenths < 10.0 {
+            return format!("{:.1}{}", tenths, UNITS[unit]);
+        }
+    }
+    format!("{}{}", value.ceil() as u64, UNITS[unit])
+}
+
+/// `Node::size_by_extension` as `(extension, bytes, files)`, largest first
+/// and by extension for equal sizes
+#[cfg(any(feature = "pro", feature = "svg"))]
+fn sorted_extensions(root: &Node) -> Vec<(String, u64, u64)> {
+    let mut exts: Vec<(String, u64, u64)> = root
+        .size_by_extension()
+        .into_iter()
+        .map(|(ext, (size, files))| (ext, size, files))
+        .collect();
+    exts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    exts
+}
+
+/// Render the summary card for `export_summary_svg`
+#[cfg(feature = "svg")]
+fn render_summary_svg(root: &Node) -> String {
+    use std::fmt::Write;
+
+    const WIDTH: u32 = 600;
+    const BAR_MAX: f64 = 320.0;
+    const TOP: usize = 5;
+
+    let mut dirs: Vec<&Node> = root.children.iter().filter(|c| c.is_dir).collect();
+    dirs.sort_by_key(|d| std::cmp::Reverse(d.size));
+    dirs.truncate(TOP);
+
+    let exts: Vec<(String, u64)> = sorted_extensions(root)
+        .into_iter()
+        .take(TOP)
+        .map(|(ext, size, _)| {
+            let label = if ext.is_empty() { "(none)".to_string() } else { format!(".{}", ext) };
+            (label, size)
+        })
+        .collect();
+
+    let rows: Vec<(&str, Vec<(String, u64)>)> = vec![
+        ("Largest directories", dirs.iter().map(|d| (d.name.clone(), d.size)).collect()),
+        ("Largest extensions", exts),
+    ];
+    let height = 110 + rows.iter().map(|(_, r)| 40 + 24 * r.len() as u32).sum::<u32>();
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="sans-serif">"#,
+        w = WIDTH,
+        h = height
+    );
+    let _ = writeln!(svg, r##"<rect width="100%" height="100%" fill="#1e1e2e"/>"##);
+    let _ = writeln!(
+        svg,
+        r##"<text x="20" y="36" font-size="20" fill="#f5e0dc">{}</text>"##,
+        xml_escape(&root.path.display().to_string())
+    );
+    let _ = writeln!(
+        svg,
+        r##"<text x="20" y="64" font-size="14" fill="#cdd6f4">Total: {} in {} files</text>"##,
+        format_du_human(root.size),
+        root.file_count
+    );
+
+    let mut y = 100;
+    for (title, entries) in rows {
+        let _ = writeln!(svg, r##"<text x="20" y="{}" font-size="15" fill="#fab387">{}</text>"##, y, title);
+        y += 16;
+        for (label, bytes) in entries {
+            let fraction = if root.size == 0 { 0.0 } else { bytes as f64 / root.size as f64 };
+            let _ = writeln!(
+                svg,
+                r##"<text x="20" y="{}" font-size="12" fill="#cdd6f4">{}</text>"##,
+                y + 13,
+                xml_escape(&label)
+            );
+            let _ = writeln!(
+                svg,
+                r##"<rect x="190" y="{}" width="{:.1}" height="16" fill="#89b4fa"/>"##,
+                y,
+                (fraction * BAR_MAX).max(1.0)
+            );
+            let _ = writeln!(
+                svg,
+                r##"<text x="{}" y="{}" font-size="12" fill="#cdd6f4">{} ({:.1}%)</text>"##,
+                WIDTH - 80,
+                y + 13,
+                format_du_human(bytes),
+                fraction * 100.0
+            );
+            y += 24;
+        }
+        y += 24;
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Escape text for use inside SVG/XML and HTML
+#[cfg(any(feature = "pro", feature = "svg"))]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Page around `Scanner::export_html`'s tree; `{{title}}`, `{{summary}}`
+/// and `{{tree}}` are filled in by `fill_template`
+#[cfg(feature = "pro")]
+const HTML_REPORT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Disk usage: {{title}}</title>
+<style>
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.3em; word-break: break-all; }
+ul { list-style: none; margin: 0; padding-left: 1.4em; }
+ul.tree { padding-left: 0; }
+li { margin: 2px 0; }
+summary { cursor: pointer; }
+.row { display: inline-flex; gap: 0.6em; align-items: center; }
+.bar { display: inline-block; width: 120px; height: 0.8em; background: #e4e4e4; }
+.bar > span { display: block; height: 100%; background: #4a90d9; }
+.size { min-width: 6.5em; text-align: right; font-variant-numeric: tabular-nums; }
+.pct { min-width: 3.5em; text-align: right; color: #777; }
+</style>
+</head>
+<body>
+<h1>{{title}}</h1>
+<p>{{summary}}</p>
+<ul class="tree">
+{{tree}}</ul>
+</body>
+</html>
+"#;
+
+/// Build the page written by `Scanner::export_html`
+#[cfg(feature = "pro")]
+fn render_html_report(root: &Node) -> String {
+    let title = xml_escape(&root.path.display().to_string());
+    let summary = format!("Total: {} in {} files", format_size(root.size), root.file_count);
+    let mut tree = String::new();
+    write_html_node(&mut tree, root, root.size, 0);
+    fill_template(
+        HTML_REPORT_TEMPLATE,
+        &[("title", &title), ("summary", &summary), ("tree", &tree)],
+    )
+}
+
+/// One `<li>` for `node`, with its children nested in a `<details>` list
+#[cfg(feature = "pro")]
+fn write_html_node(out: &mut String, node: &Node, parent_size: u64, depth: usize) {
+    use std::fmt::Write;
+
+    let percent = if parent_size == 0 {
+        0.0
+    } else {
+        node.size as f64 / parent_size as f64 * 100.0
+    };
+    let suffix = if node.is_dir { "/" } else { "" };
+    let row = format!(
+        r#"<span class="row"><span class="bar"><span style="width:{:.1}%"></span></span><span class="size">{}</span><span class="pct">{:.1}%</span><span class="name">{}{}</span></span>"#,
+        percent,
+        format_size(node.size),
+        percent,
+        xml_escape(&node.name),
+        suffix
+    );
+    if node.children.is_empty() {
+        let _ = writeln!(out, "<li>{}</li>", row);
+        return;
+    }
+
+    let open = if depth == 0 { " open" } else { "" };
+    let _ = writeln!(out, "<li><details{}><summary>{}</summary><ul>", open, row);
+    let mut children: Vec<&Node> = node.children.iter().collect();
+    children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    for child in children {
+        write_html_node(out, child, node.size, depth + 1);
+    }
+    out.push_str("</ul></details></li>\n");
+}
+
+/// Replace each `{{key}}` in `template` with its value in a single pass, so
+/// inserted text is never searched for markers itself. Unknown markers are
+/// kept as they are.
+#[cfg(feature = "pro")]
+fn fill_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start..].find("}}") else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let marker = &rest[start..start + len + 2];
+        let key = &marker[2..marker.len() - 2];
+        match values.iter().find(|(k, _)| *k == key) {
+            Some((_, value)) => out.push_str(value),
+            None => out.push_str(marker),
+        }
+        rest = &rest[start + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Subset of a tree chosen for a size-capped export
+#[cfg(feature = "pro")]
+struct NodeSelection {
+    kept: std::collections::HashSet<*const Node>,
+    /// Nodes left out of the export
+    omitted_rows: usize,
+    /// Bytes of the omitted subtrees directly under kept nodes
+    omitted_bytes: u64,
+    /// Files in the omitted subtrees
+    omitted_files: u64,
+}
+
+#[cfg(feature = "pro")]
+impl NodeSelection {
+    fn contains(&self, node: &Node) -> bool {
+        self.kept.contains(&(node as *const Node))
+    }
+}
+
+/// Pick the `max` largest nodes best-first from the root, so every kept node's
+/// parent is kept too.
+#[cfg(feature = "pro")]
+fn select_largest_nodes(root: &Node, max: usize) -> NodeSelection {
+    use std::collections::{BinaryHeap, HashSet};
+
+    // Heap entries: (size, tie-break sequence, index into `frontier`)
+    let mut frontier: Vec<&Node> = vec![root];
+    let mut heap = BinaryHeap::from([(root.size, std::cmp::Reverse(0usize), 0usize)]);
+    let mut kept = HashSet::new();
+
+    while kept.len() < max {
+        let Some((_, _, idx)) = heap.pop() else {
+            break;
+        };
+        let node = frontier[idx];
+        kept.insert(node as *const Node);
+        for child in &node.children {
+            frontier.push(child);
+            let seq = frontier.len() - 1;
+            heap.push((child.size, std::cmp::Reverse(seq), seq));
+        }
+    }
+
+    let omitted_bytes = heap
+        .iter()
+        .fold(0u64, |acc, (size, _, _)| acc.saturating_add(*size));
+    let omitted_rows = heap
+        .iter()
+        .map(|(_, _, idx)| frontier[*idx].total_nodes())
+        .sum();
+    let omitted_files = heap.iter().map(|(_, _, idx)| frontier[*idx].file_count).sum();
+
+    NodeSelection {
+        kept,
+        omitted_rows,
+        omitted_bytes,
+        omitted_files,
+    }
+}
+
+/// Name of a non-regular, non-directory, non-symlink file type
+#[cfg(unix)]
+fn special_file_kind(file_type: &std::fs::FileType) -> Option<&'static str> {
+    use std::os::unix::fs::FileTypeExt;
+    if file_type.is_fifo() {
+        Some("FIFO")
+    } else if file_type.is_socket() {
+        Some("socket")
+    } else if file_type.is_block_device() {
+        Some("block device")
+    } else if file_type.is_char_device() {
+        Some("character device")
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn special_file_kind(_file_type: &std::fs::FileType) -> Option<&'static str> {
+    None
+}
+
+/// Bytes a file occupies on disk. `cluster_size` is the allocation unit of
+/// the scanned volume where the platform has no per-file block count.
+#[cfg(unix)]
+fn allocated_size(md: &std::fs::Metadata, _cluster_size: Option<u64>) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    // st_blocks is always in 512-byte units, regardless of the filesystem block size
+    md.blocks().saturating_mul(512)
+}
+
+#[cfg(not(unix))]
+fn allocated_size(md: &std::fs::Metadata, cluster_size: Option<u64>) -> u64 {
+    round_up_to_cluster(md.len(), cluster_size)
+}
+
+/// `len` rounded up to whole clusters; unchanged without a cluster size.
+/// Compressed, sparse and MFT-resident files really use less, so this is
+/// an upper bound there.
+#[cfg(any(not(unix), test))]
+fn round_up_to_cluster(len: u64, cluster_size: Option<u64>) -> u64 {
+    match cluster_size.filter(|&c| c > 0) {
+        Some(cluster) => len.div_ceil(cluster).saturating_mul(cluster),
+        None => len,
+    }
+}
+
+/// Allocation unit of the volume holding `path`, for `allocated_size`
+#[cfg(windows)]
+fn volume_cluster_size(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetVolumePathNameW(file_name: *const u16, volume_path: *mut u16, length: u32) -> i32;
+        fn GetDiskFreeSpaceW(
+            root_path: *const u16,
+            sectors_per_cluster: *mut u32,
+            bytes_per_sector: *mut u32,
+            free_clusters: *mut u32,
+            total_clusters: *mut u32,
+        ) -> i32;
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut volume = vec![0u16; 1024];
+    // SAFETY: `wide` is NUL-terminated and `volume` holds `volume.len()` elements
+    if unsafe { GetVolumePathNameW(wide.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) } == 0 {
+        log::debug!("GetVolumePathNameW failed for {}: {}", path.display(), std::io::Error::last_os_error());
+        return None;
+    }
+    let (mut sectors_per_cluster, mut bytes_per_sector, mut free, mut total) = (0u32, 0u32, 0u32, 0u32);
+    // SAFETY: `volume` was NUL-terminated by the call above and the out pointers are valid
+    let ok = unsafe {
+        GetDiskFreeSpaceW(volume.as_ptr(), &mut sectors_per_cluster, &mut bytes_per_sector, &mut free, &mut total)
+    };
+    if ok == 0 {
+        log::debug!("GetDiskFreeSpaceW failed for {}: {}", path.display(), std::io::Error::last_os_error());
+        return None;
+    }
+    Some(u64::from(sectors_per_cluster) * u64::from(bytes_per_sector))
+}
+
+#[cfg(not(windows))]
+fn volume_cluster_size(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Identity of a file on disk: (device, inode)
+type FileId = (u64, u64);
+
+/// File identity for hardlink deduplication.
+///
+/// Files with a single link can only be reached twice through bind mounts or
+/// overlapping roots, so they are skipped unless `track_all` is set.
+#[cfg(unix)]
+fn file_id(md: &std::fs::Metadata, track_all: bool) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    if track_all || md.nlink() > 1 {
+        Some((md.dev(), md.ino()))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn file_id(_md: &std::fs::Metadata, _track_all: bool) -> Option<FileId> {
+    None
+}
+
+/// Per-entry state attached by the walk's `process_read_dir` hook
+#[derive(Debug, Default, Clone)]
+struct EntryState {
+    /// Directory lives on a different filesystem than the scan root
+    crossed_mount: bool,
+    /// Directory was rejected by `ScanConfig::entry_filter`
+    filtered_out: bool,
+    /// Entry matched `ScanConfig::excludes`
+    excluded: bool,
+    /// Entry is ignored by a `.gitignore` (`ScanConfig::respect_gitignore`)
+    gitignored: bool,
+    /// Directory was already walked through another path (followed links)
+    revisited: bool,
+}
+
+type Walker = WalkDirGeneric<(GitignoreStack, EntryState)>;
+
+/// `.gitignore` matchers of the directory being read and of its ancestors up
+/// to the scan root, shallowest first. jwalk hands each directory's state down
+/// to its subdirectories.
+#[derive(Debug, Clone, Default)]
+struct GitignoreStack(Arc<Vec<Gitignore>>);
+
+impl GitignoreStack {
+    /// Add the `.gitignore` of `dir`, if it has one
+    fn enter(&mut self, dir: &Path) {
+        let file = dir.join(".gitignore");
+        if !file.is_file() {
+            return;
+        }
+        let mut builder = GitignoreBuilder::new(dir);
+        if let Some(e) = builder.add(&file) {
+            log::warn!("problem reading {}: {}", file.display(), e);
+        }
+        match builder.build() {
+            Ok(gitignore) => {
+                let mut stack = Vec::clone(&self.0);
+                stack.push(gitignore);
+                self.0 = Arc::new(stack);
+            }
+            Err(e) => log::warn!("ignoring {}: {}", file.display(), e),
+        }
+    }
+
+    /// Whether the deepest `.gitignore` with an opinion on `path` ignores it
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for gitignore in self.0.iter().rev() {
+            match gitignore.matched(path, is_dir) {
+                Match::None => continue,
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+            }
+        }
+        false
+    }
+}
+
+/// Compiled `ScanConfig::excludes`, split by what each pattern is matched
+/// against and whether it applies to directories only
+#[derive(Debug)]
+struct ExcludeSet {
+    names: GlobSet,
+    dir_names: GlobSet,
+    paths: GlobSet,
+    dir_paths: GlobSet,
+}
+
+impl ExcludeSet {
+    /// Compile `patterns`, or `None` if there are none
+    fn build(patterns: &[String]) -> Result<Option<Self>, ScanError> {
+        if patterns.is_empty() {
+            return Ok(None);
+        }
+        let mut names = GlobSetBuilder::new();
+        let mut dir_names = GlobSetBuilder::new();
+        let mut paths = GlobSetBuilder::new();
+        let mut dir_paths = GlobSetBuilder::new();
+        for pattern in patterns {
+            let (glob, dir_only) = match pattern.strip_suffix('/') {
+                Some(glob) => (glob, true),
+                None => (pattern.as_str(), false),
+            };
+            let compiled = GlobBuilder::new(glob)
+                .literal_separator(true)
+                .build()
+                .map_err(|e| ScanError::InvalidExclude { pattern: pattern.clone(), message: e.to_string() })?;
+            let set = match (glob.contains('/'), dir_only) {
+                (false, false) => &mut names,
+                (false, true) => &mut dir_names,
+                (true, false) => &mut paths,
+                (true, true) => &mut dir_paths,
+            };
+            set.add(compiled);
+        }
+        let invalid = |e: globset::Error| ScanError::InvalidExclude {
+            pattern: patterns.join(", "),
+            message: e.to_string(),
+        };
+        Ok(Some(Self {
+            names: names.build().map_err(invalid)?,
+            dir_names: dir_names.build().map_err(invalid)?,
+            paths: paths.build().map_err(invalid)?,
+            dir_paths: dir_paths.build().map_err(invalid)?,
+        }))
+    }
+
+    /// Whether the entry at `relative` (to the scan root) is excluded
+    fn is_excluded(&self, relative: &Path, is_dir: bool) -> bool {
+        let name_matches = |set: &GlobSet| relative.file_name().is_some_and(|name| set.is_match(name));
+        name_matches(&self.names)
+            || self.paths.is_match(relative)
+            || (is_dir && (name_matches(&self.dir_names) || self.dir_paths.is_match(relative)))
+    }
+}
+
+/// `ScanReport::unaccounted_bytes` for a finished scan of `root_path`
+fn measure_unaccounted(root_path: &Path, scanned: u64) -> Option<i64> {
+    if !is_filesystem_root(root_path) {
+        log::debug!("{} is not a filesystem root, not comparing with used space", root_path.display());
+        return None;
+    }
+    let used = filesystem_used_bytes(root_path)?;
+    log::debug!("filesystem reports {} bytes used, scan found {}", used, scanned);
+    Some(unaccounted_difference(used, scanned))
+}
+
+/// `used - scanned` as a signed value, saturating at the `i64` range
+fn unaccounted_difference(used: u64, scanned: u64) -> i64 {
+    if used >= scanned {
+        i64::try_from(used - scanned).unwrap_or(i64::MAX)
+    } else {
+        i64::try_from(scanned - used).map_or(i64::MIN, |d| -d)
+    }
+}
+
+/// Bytes in use on the filesystem holding `path`, computed like `df`:
+/// total blocks minus free blocks (reserved blocks count as used).
+#[cfg(unix)]
+fn filesystem_used_bytes(path: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = std::ffi::CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `c_path` is NUL-terminated and `stat` is a valid out pointer
+    if unsafe { libc::statvfs(c_path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        log::debug!("statvfs failed for {}: {}", path.display(), std::io::Error::last_os_error());
+        return None;
+    }
+    // SAFETY: statvfs returned success, so the struct is initialized
+    let stat = unsafe { stat.assume_init() };
+    // The field types are platform dependent (u32 on macOS, u64 on Linux)
+    #[allow(clippy::useless_conversion)]
+    let used_blocks = u64::from(stat.f_blocks).saturating_sub(u64::from(stat.f_bfree));
+    #[allow(clippy::useless_conversion)]
+    let block_size = u64::from(stat.f_frsize);
+    used_blocks.checked_mul(block_size)
+}
+
+/// Bytes in use on the volume holding `path` (total minus free)
+#[cfg(windows)]
+fn filesystem_used_bytes(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(
+            directory: *const u16,
+            free_to_caller: *mut u64,
+            total: *mut u64,
+            total_free: *mut u64,
+        ) -> i32;
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let (mut free_to_caller, mut total, mut total_free) = (0u64, 0u64, 0u64);
+    // SAFETY: `wide` is NUL-terminated and the out pointers are valid
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut free_to_caller, &mut total, &mut total_free) };
+    if ok == 0 {
+        log::debug!("GetDiskFreeSpaceExW failed for {}: {}", path.display(), std::io::Error::last_os_error());
+        return None;
+    }
+    Some(total.saturating_sub(total_free))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn filesystem_used_bytes(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(unix)]
+fn device_id(md: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(md.dev())
+}
+
+#[cfg(not(unix))]
+fn device_id(_md: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Whether an entry on `entry_dev` should be treated as a different filesystem.
+///
+/// Only directories can be mount points. Overlayfs reports the lower layer's
+/// device for non-directory objects, so files are never treated as boundaries.
+fn is_mount_boundary(root_dev: u64, entry_dev: u64, is_dir: bool) -> bool {
+    is_dir && entry_dev != root_dev
+}
+
+#[cfg(target_os = "linux")]
+fn find_overlay_mounts(root: &Path) -> Vec<PathBuf> {
+    let root = std::fs::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    std::fs::read_to_string("/proc/self/mountinfo")
+        .map(|info| parse_overlay_mounts(&info, &root))
+        .unwrap_or_default()
+}
+
+/// Extract overlay mount points related to `root` from `/proc/self/mountinfo` text.
+///
+/// A mount is related if it is inside the scan root or if the scan root is
+/// inside it (e.g. scanning `/` from within a container).
+#[cfg(any(target_os = "linux", test))]
+fn parse_overlay_mounts(mountinfo: &str, root: &Path) -> Vec<PathBuf> {
+    let mut mounts = Vec::new();
+    for line in mountinfo.lines() {
+        // Fields: id parent major:minor root mount_point options [optional...] - fstype source super_options
+        let Some((pre, post)) = line.split_once(" - ") else {
+            continue;
+        };
+        if post.split_whitespace().next() != Some("overlay") {
+            continue;
+        }
+        let Some(mount_point) = pre.split_whitespace().nth(4) else {
+            continue;
+        };
+        let mount_point = PathBuf::from(mount_point.replace("\\040", " "));
+        if mount_point.starts_with(root) || root.starts_with(&mount_point) {
+            mounts.push(mount_point);
+        }
+    }
+    mounts
+}
+
+/// How often the walk republishes `SharedProgress::top_level`
+const PARTIAL_PUBLISH_INTERVAL: Duration = Duration::from_millis(250);
+
+fn record_top_level(top_level: &mut Vec<PartialEntry>, relative: &Path, size: u64) {
+    let mut components = relative.components();
+    let Some(first) = components.next() else {
+        return;
+    };
+    let is_dir = components.next().is_some();
+    let name = first.as_os_str().to_string_lossy();
+
+    match top_level.iter_mut().find(|e| e.name == name) {
+        Some(existing) => {
+            existing.size = existing.size.saturating_add(size);
+            existing.is_dir |= is_dir;
+        }
+        None => top_level.push(PartialEntry {
+            name: name.to_string(),
+            size,
+            is_dir,
+        }),
+    }
+}
+
+/// How far back `SharedProgress::rate_per_sec` looks
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Sets the flag when dropped, so a helper thread stops however the scan ends
+struct StopOnDrop(Arc<AtomicBool>);
+
+impl Drop for StopOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Where a `scan_impl` walk sits in the tree it belongs to. Plain scans use
+/// the default; `Scanner::rescan` and `Scanner::rescan_subtree` walk single
+/// directories of an existing tree and need them treated as they were in
+/// the full scan.
+#[derive(Debug, Clone, Default)]
+struct WalkScope<'a> {
+    /// Deepest level to create `Node`s for, counted from the walked directory
+    materialize_depth: Option<usize>,
+    /// Root of the whole scan, which exclude patterns are relative to. The
+    /// walked directory itself when `None`.
+    scan_root: Option<&'a Path>,
+    /// Levels between `scan_root` and the walked directory, for `max_depth`
+    depth: usize,
+    /// `.gitignore` files of the walked directory's ancestors
+    gitignores: GitignoreStack,
+    /// Read only the walked directory; its subdirectories come back empty
+    list_only: bool,
+    /// Setup done once for `scan_root`; `scan_impl` prepares its own when
+    /// `None`
+    setup: Option<&'a WalkSetup>,
+}
+
+/// The per-scan work `scan_impl` would otherwise repeat for every walk,
+/// decided from the root of the whole scan
+#[derive(Debug)]
+struct WalkSetup {
+    /// Leave out FIFOs, sockets and device files
+    skip_special: bool,
+    /// Cluster size of the scanned volume, for allocated sizes
+    cluster_size: Option<u64>,
+    /// Compiled `ScanConfig::excludes`, shared with the walker threads
+    excludes: Option<Arc<ExcludeSet>>,
+}
+
+impl WalkSetup {
+    fn prepare(scan_root: &Path, config: &ScanConfig) -> Result<Self, ScanError> {
+        Ok(Self {
+            skip_special: config
+                .skip_special_files
+                .unwrap_or_else(|| is_filesystem_root(scan_root)),
+            cluster_size: volume_cluster_size(scan_root),
+            excludes: ExcludeSet::build(&config.excludes)?.map(Arc::new),
+        })
+    }
+}
+
+/// Pause and cancel flags `scan_impl` checks before each entry, and where
+/// it reports skipped entries live
+#[derive(Debug, Clone, Copy, Default)]
+struct ScanControl<'a> {
+    cancel: Option<&'a AtomicBool>,
+    /// Shared rather than borrowed so the estimate thread can wait on it too
+    pause: Option<&'a Arc<AtomicBool>>,
+    skipped_tx: Option<&'a mpsc::Sender<SkippedEntry>>,
+}
+
+impl ScanControl<'_> {
+    fn is_paused(&self) -> bool {
+        self.pause.is_some_and(|p| p.load(Ordering::Relaxed))
+    }
+
+    /// Add `entry` to the report, sending a copy first if a receiver wants it
+    fn record_skipped(&self, report: &mut ScanReport, entry: SkippedEntry) {
+        if let Some(tx) = self.skipped_tx {
+            let _ = tx.send(entry.clone());
+        }
+        report.skipped.push(entry);
+    }
+}
+
+/// How often a paused walk checks whether it may continue
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Sleep until `pause` is cleared or `stop` is set
+fn wait_while_paused(pause: Option<&AtomicBool>, stop: Option<&AtomicBool>) {
+    while pause.is_some_and(|p| p.load(Ordering::Relaxed)) && !stop.is_some_and(|s| s.load(Ordering::Relaxed)) {
+        std::thread::sleep(PAUSE_POLL_INTERVAL);
+    }
+}
+
+/// jwalk parallelism for `ScanConfig::threads`, `None` for jwalk's default
+fn walk_parallelism(threads: Option<usize>) -> Option<jwalk::Parallelism> {
+    match threads? {
+        0 => None,
+        1 => Some(jwalk::Parallelism::Serial),
+        n => Some(jwalk::Parallelism::RayonNewPool(n)),
+    }
+}
+
+/// Count the files under `root` for `ScanConfig::estimate_total`, listing
+/// directories without reading metadata with the scan's `threads`. Waits
+/// while `pause` is set and gives up with `None` once `stop` is set.
+fn count_files(root: &Path, stop: &AtomicBool, pause: Option<&AtomicBool>, threads: Option<usize>) -> Option<u64> {
+    let mut walker = jwalk::WalkDir::new(root);
+    if let Some(parallelism) = walk_parallelism(threads) {
+        walker = walker.parallelism(parallelism);
+    }
+    let mut count = 0u64;
+    for entry in walker.into_iter().flatten() {
+        wait_while_paused(pause, Some(stop));
+        if stop.load(Ordering::Relaxed) {
+            return None;
+        }
+        if !entry.file_type().is_dir() {
+            count += 1;
+        }
+    }
+    Some(count)
+}
+
+fn publish_top_level(sp: &SharedProgress, top_level: &mut [PartialEntry]) {
+    top_level.sort_by_key(|e| std::cmp::Reverse(e.size));
+    log::trace!("publishing {} top-level entries", top_level.len());
+    if let Ok(mut published) = sp.top_level.lock() {
+        published.clear();
+        published.extend_from_slice(top_level);
+    }
+}
+
+/// Turn a skip into a scan error when `ScanConfig::on_permission_error` is
+/// `SkipOrFail::Fail` and the entry was denied.
+fn check_skip_allowed(
+    config: &ScanConfig,
+    reason: SkipReason,
+    path: Option<&Path>,
+    error: &dyn std::fmt::Display,
+) -> Result<(), ScanError> {
+    if reason == SkipReason::PermissionDenied && config.on_permission_error == SkipOrFail::Fail {
+        return Err(ScanError::PermissionDenied {
+            path: path.map(Path::to_path_buf),
+            message: error.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Record a walk error in the report if it is one worth reporting (see
+/// `classify_error`), or fail when `ScanConfig::on_permission_error` says so.
+fn record_walk_error(
+    config: &ScanConfig,
+    control: ScanControl<'_>,
+    report: &mut ScanReport,
+    e: &jwalk::Error,
+    path: Option<&Path>,
+) -> Result<(), ScanError> {
+    let Some(reason) = classify_error(e) else {
+        return Ok(());
+    };
+    check_skip_allowed(config, reason, path, e)?;
+    if reason == SkipReason::PathTooLong {
+        report.long_paths.extend(path.map(Path::to_path_buf));
+    }
+    log::warn!("skipped entry ({:?}): {}", reason, e);
+    control.record_skipped(report, SkippedEntry {
+        path: path.map(Path::to_path_buf),
+        reason,
+        message: e.to_string(),
+    });
+    Ok(())
+}
+
+/// Check that the scan root exists and is a directory (following symlinks),
+/// so a file root fails up front instead of producing a one-node tree
+fn validate_root(root: &Path) -> Result<(), ScanError> {
+    match std::fs::metadata(root) {
+        Ok(md) if md.is_dir() => Ok(()),
+        Ok(_) => Err(ScanError::RootNotADirectory(root.to_path_buf())),
+        Err(e) => Err(ScanError::from_root_io(root, e)),
+    }
+}
+
+/// Whether the scan root can no longer be read. Only checked after a walk
+/// error, so a vanished root stops the scan instead of failing every entry.
+fn root_vanished(root: &Path) -> bool {
+    std::fs::symlink_metadata(root).is_err()
+}
+
+/// Decide whether a walk error should be recorded in the report, and why.
+///
+/// Errors that are neither permission nor network related are dropped silently,
+/// matching the scanner's best-effort behavior.
+fn classify_error(e: &jwalk::Error) -> Option<SkipReason> {
+    e.io_error().and_then(classify_io_error)
+}
+
+fn classify_io_error(e: &std::io::Error) -> Option<SkipReason> {
+    use std::io::ErrorKind;
+    match e.kind() {
+        ErrorKind::PermissionDenied => Some(SkipReason::PermissionDenied),
+        ErrorKind::TimedOut
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::NotConnected
+        | ErrorKind::BrokenPipe => Some(SkipReason::Network),
+        _ if is_network_os_error(e) => Some(SkipReason::Network),
+        _ if is_path_too_long_os_error(e) => Some(SkipReason::PathTooLong),
+        _ => None,
+    }
+}
+
+/// ERROR_FILENAME_EXCED_RANGE, reported for paths over MAX_PATH
+#[cfg(windows)]
+fn is_path_too_long_os_error(e: &std::io::Error) -> bool {
+    e.raw_os_error() == Some(206)
+}
+
+#[cfg(not(windows))]
+fn is_path_too_long_os_error(_e: &std::io::Error) -> bool {
+    false
+}
+
+/// Re-read metadata through the extended-length form of `path` after a
+/// `SkipReason::PathTooLong` failure, if `ScanConfig::retry_long_paths` is set.
+#[cfg(windows)]
+fn retry_long_path(config: &ScanConfig, reason: Option<SkipReason>, path: &Path) -> Option<std::fs::Metadata> {
+    if !config.retry_long_paths || reason != Some(SkipReason::PathTooLong) {
+        return None;
+    }
+    // The prefix disables path normalization, so it needs an absolute path
+    let absolute = std::path::absolute(path).ok()?;
+    let md = std::fs::symlink_metadata(extended_length_path(&absolute));
+    if md.is_ok() {
+        log::debug!("read over-long path via extended-length prefix: {}", path.display());
+    }
+    md.ok()
+}
+
+#[cfg(not(windows))]
+fn retry_long_path(_config: &ScanConfig, _reason: Option<SkipReason>, _path: &Path) -> Option<std::fs::Metadata> {
+    None
+}
+
+/// `C:\dir` becomes `\\?\C:\dir` and `\\server\share` becomes
+/// `\\?\UNC\server\share`. Already-prefixed paths are returned as is.
+#[cfg(any(windows, test))]
+fn extended_length_path(path: &Path) -> PathBuf {
+    let text = path.as_os_str().to_string_lossy();
+    if text.starts_with(r"\\?\") {
+        path.to_path_buf()
+    } else if let Some(unc) = text.strip_prefix(r"\\") {
+        PathBuf::from(format!(r"\\?\UNC\{}", unc))
+    } else {
+        PathBuf::from(format!(r"\\?\{}", text))
+    }
+}
+
+/// Windows reports most SMB failures as raw OS errors without a useful `ErrorKind`.
+#[cfg(windows)]
+fn is_network_os_error(e: &std::io::Error) -> bool {
+    // ERROR_BAD_NETPATH, ERROR_NETWORK_BUSY, ERROR_BAD_NET_RESP, ERROR_UNEXP_NET_ERR,
+    // ERROR_NETNAME_DELETED, ERROR_NETWORK_ACCESS_DENIED, ERROR_BAD_NET_NAME,
+    // ERROR_SEM_TIMEOUT, ERROR_NETWORK_UNREACHABLE
+    const NETWORK_ERRORS: &[i32] = &[53, 54, 58, 59, 64, 65, 67, 121, 1231];
+    e.raw_os_error().is_some_and(|code| NETWORK_ERRORS.contains(&code))
+}
+
+#[cfg(not(windows))]
+fn is_network_os_error(_e: &std::io::Error) -> bool {
+    false
+}
+
+/// Display name for the scan root.
+///
+/// Roots such as `/`, `C:\`, `\\server\share` and `\\?\UNC\server\share` have no
+/// final component, so `file_name()` returns `None`; fall back to the full path.
+fn root_display_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        None if path.as_os_str().is_empty() => ".".to_string(),
+        None => path.display().to_string(),
+    }
+}
+
+/// Check the `FILE_ATTRIBUTE_REPARSE_POINT` attribute.
+#[cfg(windows)]
+fn is_reparse_point(md: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+    md.file_attributes() & FILE_ATTRIBUTE_REPARSE_POINT != 0
+}
+
+fn ensure_dir_path<'a>(root: &'a mut Node, path: &Path) -> &'a mut Node {
+    let mut current = root;
+    for component in path.components() {
+        let name = component.as_os_str().to_string_lossy().to_string();
+        let existing_idx = current.children.iter().position(|c| c.name == name);
+        let idx = match existing_idx {
+            Some(i) => i,
+            None => {
+                current.children.push(Node::new(
+                    name.clone(),
+                    current.path.join(&name),
+                    true,
+                ));
+                current.children.len() - 1
+            }
+        };
+        current = &mut current.children[idx];
+        current.is_dir = true;
+    }
+    current
+}
+
+fn add_file_to_tree<'a>(root: &'a mut Node, path: &Path, size: u64) -> &'a mut Node {
+    let mut current = root;
+    let mut components = path.components().peekable();
+
+    while let Some(component) = components.next() {
+        let name = component.as_os_str().to_string_lossy().to_string();
+        let is_leaf = components.peek().is_none();
+
+        let existing_idx = current.children.iter().position(|c| c.name == name);
+        let idx = match existing_idx {
+            Some(i) => i,
+            None => {
+                current.children.push(Node::new(
+                    name.clone(),
+                    current.path.join(&name),
+                    !is_leaf,
+                ));
+                current.children.len() - 1
+            }
+        };
+
+        current = &mut current.children[idx];
+
+        if is_leaf {
+            current.is_dir = false;
+            current.file_count = 1;
+            current.size = current.size.saturating_add(size);
+        } else {
+            current.is_dir = true;
+        }
+    }
+    current
+}
+
+/// Roll child sizes and file counts up into directories.
+///
+/// A directory's own `size`, `allocated_size` and `file_count` going in are
+/// kept and added to: they hold descendants that were not materialized (see
+/// `scan_impl`). Call once on a freshly walked tree.
+fn calculate_dir_sizes(node: &mut Node) {
+    if !node.is_dir {
+        return;
+    }
+
+    for child in &mut node.children {
+        calculate_dir_sizes(child);
+        node.size = node.size.saturating_add(child.size);
+        node.allocated_size = node.allocated_size.saturating_add(child.allocated_size);
+        node.file_count += child.file_count;
+    }
+}
+
+/// Fill in `largest_file` for every directory, returning the largest file
+/// under `node` (or `node` itself if it is a file).
+///
+/// A directory's pre-existing `largest_file` comes from files that were not
+/// materialized and is kept as a candidate.
+fn find_largest_files(node: &mut Node) -> Option<(PathBuf, u64)> {
+    if node.is_file() {
+        return Some((node.path.clone(), node.size));
+    }
+    if node.is_summary {
+        return None;
+    }
+
+    let mut largest = node.largest_file.take();
+    for child in &mut node.children {
+        if let Some(candidate) = find_largest_files(child) {
+            if largest.as_ref().is_none_or(|(_, size)| candidate.1 > *size) {
+                largest = Some(candidate);
+            }
+        }
+    }
+    node.largest_file = largest.clone();
+    largest
+}
+
+/// Replace child directories smaller than `min_size` with a single summary entry.
+///
+/// Must run after `calculate_dir_sizes`. The summary node carries the combined
+/// size of the folded directories so parent totals are unchanged.
+fn prune_small_dirs(node: &mut Node, min_size: u64, mode: SizeMode) {
+    let mut folded_size = 0u64;
+    let mut folded_allocated = 0u64;
+    let mut folded_files = 0u64;
+    let mut folded_count = 0usize;
+
+    node.children.retain(|child| {
+        if child.is_dir && child.size_in(mode) < min_size {
+            folded_size = folded_size.saturating_add(child.size);
+            folded_allocated = folded_allocated.saturating_add(child.allocated_size);
+            folded_files += child.file_count;
+            folded_count += 1;
+            false
+        } else {
+            true
+        }
+    });
+
+    for child in &mut node.children {
+        if child.is_dir {
+            prune_small_dirs(child, min_size, mode);
+        }
+    }
+
+    if folded_count > 0 {
+        let name = format!("<{} small directories>", folded_count);
+        let mut summary = Node::new(name.clone(), node.path.join(&name), false);
+        summary.is_summary = true;
+        summary.size = folded_size;
+        summary.allocated_size = folded_allocated;
+        summary.file_count = folded_files;
+        node.children.push(summary);
+    }
+}
+
+/// Sort every directory's children by size, largest first. With
+/// `deterministic`, equal sizes are ordered by name instead of walk order.
+fn sort_tree(node: &mut Node, deterministic: bool, mode: SizeMode) {
+    node.children.sort_by(|a, b| sibling_order(a, b, deterministic, mode));
+    for child in &mut node.children {
+        sort_tree(child, deterministic, mode);
+    }
+}
+
+/// Order of two siblings in a scanned tree, as used by `sort_tree`
+fn sibling_order(a: &Node, b: &Node, deterministic: bool, mode: SizeMode) -> std::cmp::Ordering {
+    let order = b.size_in(mode).cmp(&a.size_in(mode));
+    if deterministic {
+        order.then_with(|| a.name.cmp(&b.name))
+    } else {
+        order
+    }
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_scan_empty_directory() {
+        let dir = tempdir().unwrap();
+        let (tx, _rx) = mpsc::channel();
+        let result = scan_directory(dir.path(), Some(tx));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_scanner_api() {
+        let dir = tempdir().unwrap();
+        let scanner = Scanner::new();
+        let result = scanner.scan(dir.path());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_shared_progress_publishes_top_level_totals() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/a.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("sub/b.bin"), vec![0u8; 50]).unwrap();
+        std::fs::write(dir.path().join("file.txt"), vec![0u8; 10]).unwrap();
+
+        let progress = Arc::new(SharedProgress::default());
+        Scanner::new()
+            .scan_with_progress(dir.path(), Arc::clone(&progress))
+            .unwrap();
+
+        let top_level = progress.top_level.lock().unwrap();
+        assert_eq!(
+            *top_level,
+            vec![
+                PartialEntry { name: "sub".to_string(), size: 150, is_dir: true },
+                PartialEntry { name: "file.txt".to_string(), size: 10, is_dir: false },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_io_error() {
+        use std::io::{Error, ErrorKind};
+        assert_eq!(
+            classify_io_error(&Error::from(ErrorKind::PermissionDenied)),
+            Some(SkipReason::PermissionDenied)
+        );
+        assert_eq!(
+            classify_io_error(&Error::from(ErrorKind::TimedOut)),
+            Some(SkipReason::Network)
+        );
+        assert_eq!(classify_io_error(&Error::from(ErrorKind::NotFound)), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_root_display_name_unix() {
+        assert_eq!(root_display_name(Path::new("/")), "/");
+        assert_eq!(root_display_name(Path::new("/home/user")), "user");
+        assert_eq!(root_display_name(Path::new("")), ".");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_root_display_name_windows() {
+        assert_eq!(root_display_name(Path::new(r"C:\")), r"C:\");
+        assert_eq!(root_display_name(Path::new(r"\\server\share")), r"\\server\share");
+        assert_eq!(root_display_name(Path::new(r"\\server\share\docs")), "docs");
+        assert_eq!(root_display_name(Path::new(r"\\?\UNC\server\share")), r"\\?\UNC\server\share");
+        assert_eq!(root_display_name(Path::new(r"\\?\C:\data")), "data");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_unc_strip_prefix() {
+        let root = Path::new(r"\\server\share");
+        let entry = Path::new(r"\\server\share\docs\a.txt");
+        let relative = entry.strip_prefix(root).unwrap();
+        let names: Vec<_> = relative.components().map(|c| c.as_os_str().to_string_lossy().to_string()).collect();
+        assert_eq!(names, vec!["docs", "a.txt"]);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_classify_windows_network_errors() {
+        let netname_deleted = std::io::Error::from_raw_os_error(64);
+        assert_eq!(classify_io_error(&netname_deleted), Some(SkipReason::Network));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_dir_is_marked_and_not_descended() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("real")).unwrap();
+        std::fs::write(dir.path().join("real/data.bin"), vec![0u8; 100]).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("real"), dir.path().join("link")).unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        let link = root.children.iter().find(|c| c.name == "link").unwrap();
+        assert!(link.is_symlink);
+        assert!(!link.is_dir);
+        assert!(link.children.is_empty());
+
+        let real = root.children.iter().find(|c| c.name == "real").unwrap();
+        assert!(!real.is_symlink);
+        assert_eq!(real.size, 100);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_policy() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("real/inner")).unwrap();
+        std::fs::write(dir.path().join("real/inner/data.bin"), vec![0u8; 100]).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("real"), dir.path().join("dir_link")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("real/inner/data.bin"), dir.path().join("file_link")).unwrap();
+        // Relative link back to an ancestor: following it naively never ends
+        std::os::unix::fs::symlink("../..", dir.path().join("real/inner/up")).unwrap();
+        // Absolute link to the root, which the walker's own loop check catches
+        std::os::unix::fs::symlink(dir.path(), dir.path().join("real/root_link")).unwrap();
+
+        let scan = |policy| {
+            Scanner::new()
+                .with_symlink_policy(policy)
+                .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+                .unwrap()
+        };
+
+        let (root, report) = scan(SymlinkPolicy::Skip);
+        assert_eq!(root.size, 100);
+        assert_eq!(report.symlinks_skipped.len(), 4);
+        let link = root.find(&dir.path().join("file_link")).unwrap();
+        assert!(link.is_symlink);
+        assert_eq!(link.size, 0);
+
+        let (root, report) = scan(SymlinkPolicy::CountAsFile);
+        assert!(report.symlinks_skipped.is_empty());
+        let link_sizes: u64 = root.iter().filter(|n| n.is_symlink).map(|n| n.size).sum();
+        assert!(link_sizes > 0);
+        assert_eq!(root.size, 100 + link_sizes);
+
+        let (root, report) = scan(SymlinkPolicy::Follow);
+        // file_link counts its target again; dir_link is not descended since
+        // real/ claims the directory first, and both loops are cut
+        assert_eq!(root.size, 200);
+        let dir_link = root.find(&dir.path().join("dir_link")).unwrap();
+        assert!(dir_link.is_symlink);
+        assert!(dir_link.children.is_empty());
+        let mut skipped = report.symlinks_skipped.clone();
+        skipped.sort();
+        assert_eq!(
+            skipped,
+            [
+                dir.path().join("dir_link"),
+                dir.path().join("real/inner/up"),
+                dir.path().join("real/root_link"),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exclude_symlinks() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("real")).unwrap();
+        std::fs::write(dir.path().join("real/data.bin"), vec![0u8; 100]).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("real"), dir.path().join("dir_link")).unwrap();
+        std::os::unix::fs::symlink(dir.path().join("real/data.bin"), dir.path().join("file_link")).unwrap();
+
+        let (root, report) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert_eq!(root.children.len(), 3);
+        assert_eq!(report.symlinks_excluded, 0);
+
+        let (root, report) = Scanner::new()
+            .with_exclude_symlinks(true)
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        let names: Vec<_> = root.iter().skip(1).map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["real", "data.bin"]);
+        assert!(root.iter().all(|n| !n.is_symlink));
+        assert_eq!(root.size, 100);
+        assert_eq!(report.symlinks_excluded, 2);
+    }
+
+    #[test]
+    fn test_extended_length_path() {
+        assert_eq!(
+            extended_length_path(Path::new(r"C:\Users\dev\node_modules")),
+            PathBuf::from(r"\\?\C:\Users\dev\node_modules")
+        );
+        assert_eq!(
+            extended_length_path(Path::new(r"\\server\share\deep")),
+            PathBuf::from(r"\\?\UNC\server\share\deep")
+        );
+        assert_eq!(
+            extended_length_path(Path::new(r"\\?\C:\already")),
+            PathBuf::from(r"\\?\C:\already")
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_classify_path_too_long() {
+        let too_long = std::io::Error::from_raw_os_error(206);
+        assert_eq!(classify_io_error(&too_long), Some(SkipReason::PathTooLong));
+        let config = ScanConfig::default();
+        assert!(retry_long_path(&config, Some(SkipReason::PathTooLong), Path::new("missing")).is_none());
+    }
+
+    #[test]
+    fn test_mount_boundary_with_synthetic_devices() {
+        // Same device is never a boundary
+        assert!(!is_mount_boundary(42, 42, true));
+        assert!(!is_mount_boundary(42, 42, false));
+        // A directory on another device is a mount point
+        assert!(is_mount_boundary(42, 7, true));
+        // Overlay files may report the lower layer's device; keep them
+        assert!(!is_mount_boundary(42, 7, false));
+    }
+
+    #[test]
+    fn test_parse_overlay_mounts() {
+        let mountinfo = "\
+22 1 8:1 / / rw,relatime shared:1 - ext4 /dev/sda1 rw
+98 22 0:50 / /var/lib/docker/overlay2/abc/merged rw,relatime - overlay overlay rw,lowerdir=/l,upperdir=/u,workdir=/w
+99 22 0:51 / /srv/other rw,relatime - overlay overlay rw,lowerdir=/l2
+100 22 0:52 / /var/lib/my\\040data rw - overlay overlay rw
+101 22 0:22 / /proc rw,nosuid - proc proc rw
+";
+        assert_eq!(
+            parse_overlay_mounts(mountinfo, Path::new("/var/lib")),
+            vec![
+                PathBuf::from("/var/lib/docker/overlay2/abc/merged"),
+                PathBuf::from("/var/lib/my data"),
+            ]
+        );
+        assert_eq!(
+            parse_overlay_mounts(mountinfo, Path::new("/srv/other/app")),
+            vec![PathBuf::from("/srv/other")]
+        );
+        assert!(parse_overlay_mounts(mountinfo, Path::new("/home")).is_empty());
+    }
+
+    #[test]
+    fn test_cancelled_scan_returns_partial_result() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"hello").unwrap();
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        let (root, report) = Scanner::new()
+            .with_cancel_token(cancel)
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert!(report.cancelled);
+        assert!(root.children.is_empty());
+
+        let (root, report) = Scanner::new()
+            .with_cancel_token(Arc::new(AtomicBool::new(false)))
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert!(!report.cancelled);
+        assert_eq!(root.size, 5);
+    }
+
+    #[test]
+    fn test_one_filesystem_keeps_same_device_entries() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/a.txt"), b"hello").unwrap();
+        std::fs::write(dir.path().join("b.txt"), b"hi").unwrap();
+
+        let (root, report) = scan_directory_with_config(
+            dir.path(),
+            Scanner::new().with_one_filesystem(true).config(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert!(report.mount_points.is_empty());
+        assert_eq!(root.size, 7);
+        assert_eq!(root.children.len(), 2);
+    }
+
+    #[test]
+    fn test_scan_top_level_matches_full_totals() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b/c")).unwrap();
+        std::fs::write(dir.path().join("a/one.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("a/b/c/deep.bin"), vec![0u8; 90]).unwrap();
+        std::fs::write(dir.path().join("top.txt"), vec![0u8; 5]).unwrap();
+
+        let full = Scanner::new().scan(dir.path()).unwrap();
+        let (quick, _report) = Scanner::new().scan_top_level(dir.path(), None).unwrap();
+
+        assert_eq!(quick.size, full.size);
+        assert_eq!(quick.children.len(), 2);
+        let a = &quick.children[0];
+        assert_eq!(a.name, "a");
+        assert!(a.is_dir);
+        assert_eq!(a.size, 100);
+        assert!(a.children.is_empty());
+        assert_eq!(quick.children[1].name, "top.txt");
+        assert_eq!(quick.children[1].size, 5);
+
+        // Filling in one directory keeps the rest of the overview
+        let mut quick = quick;
+        let progress = Arc::new(SharedProgress::default());
+        Scanner::new()
+            .rescan_subtree_with_progress(&mut quick, &dir.path().join("a"), Arc::clone(&progress))
+            .unwrap();
+        assert_eq!(progress.files_scanned.load(Ordering::Relaxed), 2);
+        assert_eq!(quick.size, full.size);
+        assert_eq!(quick.find(&dir.path().join("a/b/c/deep.bin")).unwrap().size, 90);
+        assert_eq!(quick.children[1].name, "top.txt");
+    }
+
+    #[test]
+    fn test_max_depth_keeps_totals() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b/c")).unwrap();
+        std::fs::write(dir.path().join("a/one.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("a/b/two.bin"), vec![0u8; 20]).unwrap();
+        std::fs::write(dir.path().join("a/b/c/deep.bin"), vec![0u8; 90]).unwrap();
+        std::fs::write(dir.path().join("top.txt"), vec![0u8; 5]).unwrap();
+
+        let full = Scanner::new().scan(dir.path()).unwrap();
+        let b_path = dir.path().join("a/b");
+        let full_b = full.find(&b_path).unwrap();
+
+        // Depth 2: root -> a -> {b, one.bin}, with b folded
+        let shallow = Scanner::new().with_max_depth(2).scan(dir.path()).unwrap();
+        assert_eq!(shallow.size, full.size);
+        assert_eq!(shallow.file_count, full.file_count);
+        let names: Vec<&str> = shallow.iter().skip(1).map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "one.bin", "top.txt"]);
+        let b = shallow.find(&b_path).unwrap();
+        assert!(b.children.is_empty());
+        assert_eq!(b.size, full_b.size);
+        assert_eq!(b.allocated_size, full_b.allocated_size);
+        assert_eq!(b.file_count, 2);
+
+        // Depth 0: the root alone still carries every byte
+        let root_only = Scanner::new().with_max_depth(0).scan(dir.path()).unwrap();
+        assert!(root_only.children.is_empty());
+        assert_eq!(root_only.size, full.size);
+        assert_eq!(root_only.file_count, 4);
+
+        // The quick scan's own limit is not widened by a deeper max_depth
+        let (quick, _) = Scanner::new().with_max_depth(5).scan_top_level(dir.path(), None).unwrap();
+        assert!(quick.children.iter().all(|c| c.children.is_empty()));
+    }
+
+    #[test]
+    fn test_excludes_prune_directories() {
+        let dir = tempdir().unwrap();
+        for sub in [
+            "node_modules/pkg/node_modules/inner",
+            "app/node_modules/lib",
+            "app/src/generated",
+            "target/debug",
+            "docs",
+        ] {
+            std::fs::create_dir_all(dir.path().join(sub)).unwrap();
+        }
+        std::fs::write(dir.path().join("node_modules/pkg/index.js"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("app/node_modules/lib/x.js"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("app/src/generated/out.rs"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("app/src/main.rs"), vec![0u8; 7]).unwrap();
+        std::fs::write(dir.path().join("target/debug/app"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("docs/target"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("docs/notes.tmp"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("docs/readme.md"), vec![0u8; 3]).unwrap();
+
+        let (root, report) = Scanner::new()
+            .with_excludes(["node_modules", "target/", "app/src/generated", "*.tmp"])
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+
+        // The nested node_modules is never reached, so only the outer ones are listed
+        let mut excluded: Vec<PathBuf> = report
+            .excluded
+            .iter()
+            .map(|p| p.strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect();
+        excluded.sort();
+        let expected: Vec<PathBuf> = ["app/node_modules", "app/src/generated", "node_modules", "target"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(excluded, expected);
+
+        // "target/" only matches directories, so the docs/target file stays
+        let files: Vec<&str> = root.iter().filter(|n| !n.is_dir).map(|n| n.name.as_str()).collect();
+        assert_eq!(files.len(), 3, "{:?}", files);
+        assert!(files.contains(&"target"));
+        assert!(files.contains(&"main.rs"));
+        assert!(files.contains(&"readme.md"));
+        assert_eq!(root.size, 110);
+
+        let err = Scanner::new().with_excludes(["a/[b"]).scan(dir.path()).unwrap_err();
+        assert!(matches!(err, ScanError::InvalidExclude { ref pattern, .. } if pattern == "a/[b"));
+        assert!(err.to_string().contains("a/[b"));
+    }
+
+    #[test]
+    fn test_respect_gitignore() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "build/\n*.log\n!keep.log\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("build/deep")).unwrap();
+        std::fs::write(dir.path().join("build/deep/out.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("app.log"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("keep.log"), vec![0u8; 20]).unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        // Nested file: ignores one more file and re-includes a log
+        std::fs::write(dir.path().join("src/.gitignore"), "generated.rs\n!debug.log\n").unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), vec![0u8; 5]).unwrap();
+        std::fs::write(dir.path().join("src/generated.rs"), vec![0u8; 7]).unwrap();
+        std::fs::write(dir.path().join("src/debug.log"), vec![0u8; 3]).unwrap();
+
+        let (root, report) = Scanner::new()
+            .with_respect_gitignore(true)
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert!(root.find(&dir.path().join("build")).is_none());
+        assert!(root.find(&dir.path().join("app.log")).is_none());
+        assert!(root.find(&dir.path().join("src/generated.rs")).is_none());
+        assert!(root.find(&dir.path().join("keep.log")).is_some());
+        assert!(root.find(&dir.path().join("src/debug.log")).is_some());
+        assert_eq!((root.size, root.file_count), (20 + 5 + 3, 3));
+        assert_eq!(report.gitignored, 3);
+
+        // Off by default
+        let (root, report) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert!(root.find(&dir.path().join("build/deep/out.bin")).is_some());
+        assert_eq!(root.file_count, 6);
+        assert_eq!(report.gitignored, 0);
+    }
+
+    #[test]
+    fn test_scan_directory_validates_root() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        std::fs::write(&file, b"hello").unwrap();
+        let progress = Arc::new(SharedProgress::default());
+
+        let err = scan_directory_with_report_shared(&file, None, Some(Arc::clone(&progress)), None).unwrap_err();
+        assert!(matches!(err, ScanError::RootNotADirectory(ref p) if *p == file));
+        assert!(err.to_string().contains("notes.txt"));
+
+        let missing = dir.path().join("gone");
+        let err = scan_directory_with_report_shared(&missing, None, None, None).unwrap_err();
+        assert!(matches!(err, ScanError::RootNotFound(ref p) if *p == missing));
+        // Nothing was walked
+        assert_eq!(progress.files_scanned.load(Ordering::Relaxed), 0);
+
+        let (root, report) = scan_directory_with_report_shared(dir.path(), None, Some(progress), None).unwrap();
+        assert_eq!((root.size, root.file_count), (5, 1));
+        assert!(report.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_scan_errors() {
+        use std::error::Error;
+
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("file.txt"), b"hello").unwrap();
+        let scanner = Scanner::new();
+
+        let missing = dir.path().join("missing");
+        let err = scanner.scan(&missing).unwrap_err();
+        assert!(matches!(err, ScanError::RootNotFound(ref p) if *p == missing));
+        assert!(matches!(
+            scanner.scan_many(&[dir.path().to_path_buf(), missing.clone()]),
+            Err(ScanError::RootNotFound(_))
+        ));
+        assert!(matches!(
+            Scanner::new().with_dedup_hardlinks(true).scan_many(&[&missing]),
+            Err(ScanError::RootNotFound(_))
+        ));
+
+        let file = dir.path().join("file.txt");
+        let err = scanner.scan_with_progress(&file, Arc::new(SharedProgress::default())).unwrap_err();
+        assert!(matches!(err, ScanError::RootNotADirectory(ref p) if *p == file));
+
+        // A path through a file fails with the OS error, not "not found"
+        let err = scanner.scan(file.join("below")).unwrap_err();
+        assert!(matches!(err, ScanError::Io(_)));
+        assert!(err.source().is_some());
+
+        // `scan` has no report to flag a partial tree with
+        let cancelled = Scanner::new().with_cancel_token(Arc::new(AtomicBool::new(true)));
+        assert!(matches!(cancelled.scan(dir.path()), Err(ScanError::Interrupted)));
+        let (_, report) = cancelled
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert!(report.cancelled);
+
+        // The binaries keep working with `anyhow`
+        let err: anyhow::Error = scanner.scan(&missing).unwrap_err().into();
+        assert!(matches!(err.downcast_ref::<ScanError>(), Some(ScanError::RootNotFound(_))));
+    }
+
+    #[test]
+    fn test_find_and_find_mut() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b/c")).unwrap();
+        std::fs::write(dir.path().join("a/b/c/deep.txt"), b"deep").unwrap();
+        std::fs::write(dir.path().join("top.txt"), b"x").unwrap();
+
+        let mut root = Scanner::new().scan(dir.path()).unwrap();
+        assert!(std::ptr::eq(root.find(dir.path()).unwrap(), &root));
+
+        let deep_path = dir.path().join("a/b/c/deep.txt");
+        let deep = root.find(&deep_path).unwrap();
+        assert_eq!(deep.name, "deep.txt");
+        assert_eq!(deep.size, 4);
+
+        assert!(root.find(&dir.path().join("a/missing.txt")).is_none());
+        assert!(root.find(&dir.path().join("top.txt/under_a_file")).is_none());
+        assert!(root.find(Path::new("/somewhere/else")).is_none());
+
+        root.find_mut(&deep_path).unwrap().name = "renamed.txt".to_string();
+        assert!(root.find(&deep_path).is_none());
+        assert!(root.find(&dir.path().join("a/b/c/renamed.txt")).is_some());
+        assert!(root.find_mut(&dir.path().join("nope")).is_none());
+    }
+
+    #[test]
+    fn test_dominant_child() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("big/inner")).unwrap();
+        std::fs::write(dir.path().join("big/inner/blob.bin"), vec![0u8; 960]).unwrap();
+        std::fs::write(dir.path().join("big/note.txt"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("small.txt"), vec![0u8; 30]).unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        assert_eq!(root.dominant_child(0.95), Some(0));
+        assert_eq!(root.children[0].name, "big");
+        assert_eq!(root.dominant_child(0.99), None);
+        // Files never count as dominant
+        let inner = &root.children[0].children[0];
+        assert_eq!(inner.name, "inner");
+        assert_eq!(inner.dominant_child(0.5), None);
+    }
+
+    #[test]
+    fn test_total_matching() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("var/log")).unwrap();
+        std::fs::write(dir.path().join("var/log/app.log"), vec![0u8; 400]).unwrap();
+        std::fs::write(dir.path().join("var/log/db.LOG"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("var/data.bin"), vec![0u8; 5000]).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), vec![0u8; 20]).unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        let is_log = |n: &Node| n.name.to_lowercase().ends_with(".log");
+        assert_eq!(root.total_matching(is_log), 500);
+        assert_eq!(root.total_matching(|n| n.size >= 1000), 5000);
+        assert_eq!(root.total_matching(|_| true), root.size);
+        assert_eq!(root.total_matching(|n| n.name == "var"), 0);
+    }
+
+    #[test]
+    fn test_ancestors_of() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("x/y/z")).unwrap();
+        std::fs::create_dir(dir.path().join("x/sibling")).unwrap();
+        std::fs::write(dir.path().join("x/y/z/deep.bin"), vec![0u8; 64]).unwrap();
+        std::fs::write(dir.path().join("x/sibling/other.bin"), vec![0u8; 8]).unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+
+        let chain = root.ancestors_of(&dir.path().join("x/y/z")).unwrap();
+        let paths: Vec<&Path> = chain.iter().map(|n| n.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            [
+                dir.path().to_path_buf(),
+                dir.path().join("x"),
+                dir.path().join("x/y"),
+                dir.path().join("x/y/z"),
+            ]
+        );
+        assert_eq!(chain.iter().map(|n| n.size).collect::<Vec<_>>(), [72, 72, 64, 64]);
+
+        let chain = root.ancestors_of(&dir.path().join("x/sibling/other.bin")).unwrap();
+        assert_eq!(chain.last().unwrap().name, "other.bin");
+        assert!(std::ptr::eq(chain[0], &root));
+        assert_eq!(root.ancestors_of(dir.path()).unwrap().len(), 1);
+        assert!(root.ancestors_of(&dir.path().join("x/y/nope")).is_none());
+        assert!(root.ancestors_of(Path::new("/definitely/not/here")).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_allocated_size_for_sparse_file() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let sparse = std::fs::File::create(dir.path().join("sub/sparse.bin")).unwrap();
+        sparse.set_len(4 * 1024 * 1024).unwrap();
+        std::fs::write(dir.path().join("data.bin"), vec![1u8; 10_000]).unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        let sub = root.children.iter().find(|c| c.name == "sub").unwrap();
+        assert_eq!(sub.size_in(SizeMode::Apparent), 4 * 1024 * 1024);
+        assert!(sub.size_in(SizeMode::Allocated) < sub.size);
+
+        let data = root.children.iter().find(|c| c.name == "data.bin").unwrap();
+        assert!(data.allocated_size >= 10_000);
+        assert_eq!(root.allocated_size, sub.allocated_size + data.allocated_size);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_size_mode_orders_and_prunes() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let sparse = std::fs::File::create(dir.path().join("sub/sparse.bin")).unwrap();
+        sparse.set_len(4 * 1024 * 1024).unwrap();
+        std::fs::write(dir.path().join("data.bin"), vec![1u8; 10_000]).unwrap();
+
+        let names = |root: &Node| root.children.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+        let apparent = Scanner::new().scan(dir.path()).unwrap();
+        assert_eq!(names(&apparent), ["sub", "data.bin"]);
+        let allocated = Scanner::new().with_size_mode(SizeMode::Allocated).scan(dir.path()).unwrap();
+        assert_eq!(names(&allocated), ["data.bin", "sub"]);
+        assert_eq!(allocated.size, apparent.size);
+
+        // The sparse directory is big by length but tiny on disk
+        let pruned = Scanner::new()
+            .with_size_mode(SizeMode::Allocated)
+            .with_min_dir_size(1024 * 1024)
+            .scan(dir.path())
+            .unwrap();
+        assert_eq!(names(&pruned), ["data.bin", "<1 small directories>"]);
+        let kept = Scanner::new().with_min_dir_size(1024 * 1024).scan(dir.path()).unwrap();
+        assert_eq!(names(&kept), ["sub", "data.bin"]);
+    }
+
+    #[test]
+    fn test_round_up_to_cluster() {
+        assert_eq!(round_up_to_cluster(1, Some(4096)), 4096);
+        assert_eq!(round_up_to_cluster(4096, Some(4096)), 4096);
+        assert_eq!(round_up_to_cluster(4097, Some(4096)), 8192);
+        assert_eq!(round_up_to_cluster(0, Some(4096)), 0);
+        assert_eq!(round_up_to_cluster(10, None), 10);
+        assert_eq!(round_up_to_cluster(10, Some(0)), 10);
+    }
+
+    #[test]
+    fn test_largest_file_per_directory() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("media/movies")).unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("media/movies/movie.mkv"), vec![0u8; 900]).unwrap();
+        std::fs::write(dir.path().join("media/cover.jpg"), vec![0u8; 50]).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), vec![0u8; 1000]).unwrap();
+
+        let root = Scanner::new().with_largest_file(true).scan(dir.path()).unwrap();
+        assert_eq!(root.largest_file, Some((dir.path().join("notes.txt"), 1000)));
+        let media = root.children.iter().find(|c| c.name == "media").unwrap();
+        assert_eq!(media.largest_file, Some((dir.path().join("media/movies/movie.mkv"), 900)));
+        let empty = root.children.iter().find(|c| c.name == "empty").unwrap();
+        assert_eq!(empty.largest_file, None);
+
+        // Quick scans still see files below the materialized level
+        let (root, _) = Scanner::new().with_largest_file(true).scan_top_level(dir.path(), None).unwrap();
+        let media = root.children.iter().find(|c| c.name == "media").unwrap();
+        assert_eq!(media.largest_file, Some((dir.path().join("media/movies/movie.mkv"), 900)));
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        assert!(root.iter().all(|n| n.largest_file.is_none()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_skip_special_files() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("regular.txt"), b"hello").unwrap();
+        let fifo = dir.path().join("pipe");
+        let status = std::process::Command::new("mkfifo").arg(&fifo).status().unwrap();
+        assert!(status.success());
+
+        // Not a filesystem root, so special files are kept by default
+        let (root, report) = scan_directory_with_report(dir.path(), None).unwrap();
+        assert!(root.children.iter().any(|c| c.name == "pipe"));
+        assert!(report.skipped.is_empty());
+
+        let scanner = Scanner::new().with_skip_special_files(true);
+        let (root, report) = scanner
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert_eq!(root.children.len(), 1);
+        assert_eq!(root.children[0].name, "regular.txt");
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].reason, SkipReason::SpecialFile);
+        assert_eq!(report.skipped[0].path.as_deref(), Some(fifo.as_path()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_skipped_entries_sent_live() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("regular.txt"), b"hello").unwrap();
+        for name in ["pipe1", "pipe2"] {
+            let status = std::process::Command::new("mkfifo").arg(dir.path().join(name)).status().unwrap();
+            assert!(status.success());
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let scanner = Scanner::new().with_skip_special_files(true).with_skipped_sender(tx);
+        let (_, report) = scanner
+            .scan_with_cancel(dir.path(), Arc::new(AtomicBool::new(false)))
+            .unwrap();
+        let mut sent: Vec<SkippedEntry> = rx.try_iter().collect();
+        assert_eq!(sent.len(), 2);
+        let mut skipped = report.skipped.clone();
+        sent.sort_by(|a, b| a.path.cmp(&b.path));
+        skipped.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(sent, skipped);
+
+        // A dropped receiver does not disturb the scan
+        drop(rx);
+        let (_, report) = scanner
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert_eq!(report.skipped.len(), 2);
+    }
+
+    #[cfg(feature = "profile")]
+    #[test]
+    fn test_slow_entries_recorded_over_threshold() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), b"a").unwrap();
+        std::fs::write(dir.path().join("b.txt"), b"b").unwrap();
+        let progress = Arc::new(SharedProgress::default());
+
+        // Every read takes at least zero time
+        let (_, report) = Scanner::new()
+            .with_slow_entry_threshold(Duration::ZERO)
+            .scan_with_progress(dir.path(), Arc::clone(&progress))
+            .unwrap();
+        let mut slow: Vec<&Path> = report.slow_entries.iter().map(|(p, _)| p.as_path()).collect();
+        slow.sort();
+        assert_eq!(slow, [dir.path().join("a.txt"), dir.path().join("b.txt")]);
+
+        // Local reads never take an hour
+        let (_, report) = Scanner::new()
+            .with_slow_entry_threshold(Duration::from_secs(3600))
+            .scan_with_progress(dir.path(), progress)
+            .unwrap();
+        assert!(report.slow_entries.is_empty());
+    }
+
+    #[test]
+    fn test_is_filesystem_root() {
+        let dir = tempdir().unwrap();
+        assert!(!is_filesystem_root(dir.path()));
+        assert!(!is_filesystem_root(&dir.path().join("missing")));
+        #[cfg(unix)]
+        assert!(is_filesystem_root(Path::new("/")));
+    }
+
+    #[test]
+    fn test_stream_events_sequence() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/inner.bin"), vec![0u8; 20]).unwrap();
+        std::fs::write(dir.path().join("top.txt"), vec![0u8; 5]).unwrap();
+        let root = Scanner::new().scan(dir.path()).unwrap();
+
+        let mut events = Vec::new();
+        root.stream_events(|event| {
+            events.push(match event {
+                TreeEvent::Enter(n) => format!("+{}", n.name),
+                TreeEvent::Leave(n) => format!("-{}", n.name),
+            })
+        });
+        let root_name = &root.name;
+        assert_eq!(
+            events,
+            [
+                format!("+{}", root_name),
+                "+sub".to_string(),
+                "+inner.bin".to_string(),
+                "-inner.bin".to_string(),
+                "-sub".to_string(),
+                "+top.txt".to_string(),
+                "-top.txt".to_string(),
+                format!("-{}", root_name),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_depth_histogram() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b/c")).unwrap();
+        std::fs::write(dir.path().join("top1"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("top2"), vec![0u8; 20]).unwrap();
+        std::fs::write(dir.path().join("a/b/c/deep1"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("a/b/c/deep2"), vec![0u8; 200]).unwrap();
+        std::fs::write(dir.path().join("a/b/c/deep3"), vec![0u8; 300]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        assert_eq!(
+            scanner.depth_histogram(&root),
+            [(1, 2, 30), (2, 0, 0), (3, 0, 0), (4, 3, 600)]
+        );
+
+        let empty = tempdir().unwrap();
+        assert!(scanner.depth_histogram(&scanner.scan(empty.path()).unwrap()).is_empty());
+
+        // Folded directories count every file at their summary's level
+        let folding = Scanner::new().with_min_dir_size(1000);
+        let root = folding.scan(dir.path()).unwrap();
+        assert!(root.children.iter().any(|c| c.is_summary));
+        assert_eq!(folding.depth_histogram(&root), [(1, 5, 630)]);
+
+        // Files below the depth cutoff are left out
+        let shallow = Scanner::new().with_max_depth(2);
+        let root = shallow.scan(dir.path()).unwrap();
+        assert_eq!(root.file_count, 5);
+        assert_eq!(shallow.depth_histogram(&root), [(1, 2, 30)]);
+    }
+
+    #[test]
+    fn test_category_breakdown() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("photo.JPG"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("icon.png"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("clip.mp4"), vec![0u8; 1000]).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), vec![0u8; 50]).unwrap();
+        std::fs::write(dir.path().join("backup.zip"), vec![0u8; 200]).unwrap();
+        std::fs::write(dir.path().join("data.xyz"), vec![0u8; 7]).unwrap();
+        std::fs::write(dir.path().join("README"), vec![0u8; 3]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        assert_eq!(
+            scanner.category_breakdown(&root),
+            [
+                (FileCategory::Video, 1000, 1),
+                (FileCategory::Image, 400, 2),
+                (FileCategory::Archive, 200, 1),
+                (FileCategory::Code, 50, 1),
+                (FileCategory::Other, 10, 2),
+            ]
+        );
+
+        let scanner = Scanner::new().with_category(".XYZ", FileCategory::Document);
+        let breakdown = scanner.category_breakdown(&root);
+        assert!(breakdown.contains(&(FileCategory::Document, 7, 1)));
+        assert!(breakdown.contains(&(FileCategory::Other, 3, 1)));
+    }
+
+    #[test]
+    fn test_size_by_category() {
+        for (ext, category) in [
+            ("jpeg", FileCategory::Image),
+            ("PNG", FileCategory::Image),
+            ("mkv", FileCategory::Video),
+            ("mp3", FileCategory::Audio),
+            ("pdf", FileCategory::Document),
+            ("tar", FileCategory::Archive),
+            ("rs", FileCategory::Code),
+            ("unknownext", FileCategory::Other),
+            ("", FileCategory::Other),
+        ] {
+            assert_eq!(FileCategory::from_extension(ext), category, "{ext}");
+        }
+
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("music")).unwrap();
+        std::fs::write(dir.path().join("music/a.mp3"), vec![0u8; 400]).unwrap();
+        std::fs::write(dir.path().join("music/b.FLAC"), vec![0u8; 600]).unwrap();
+        std::fs::write(dir.path().join("notes.pdf"), vec![0u8; 30]).unwrap();
+        std::fs::write(dir.path().join("lib.py"), vec![0u8; 20]).unwrap();
+        std::fs::write(dir.path().join("Makefile"), vec![0u8; 5]).unwrap();
+        std::fs::write(dir.path().join("blob.qqq"), vec![0u8; 7]).unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        let totals = root.size_by_category();
+        assert_eq!(totals.len(), 4);
+        assert_eq!(totals[&FileCategory::Audio], (1000, 2));
+        assert_eq!(totals[&FileCategory::Document], (30, 1));
+        assert_eq!(totals[&FileCategory::Code], (20, 1));
+        assert_eq!(totals[&FileCategory::Other], (12, 2));
+
+        let mut root = root;
+        root.find_mut(&dir.path().join("music/a.mp3")).unwrap().allocated_size = 4096;
+        root.find_mut(&dir.path().join("music/b.FLAC")).unwrap().allocated_size = 8192;
+        let allocated = root.size_by_category_in(SizeMode::Allocated);
+        assert_eq!(allocated[&FileCategory::Audio], (12288, 2));
+    }
+
+    #[test]
+    fn test_delete_node_removes_nested_directory() {
+        let dir = tempdir().unwrap();
+        let outer = dir.path().join("outer");
+        let inner = outer.join("inner");
+        std::fs::create_dir_all(inner.join("deep")).unwrap();
+        std::fs::write(inner.join("a.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(inner.join("deep/b.bin"), vec![0u8; 40]).unwrap();
+        std::fs::write(outer.join("keep.bin"), vec![0u8; 7]).unwrap();
+        std::fs::write(dir.path().join("top.bin"), vec![0u8; 3]).unwrap();
+
+        let mut root = Scanner::new().scan(dir.path()).unwrap();
+        assert_eq!((root.size, root.file_count), (150, 4));
+
+        root.delete_node(&inner).unwrap();
+        assert!(!inner.exists());
+        assert!(root.find(&inner).is_none());
+        let outer_node = root.find(&outer).unwrap();
+        assert_eq!((outer_node.size, outer_node.file_count), (7, 1));
+        assert_eq!((root.size, root.file_count), (10, 2));
+
+        // Unknown paths and the root itself are reported, not touched
+        assert!(matches!(root.delete_node(&inner), Err(DeleteError::NotFound(_))));
+        assert!(matches!(root.delete_node(dir.path()), Err(DeleteError::NotFound(_))));
+        assert!(dir.path().exists());
+
+        // A failed removal leaves the tree as it was
+        std::fs::remove_file(dir.path().join("top.bin")).unwrap();
+        let err = root.delete_node(&dir.path().join("top.bin")).unwrap_err();
+        assert!(matches!(err, DeleteError::Io { .. }), "{err}");
+        assert_eq!((root.size, root.file_count), (10, 2));
+    }
+
+    #[test]
+    fn test_delete_node_updates_largest_file() {
+        let dir = tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        std::fs::create_dir_all(sub.join("deep")).unwrap();
+        std::fs::write(sub.join("deep/big.bin"), vec![0u8; 500]).unwrap();
+        std::fs::write(sub.join("mid.bin"), vec![0u8; 200]).unwrap();
+        std::fs::write(dir.path().join("small.bin"), vec![0u8; 50]).unwrap();
+
+        let mut root = Scanner::new().with_largest_file(true).scan(dir.path()).unwrap();
+        assert_eq!(root.largest_file, Some((sub.join("deep/big.bin"), 500)));
+
+        root.delete_node(&sub.join("deep/big.bin")).unwrap();
+        assert_eq!(root.largest_file, Some((sub.join("mid.bin"), 200)));
+        assert_eq!(root.find(&sub).unwrap().largest_file, Some((sub.join("mid.bin"), 200)));
+        assert_eq!(root.find(&sub.join("deep")).unwrap().largest_file, None);
+
+        // Deleting a directory that holds it works the same way
+        root.delete_node(&sub).unwrap();
+        assert_eq!(root.largest_file, Some((dir.path().join("small.bin"), 50)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_delete_node_permission_denied() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let locked = dir.path().join("locked");
+        std::fs::create_dir(&locked).unwrap();
+        std::fs::write(locked.join("stuck.bin"), vec![0u8; 10]).unwrap();
+        let mut root = Scanner::new().scan(dir.path()).unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555)).unwrap();
+
+        let result = root.delete_node(&locked.join("stuck.bin"));
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+        // Root (e.g. in containers) can remove the file anyway
+        if result.is_ok() {
+            return;
+        }
+
+        assert!(matches!(result, Err(DeleteError::PermissionDenied(_))));
+        assert!(locked.join("stuck.bin").exists());
+        assert_eq!((root.size, root.file_count), (10, 1));
+    }
+
+    #[test]
+    fn test_rescan_subtree_after_delete_matches_disk() {
+        let dir = tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        std::fs::create_dir(&sub).unwrap();
+        std::fs::write(sub.join("a.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(sub.join("b.bin"), vec![0u8; 50]).unwrap();
+        std::fs::write(dir.path().join("top.bin"), vec![0u8; 10]).unwrap();
+
+        let scanner = Scanner::new();
+        let mut in_place = scanner.scan(dir.path()).unwrap();
+        let mut rescanned = in_place.clone();
+
+        // Something else writes to the directory after the scan
+        std::fs::write(sub.join("c.bin"), vec![0u8; 25]).unwrap();
+
+        in_place.delete_node(&sub.join("a.bin")).unwrap();
+        scanner.rescan_subtree(&mut rescanned, &sub).unwrap();
+
+        // In-place update only knows about the deletion
+        assert_eq!(in_place.size, 60);
+        assert_eq!(in_place.file_count, 2);
+        let in_place_sub = in_place.children.iter().find(|c| c.name == "sub").unwrap();
+        assert_eq!((in_place_sub.size, in_place_sub.file_count), (50, 1));
+
+        // The rescan also picks up the new file
+        assert_eq!(rescanned.size, 85);
+        assert_eq!(rescanned.file_count, 3);
+        let fresh = scanner.scan(dir.path()).unwrap();
+        let sub_node = rescanned.children.iter().find(|c| c.name == "sub").unwrap();
+        let fresh_sub = fresh.children.iter().find(|c| c.name == "sub").unwrap();
+        assert_eq!(sub_node.size, fresh_sub.size);
+        assert_eq!(
+            sub_node.children.iter().map(|c| &c.name).collect::<Vec<_>>(),
+            fresh_sub.children.iter().map(|c| &c.name).collect::<Vec<_>>()
+        );
+        assert_eq!(rescanned.children[0].name, "sub");
+
+        let missing = sub.join("missing");
+        let err = scanner.rescan_subtree(&mut rescanned, &missing).unwrap_err();
+        assert!(matches!(err, ScanError::RootNotFound(ref p) if *p == missing));
+        std::fs::create_dir(&missing).unwrap();
+        let err = scanner.rescan_subtree(&mut rescanned, &missing).unwrap_err();
+        assert!(matches!(err, ScanError::NotInTree(ref p) if *p == missing));
+
+        // Re-sorted ancestors keep the deterministic name tie-break
+        let tied = tempdir().unwrap();
+        for (name, len) in [("a", 10), ("b", 20)] {
+            std::fs::create_dir(tied.path().join(name)).unwrap();
+            std::fs::write(tied.path().join(name).join("f.bin"), vec![0u8; len]).unwrap();
+        }
+        let scanner = Scanner::new().with_deterministic(true);
+        let mut root = scanner.scan(tied.path()).unwrap();
+        std::fs::write(tied.path().join("b/f.bin"), vec![0u8; 10]).unwrap();
+        scanner.rescan_subtree(&mut root, &tied.path().join("b")).unwrap();
+        let names: Vec<&str> = root.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+
+        // ... and the size mode the tree was sorted by
+        let mut root = scanner.scan(tied.path()).unwrap();
+        root.find_mut(&tied.path().join("a")).unwrap().allocated_size = 1;
+        let scanner = scanner.with_size_mode(SizeMode::Allocated);
+        scanner.rescan_subtree(&mut root, &tied.path().join("b")).unwrap();
+        let b = root.find(&tied.path().join("b")).unwrap();
+        assert!(b.allocated_size > 1);
+        assert_eq!(root.children[0].name, "b");
+    }
+
+    #[test]
+    fn test_rescan_subtree_keeps_scan_root_rules() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("sub/deep/x")).unwrap();
+        std::fs::create_dir(dir.path().join("sub/skip")).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(dir.path().join("top.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("sub/b.bin"), vec![0u8; 50]).unwrap();
+        std::fs::write(dir.path().join("sub/deep/x/a.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("sub/skip/s.bin"), vec![0u8; 500]).unwrap();
+
+        let scanner = Scanner::new()
+            .with_respect_gitignore(true)
+            .with_excludes(["sub/skip/"])
+            .with_max_depth(2)
+            .with_largest_file(true);
+        let mut root = scanner.scan(dir.path()).unwrap();
+        assert_eq!(root.size, 160);
+        assert_eq!(root.largest_file, Some((dir.path().join("top.bin"), 100)));
+
+        std::fs::write(dir.path().join("sub/huge.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("sub/noise.log"), vec![0u8; 1000]).unwrap();
+        scanner.rescan_subtree(&mut root, &dir.path().join("sub")).unwrap();
+
+        // The root's .gitignore and root-relative excludes still apply
+        assert_eq!((root.size, root.file_count), (460, 4));
+        // max_depth still counts from the scan root
+        let deep = root.find(&dir.path().join("sub/deep")).unwrap();
+        assert!(deep.children.is_empty());
+        assert_eq!(deep.size, 10);
+        // Ancestors see the new largest file
+        let huge = Some((dir.path().join("sub/huge.bin"), 300));
+        assert_eq!(root.largest_file, huge);
+        assert_eq!(root.find(&dir.path().join("sub")).unwrap().largest_file, huge);
+    }
+
+    #[cfg(feature = "svg")]
+    #[test]
+    fn test_export_summary_svg() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("media & more")).unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("media & more/movie.MP4"), vec![0u8; 4000]).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), vec![0u8; 1000]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        let output = dir.path().join("summary.svg");
+        scanner.export_summary_svg(&root, &output).unwrap();
+
+        let svg = std::fs::read_to_string(&output).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains("in 2 files"));
+        assert!(svg.contains("media &amp; more"));
+        assert!(svg.find(".mp4").unwrap() < svg.find(".rs").unwrap());
+        assert!(svg.contains("(80.0%)"));
+
+        let empty = tempdir().unwrap();
+        let empty_root = scanner.scan(empty.path()).unwrap();
+        assert!(scanner.export_summary_svg(&empty_root, empty.path().join("x.svg")).is_err());
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_export_html() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("a <b> & c")).unwrap();
+        std::fs::create_dir(dir.path().join("small")).unwrap();
+        std::fs::write(dir.path().join("a <b> & c/big.bin"), vec![0u8; 3000]).unwrap();
+        std::fs::write(dir.path().join("small/note.txt"), vec![0u8; 1000]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        let output = dir.path().join("report.html");
+        scanner.export_html(&root, &output).unwrap();
+
+        let html = std::fs::read_to_string(&output).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.trim_end().ends_with("</html>"));
+        assert!(!html.contains("{{"));
+        assert!(html.contains("a &lt;b&gt; &amp; c/"));
+        assert!(html.contains("big.bin"));
+        assert!(html.contains("note.txt"));
+        assert!(html.contains("2.93 KB"));
+        assert!(html.contains("width:75.0%"));
+        assert_eq!(html.matches("<details").count(), html.matches("</details>").count());
+        // Largest sibling first
+        assert!(html.find("big.bin").unwrap() < html.find("note.txt").unwrap());
+
+        let empty = tempdir().unwrap();
+        let empty_root = scanner.scan(empty.path()).unwrap();
+        assert!(scanner.export_html(&empty_root, empty.path().join("x.html")).is_err());
+    }
+
+    #[test]
+    fn test_check_skip_allowed() {
+        let path = Path::new("/secret");
+        let skip = ScanConfig::default();
+        let fail = ScanConfig { on_permission_error: SkipOrFail::Fail, ..Default::default() };
+
+        assert!(check_skip_allowed(&skip, SkipReason::PermissionDenied, Some(path), &"denied").is_ok());
+        let err = check_skip_allowed(&fail, SkipReason::PermissionDenied, Some(path), &"denied").unwrap_err();
+        assert!(matches!(err, ScanError::PermissionDenied { path: Some(ref p), .. } if p == path));
+        assert!(err.to_string().contains("/secret"));
+        // Only permission errors are fatal
+        assert!(check_skip_allowed(&fail, SkipReason::Network, Some(path), &"reset").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_on_permission_error_modes() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let locked = dir.path().join("locked");
+        std::fs::create_dir(&locked).unwrap();
+        std::fs::write(locked.join("hidden.txt"), b"x").unwrap();
+        std::fs::write(dir.path().join("open.txt"), b"hello").unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Root (e.g. in containers) can read the directory anyway
+        if std::fs::read_dir(&locked).is_ok() {
+            std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let (root, report) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert_eq!(root.size, 5);
+        assert!(report.skipped.iter().any(|s| s.reason == SkipReason::PermissionDenied));
+        let locked_node = root.find(&locked).unwrap();
+        assert!(locked_node.is_dir);
+        assert!(locked_node.access_denied);
+        assert_eq!(locked_node.size, 0);
+        assert!(!root.access_denied);
+        assert!(report
+            .skipped
+            .iter()
+            .any(|s| s.path.as_deref() == Some(locked.as_path()) && s.reason == SkipReason::PermissionDenied));
+
+        let result = Scanner::new()
+            .with_on_permission_error(SkipOrFail::Fail)
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()));
+        assert!(matches!(result, Err(ScanError::PermissionDenied { .. })));
+
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_average_file_size() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("b.bin"), vec![0u8; 51]).unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        assert_eq!(root.average_file_size(SizeMode::Apparent), Some(75));
+        let empty = root.children.iter().find(|c| c.name == "empty").unwrap();
+        assert_eq!(empty.average_file_size(SizeMode::Apparent), None);
+    }
+
+    #[test]
+    fn test_file_count_covers_whole_subtree() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("a/one.txt"), b"1").unwrap();
+        std::fs::write(dir.path().join("a/b/two.txt"), b"2").unwrap();
+        std::fs::write(dir.path().join("a/b/three.txt"), b"3").unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        assert_eq!(root.file_count, 3);
+        let a = root.children.iter().find(|c| c.name == "a").unwrap();
+        assert_eq!(a.file_count, 3);
+        assert_eq!(a.children.len(), 2);
+        let empty = root.children.iter().find(|c| c.name == "empty").unwrap();
+        assert_eq!(empty.file_count, 0);
+        assert!(root.iter().filter(|n| !n.is_dir).all(|n| n.file_count == 1));
+    }
+
+    #[test]
+    fn test_direct_and_nested_size() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("sub/deeper")).unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("b.bin"), vec![0u8; 20]).unwrap();
+        std::fs::write(dir.path().join("sub/c.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("sub/deeper/d.bin"), vec![0u8; 7]).unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        assert_eq!(root.direct_and_nested_size(SizeMode::Apparent), (120, 307));
+        let sub = root.children.iter().find(|c| c.name == "sub").unwrap();
+        assert_eq!(sub.direct_and_nested_size(SizeMode::Apparent), (300, 7));
+        let deeper = sub.children.iter().find(|c| c.name == "deeper").unwrap();
+        assert_eq!(deeper.direct_and_nested_size(SizeMode::Apparent), (7, 0));
+    }
+
+    #[test]
+    fn test_entry_filter() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("target/debug/app"), vec![0u8; 1000]).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), vec![0u8; 40]).unwrap();
+        std::fs::write(dir.path().join("src/big.log"), vec![0u8; 500]).unwrap();
+        std::fs::write(dir.path().join("small.txt"), vec![0u8; 5]).unwrap();
+
+        // Skip build output and any file of 100 bytes or more
+        let scanner = Scanner::new().with_entry_filter(|path, md| {
+            path.file_name().is_some_and(|n| n != "target") && (md.is_dir() || md.len() < 100)
+        });
+        let root = scanner.scan(dir.path()).unwrap();
+
+        let names: Vec<_> = root.iter().skip(1).map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["src", "main.rs", "small.txt"]);
+        assert_eq!(root.size, 45);
+        assert_eq!(root.file_count, 2);
+    }
+
+    #[test]
+    fn test_shared_progress_rate_and_eta() {
+        let progress = SharedProgress::default();
+        assert_eq!(progress.rate_per_sec(), 0.0);
+        assert_eq!(progress.eta(), None);
+
+        let t0 = Instant::now();
+        progress.push_rate_sample(t0, 0);
+        progress.push_rate_sample(t0 + Duration::from_secs(2), 200);
+        assert_eq!(progress.rate_per_sec(), 100.0);
+        // No total estimate yet
+        assert_eq!(progress.eta(), None);
+
+        progress.files_scanned.store(200, Ordering::Relaxed);
+        progress.total_files_estimate.store(1000, Ordering::Relaxed);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(8)));
+
+        // Older samples leave the window, so the rate follows the recent pace
+        progress.push_rate_sample(t0 + Duration::from_secs(10), 1000);
+        progress.push_rate_sample(t0 + Duration::from_secs(12), 1100);
+        assert_eq!(progress.rate_per_sec(), 90.0);
+        progress.files_scanned.store(1100, Ordering::Relaxed);
+        assert_eq!(progress.eta(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_count_files_for_estimate() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        for name in ["a/one", "a/b/two", "three"] {
+            std::fs::write(dir.path().join(name), b"x").unwrap();
+        }
+        assert_eq!(count_files(dir.path(), &AtomicBool::new(false), None, None), Some(3));
+        assert_eq!(count_files(dir.path(), &AtomicBool::new(false), None, Some(1)), Some(3));
+        assert_eq!(count_files(dir.path(), &AtomicBool::new(true), None, None), None);
+
+        let progress = Arc::new(SharedProgress::default());
+        let (root, _) = Scanner::new()
+            .with_estimate_total(true)
+            .scan_with_progress(dir.path(), Arc::clone(&progress))
+            .unwrap();
+        assert_eq!(root.file_count, 3);
+        // The count races the scan, so it is either done and exact or absent
+        assert!(matches!(progress.total_files_estimate.load(Ordering::Relaxed), 0 | 3));
+    }
+
+    #[test]
+    fn test_shared_progress_bytes_scanned() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("sub/b.bin"), vec![0u8; 45]).unwrap();
+
+        let progress = Arc::new(SharedProgress::default());
+        let (root, _) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::clone(&progress))
+            .unwrap();
+        assert_eq!(progress.bytes_scanned.load(Ordering::Relaxed), 345);
+        assert_eq!(progress.bytes_scanned.load(Ordering::Relaxed), root.size);
+        assert_eq!(progress.dirs_scanned.load(Ordering::Relaxed), 1);
+
+        let (tx, rx) = mpsc::channel();
+        let (root, _) = scan_directory_with_report(dir.path(), Some(tx)).unwrap();
+        let updates: Vec<ScanProgress> = rx.iter().collect();
+        assert!(updates.windows(2).all(|w| w[0].dirs_scanned <= w[1].dirs_scanned));
+        assert!(updates.iter().all(|p| p.dirs_scanned <= 1));
+        let sent: Vec<u64> = updates.iter().map(|p| p.bytes_scanned).collect();
+        assert!(!sent.is_empty());
+        assert!(sent.windows(2).all(|w| w[0] <= w[1]));
+        assert!(sent.iter().all(|&b| b <= root.size));
+    }
+
+    #[test]
+    fn test_deterministic_breaks_size_ties_by_name() {
+        let dir = tempdir().unwrap();
+        for name in ["delta", "alpha", "charlie", "bravo"] {
+            std::fs::write(dir.path().join(name), vec![0u8; 10]).unwrap();
+        }
+        std::fs::write(dir.path().join("zulu"), vec![0u8; 20]).unwrap();
+
+        let root = Scanner::new().with_deterministic(true).scan(dir.path()).unwrap();
+        let names: Vec<_> = root.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["zulu", "alpha", "bravo", "charlie", "delta"]);
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_deterministic_exports_are_byte_identical() {
+        let dir = tempdir().unwrap();
+        let out = tempdir().unwrap();
+        for sub in ["b", "a", "c"] {
+            std::fs::create_dir(dir.path().join(sub)).unwrap();
+            for file in ["x", "y", "z"] {
+                std::fs::write(dir.path().join(sub).join(file), vec![0u8; 8]).unwrap();
+            }
+        }
+
+        let scanner = Scanner::new().with_deterministic(true);
+        let exports: Vec<Vec<u8>> = (0..2)
+            .map(|i| {
+                let root = scanner.scan(dir.path()).unwrap();
+                let path = out.path().join(format!("run{}.csv", i));
+                scanner.export_csv(&root, &path).unwrap();
+                std::fs::read(&path).unwrap()
+            })
+            .collect();
+        assert_eq!(exports[0], exports[1]);
+    }
+
+    /// root/{src/{main.rs, lib.rs}, docs/guide.md, README.md}
+    fn tree_text_fixture() -> Node {
+        let dir = |name: &str, children: Vec<Node>| Node {
+            size: children.iter().map(|c| c.size).sum(),
+            children,
+            ..Node::new(name.to_string(), PathBuf::from(name), true)
+        };
+        let file = |name: &str, size| Node {
+            size,
+            ..Node::new(name.to_string(), PathBuf::from(name), false)
+        };
+        dir(
+            "root",
+            vec![
+                dir("src", vec![file("main.rs", 3072), file("lib.rs", 1024)]),
+                dir("docs", vec![file("guide.md", 2048)]),
+                file("README.md", 100),
+            ],
+        )
+    }
+
+    fn tree_text(options: &TreeTextOptions) -> String {
+        let mut out = Vec::new();
+        Scanner::new()
+            .export_tree_text_with_options(&tree_text_fixture(), &mut out, options)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_export_tree_text_depth_limit() {
+        let full = tree_text(&TreeTextOptions::default());
+        assert_eq!(
+            full,
+            "root [6.10 KB]\n\
+             ├── src [4.00 KB]\n\
+             │   ├── main.rs [3.00 KB]\n\
+             │   └── lib.rs [1.00 KB]\n\
+             ├── docs [2.00 KB]\n\
+             │   └── guide.md [2.00 KB]\n\
+             └── README.md [100 B]\n"
+        );
+
+        let shallow = tree_text(&TreeTextOptions { max_depth: Some(1), ..Default::default() });
+        assert_eq!(
+            shallow,
+            "root [6.10 KB]\n├── src [4.00 KB]\n├── docs [2.00 KB]\n└── README.md [100 B]\n"
+        );
+        assert_eq!(tree_text(&TreeTextOptions { max_depth: Some(0), ..Default::default() }), "root [6.10 KB]\n");
+    }
+
+    #[test]
+    fn test_export_tree_text_indent_width() {
+        let narrow = tree_text(&TreeTextOptions { max_depth: None, indent_width: 2 });
+        assert!(narrow.contains("\n├ src [4.00 KB]\n│ ├ main.rs [3.00 KB]\n"));
+        assert!(narrow.contains("\n│ └ guide.md"));
+
+        let wide = tree_text(&TreeTextOptions { max_depth: None, indent_width: 6 });
+        assert!(wide.contains("\n├──── src [4.00 KB]\n│     ├──── main.rs [3.00 KB]\n"));
+
+        // Too-small widths fall back to 2
+        assert_eq!(tree_text(&TreeTextOptions { max_depth: None, indent_width: 0 }), narrow);
+    }
+
+    #[test]
+    fn test_root_removed_mid_scan_sets_root_lost() {
+        let parent = tempdir().unwrap();
+        let root_path = parent.path().join("share");
+        for i in 0..20 {
+            let sub = root_path.join(format!("dir{:02}", i));
+            std::fs::create_dir_all(&sub).unwrap();
+            for j in 0..5 {
+                std::fs::write(sub.join(format!("f{}", j)), b"data").unwrap();
+            }
+        }
+
+        // Remove the whole root the first time the scan looks at an entry
+        let removed = Arc::new(AtomicBool::new(false));
+        let (flag, target) = (Arc::clone(&removed), root_path.clone());
+        let scanner = Scanner::new().with_entry_filter(move |_, _| {
+            if !flag.swap(true, Ordering::SeqCst) {
+                std::fs::remove_dir_all(&target).unwrap();
+            }
+            true
+        });
+
+        let (root, report) = scanner
+            .scan_with_progress(&root_path, Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert!(removed.load(Ordering::SeqCst));
+        assert!(report.root_lost);
+        assert!(root.file_count < 100);
+
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a"), b"x").unwrap();
+        let (_, report) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert!(!report.root_lost);
+    }
+
+    #[test]
+    fn test_resolve_scan_path() {
+        assert_eq!(resolve_scan_path(Some("some/dir")).unwrap(), PathBuf::from("some/dir"));
+        let cwd = std::env::current_dir().unwrap();
+        assert_eq!(resolve_scan_path(None::<&str>).unwrap(), cwd);
+        assert_eq!(resolve_scan_path(Some("")).unwrap(), cwd);
+    }
+
+    #[test]
+    fn test_thread_count_does_not_change_tree() {
+        let dir = tempdir().unwrap();
+        for d in ["a/x", "a/y", "b", "c/d/e"] {
+            std::fs::create_dir_all(dir.path().join(d)).unwrap();
+        }
+        for (i, f) in ["a/x/1.bin", "a/x/2.bin", "a/y/3.bin", "b/4.bin", "c/d/e/5.bin", "6.bin"].iter().enumerate() {
+            std::fs::write(dir.path().join(f), vec![0u8; 100 * (i + 1)]).unwrap();
+        }
+
+        let serial = Scanner::new().with_threads(1).scan(dir.path()).unwrap();
+        let pooled = Scanner::new().with_threads(4).scan(dir.path()).unwrap();
+        let default = Scanner::new().scan(dir.path()).unwrap();
+        let shape = |root: &Node| {
+            root.iter()
+                .map(|n| (n.path.clone(), n.size, n.file_count, n.is_dir))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(shape(&serial), shape(&pooled));
+        assert_eq!(shape(&serial), shape(&default));
+        assert_eq!(serial.size, 2100);
+    }
+
+    #[test]
+    fn test_paused_scan_waits_and_resumes() {
+        let dir = tempdir().unwrap();
+        for i in 0..5 {
+            std::fs::write(dir.path().join(format!("f{}.bin", i)), vec![0u8; 10]).unwrap();
+        }
+
+        let pause = Arc::new(AtomicBool::new(true));
+        let progress = Arc::new(SharedProgress::default());
+        let scanner = Scanner::new().with_pause_token(Arc::clone(&pause));
+        let handle = {
+            let (path, progress) = (dir.path().to_path_buf(), Arc::clone(&progress));
+            std::thread::spawn(move || scanner.scan_with_progress(path, progress))
+        };
+
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(!handle.is_finished());
+        assert_eq!(progress.files_scanned.load(Ordering::Relaxed), 0);
+
+        pause.store(false, Ordering::Relaxed);
+        let (root, report) = handle.join().unwrap().unwrap();
+        assert!(!report.cancelled);
+        assert_eq!((root.size, root.file_count), (50, 5));
+
+        // Cancelling ends a paused scan
+        pause.store(true, Ordering::Relaxed);
+        let cancel = Arc::new(AtomicBool::new(true));
+        let (_, report) = Scanner::new().scan_with_control(dir.path(), pause, cancel).unwrap();
+        assert!(report.cancelled);
+    }
+
+    #[test]
+    fn test_scan_with_cancel_mid_walk_keeps_tree_consistent() {
+        let dir = tempdir().unwrap();
+        for d in 0..10 {
+            let sub = dir.path().join(format!("dir{:02}", d));
+            std::fs::create_dir(&sub).unwrap();
+            for f in 0..10 {
+                std::fs::write(sub.join(format!("f{}.bin", f)), vec![0u8; 10 * (d + 1)]).unwrap();
+            }
+        }
+
+        // Stop after a handful of entries have been filtered
+        let cancel = Arc::new(AtomicBool::new(false));
+        let seen = Arc::new(AtomicU64::new(0));
+        let scanner = {
+            let (cancel, seen) = (Arc::clone(&cancel), Arc::clone(&seen));
+            Scanner::new().with_entry_filter(move |_, _| {
+                if seen.fetch_add(1, Ordering::Relaxed) == 15 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+                true
+            })
+        };
+        let (root, report) = scanner.scan_with_cancel(dir.path(), cancel).unwrap();
+        assert!(report.cancelled);
+        assert!(root.file_count < 100);
+        for node in root.iter().filter(|n| n.is_dir) {
+            assert_eq!(node.size, node.children.iter().map(|c| c.size).sum::<u64>());
+            assert!(node.children.windows(2).all(|w| w[0].size >= w[1].size));
+        }
+
+        let (root, report) = scan_directory_with_report_shared(
+            dir.path(),
+            None,
+            None,
+            Some(Arc::new(AtomicBool::new(false))),
+        )
+        .unwrap();
+        assert!(!report.cancelled);
+        assert_eq!(root.file_count, 100);
+    }
+
+    #[test]
+    fn test_measure_unaccounted() {
+        assert_eq!(unaccounted_difference(1000, 400), 600);
+        assert_eq!(unaccounted_difference(400, 1000), -600);
+        assert_eq!(unaccounted_difference(u64::MAX, 0), i64::MAX);
+        assert_eq!(unaccounted_difference(0, u64::MAX), i64::MIN);
+
+        #[cfg(unix)]
+        assert!(filesystem_used_bytes(Path::new("/")).is_some_and(|used| used > 0));
+
+        // A plain directory is not compared against its whole filesystem
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 10]).unwrap();
+        let (_, report) = Scanner::new()
+            .with_measure_unaccounted(true)
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert_eq!(report.unaccounted_bytes, None);
+    }
+
+    #[test]
+    fn test_report_elapsed() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 10]).unwrap();
+        let before = Instant::now();
+        let (_, report) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert!(report.elapsed > Duration::ZERO);
+        assert!(report.elapsed <= before.elapsed());
+    }
+
+    #[test]
+    fn test_summary_line() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::write(dir.path().join("a/b/x.bin"), vec![0u8; 1024]).unwrap();
+        std::fs::write(dir.path().join("y.bin"), vec![0u8; 512]).unwrap();
+
+        let (root, mut report) = Scanner::new()
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert_eq!(summary_line(&root, &report), "1.50 KB across 2 files in 2 dirs");
+
+        let skipped = SkippedEntry {
+            path: None,
+            reason: SkipReason::PermissionDenied,
+            message: String::new(),
+        };
+        report.skipped = vec![skipped; 1234];
+        assert_eq!(summary_line(&root, &report), "1.50 KB across 2 files in 2 dirs (1,234 skipped)");
+
+        let single = Node {
+            file_count: 1,
+            size: 5,
+            ..Node::new("f".to_string(), PathBuf::from("f"), true)
+        };
+        assert_eq!(summary_line(&single, &ScanReport::default()), "5 B across 1 file in 0 dirs");
+        assert_eq!(group_digits(1_203_456), "1,203,456");
+        assert_eq!(group_digits(999), "999");
+    }
+
+    #[test]
+    fn test_resolve_auto_export() {
+        assert_eq!(resolve_auto_export(None::<&str>, None).unwrap(), None);
+        assert_eq!(
+            resolve_auto_export(Some("out/report.CSV"), None).unwrap(),
+            Some((PathBuf::from("out/report.CSV"), ExportFormat::Csv))
+        );
+        assert_eq!(
+            resolve_auto_export(Some("usage"), Some("du")).unwrap(),
+            Some((PathBuf::from("usage"), ExportFormat::Du))
+        );
+        assert!(resolve_auto_export(Some("usage"), None).is_err());
+        assert!(resolve_auto_export(Some("a.csv"), Some("xml")).is_err());
+        assert!(resolve_auto_export(None, Some("csv")).is_err());
+    }
+
+    #[test]
+    fn test_headless_scan_with_auto_export() {
+        let dir = tempdir().unwrap();
+        let data = dir.path().join("data");
+        std::fs::create_dir_all(data.join("sub")).unwrap();
+        std::fs::write(data.join("sub/a.bin"), vec![0u8; 2048]).unwrap();
+        let output_path = dir.path().join("report.txt");
+
+        let scanner = Scanner::new().with_auto_export(&output_path, ExportFormat::Tree);
+        let (root, report) = scanner
+            .scan_with_progress(&data, Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert!(!report.cancelled);
+        assert_eq!(scanner.auto_export(&root).unwrap(), Some(output_path.clone()));
+        let text = std::fs::read_to_string(&output_path).unwrap();
+        assert_eq!(text, "data [2.00 KB]\n└── sub [2.00 KB]\n    └── a.bin [2.00 KB]\n");
+
+        // Not configured: nothing happens
+        assert_eq!(Scanner::new().auto_export(&root).unwrap(), None);
+
+        // Errors name the target and keep the underlying cause
+        let empty = tempdir().unwrap();
+        let root = Scanner::new().scan(empty.path()).unwrap();
+        let err = scanner.auto_export(&root).unwrap_err();
+        assert!(err.to_string().contains("report.txt"));
+        assert_eq!(err.downcast_ref::<ExportError>(), Some(&ExportError::EmptyTree));
+    }
+
+    #[test]
+    fn test_export_format_availability() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.bin"), vec![0u8; 10]).unwrap();
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+
+        for (format, available) in [
+            (ExportFormat::Csv, cfg!(feature = "pro")),
+            (ExportFormat::Du, cfg!(feature = "pro")),
+            (ExportFormat::Json, cfg!(feature = "pro")),
+            (ExportFormat::Tree, true),
+            (ExportFormat::Svg, cfg!(feature = "svg")),
+        ] {
+            let output_path = dir.path().join(format!("out.{}", format.name()));
+            let result = scanner.export(&root, &output_path, format);
+            if available {
+                result.unwrap();
+                assert!(output_path.exists());
+            } else {
+                let err = result.unwrap_err();
+                assert_eq!(err.downcast_ref::<ExportError>(), Some(&ExportError::FormatUnavailable(format)));
+                assert!(!output_path.exists());
+            }
+        }
+    }
+
+    #[test]
+    fn test_rescan_rebuilds_only_changed_branches() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::create_dir(dir.path().join("c")).unwrap();
+        std::fs::write(dir.path().join("a/b/one.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("a/top.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("c/x.bin"), vec![0u8; 50]).unwrap();
+
+        let scanner = Scanner::new();
+        let mut old = scanner.scan(dir.path()).unwrap();
+        assert!(old.iter().all(|n| n.mtime.is_some()));
+
+        // Nothing changed: the same tree comes back
+        let same = scanner.rescan(&old).unwrap();
+        assert_eq!(same.size, 160);
+        assert_eq!(same.iter().count(), old.iter().count());
+
+        // Mark the untouched branch so reuse is observable, then add a file to a/b
+        old.find_mut(&dir.path().join("c/x.bin")).unwrap().size = 999;
+        old.find_mut(&dir.path().join("c")).unwrap().size = 999;
+        std::fs::write(dir.path().join("a/b/two.bin"), vec![0u8; 7]).unwrap();
+        // Coarse filesystem timestamps may not tick between the two writes
+        old.find_mut(&dir.path().join("a/b")).unwrap().mtime = Some(SystemTime::UNIX_EPOCH);
+
+        let new = scanner.rescan(&old).unwrap();
+        assert_eq!(new.find(&dir.path().join("c/x.bin")).unwrap().size, 999);
+        assert_eq!(new.find(&dir.path().join("a/b/two.bin")).unwrap().size, 7);
+        assert_eq!(new.find(&dir.path().join("a/b")).unwrap().size, 107);
+        assert_eq!(new.find(&dir.path().join("a")).unwrap().size, 117);
+        assert_eq!(new.size, 117 + 999);
+        assert_eq!(new.file_count, 4);
+        assert_eq!(new.children[0].name, "c");
+
+        // A file added at the root lists the root again, but the untouched
+        // branches below it are still reused
+        std::fs::write(dir.path().join("new.bin"), vec![0u8; 3]).unwrap();
+        old.mtime = Some(SystemTime::UNIX_EPOCH);
+        let new = scanner.rescan(&old).unwrap();
+        assert_eq!(new.find(&dir.path().join("c/x.bin")).unwrap().size, 999);
+        assert_eq!(new.find(&dir.path().join("new.bin")).unwrap().size, 3);
+        assert_eq!(new.size, 117 + 999 + 3);
+        assert_eq!(new.file_count, 5);
+
+        // Nodes without an mtime are always listed
+        let mut imported = old.clone();
+        imported.mtime = None;
+        imported.find_mut(&dir.path().join("c")).unwrap().mtime = None;
+        assert_eq!(scanner.rescan(&imported).unwrap().size, 170);
+    }
+
+    #[test]
+    fn test_rescan_keeps_scan_root_rules() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("sub/deep")).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(dir.path().join("sub/deep/a.bin"), vec![0u8; 10]).unwrap();
+
+        let scanner = Scanner::new().with_respect_gitignore(true).with_max_depth(1);
+        let mut old = scanner.scan(dir.path()).unwrap();
+        std::fs::write(dir.path().join("sub/b.bin"), vec![0u8; 20]).unwrap();
+        std::fs::write(dir.path().join("sub/skip.log"), vec![0u8; 40]).unwrap();
+        old.find_mut(&dir.path().join("sub")).unwrap().mtime = Some(SystemTime::UNIX_EPOCH);
+
+        let new = scanner.rescan(&old).unwrap();
+        // The root's .gitignore still applies inside sub
+        assert_eq!((new.size, new.file_count), (30, 2));
+        // max_depth still counts from the scan root
+        let sub = new.find(&dir.path().join("sub")).unwrap();
+        assert!(sub.children.is_empty());
+        assert_eq!(sub.size, 30);
+    }
+
+    #[test]
+    fn test_size_by_extension() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("media")).unwrap();
+        std::fs::write(dir.path().join("media/a.mp4"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("media/b.MP4"), vec![0u8; 200]).unwrap();
+        std::fs::write(dir.path().join("media/c.jpg"), vec![0u8; 40]).unwrap();
+        std::fs::write(dir.path().join("app.log"), vec![0u8; 25]).unwrap();
+        std::fs::write(dir.path().join("Makefile"), vec![0u8; 5]).unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        let totals = root.size_by_extension();
+        assert_eq!(totals.len(), 4);
+        assert_eq!(totals["mp4"], (500, 2));
+        assert_eq!(totals["jpg"], (40, 1));
+        assert_eq!(totals["log"], (25, 1));
+        assert_eq!(totals[""], (5, 1));
+        assert_eq!(totals.values().map(|(size, _)| size).sum::<u64>(), root.size);
+        assert_eq!(root.children[0].size_by_extension().len(), 2);
+    }
+
+    #[test]
+    fn test_sort_by() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("many")).unwrap();
+        for name in ["b.txt", "A.txt", "c.txt"] {
+            std::fs::write(dir.path().join("many").join(name), vec![0u8; 10]).unwrap();
+        }
+        std::fs::write(dir.path().join("Big.bin"), vec![0u8; 500]).unwrap();
+        std::fs::write(dir.path().join("apple.txt"), vec![0u8; 1]).unwrap();
+
+        let mut root = Scanner::new().scan(dir.path()).unwrap();
+        let names = |node: &Node| node.children.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&root), ["Big.bin", "many", "apple.txt"]);
+
+        root.sort_by(SortKey::Name, true);
+        assert_eq!(names(&root), ["apple.txt", "Big.bin", "many"]);
+        assert_eq!(names(&root.children[2]), ["A.txt", "b.txt", "c.txt"]);
+
+        root.sort_by(SortKey::Name, false);
+        assert_eq!(names(&root), ["many", "Big.bin", "apple.txt"]);
+        assert_eq!(names(&root.children[0]), ["c.txt", "b.txt", "A.txt"]);
+
+        // Equal counts fall back to name
+        root.sort_by(SortKey::FileCount, false);
+        assert_eq!(names(&root), ["many", "Big.bin", "apple.txt"]);
+
+        root.sort_by(SortKey::Size, true);
+        assert_eq!(names(&root), ["apple.txt", "many", "Big.bin"]);
+        root.sort_by(SortKey::Size, false);
+        assert_eq!(names(&root), ["Big.bin", "many", "apple.txt"]);
+    }
+
+    #[test]
+    fn test_node_iter_is_depth_first() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::write(dir.path().join("a/b/big.bin"), vec![0u8; 50]).unwrap();
+        std::fs::write(dir.path().join("a/small.txt"), b"x").unwrap();
+        std::fs::write(dir.path().join("c.txt"), b"hello").unwrap();
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        let names: Vec<&str> = root.iter().skip(1).map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["a", "b", "big.bin", "small.txt", "c.txt"]);
+        assert_eq!(root.iter().filter(|n| !n.is_dir).count(), 3);
+    }
+
+    #[test]
+    fn test_prune_empty_dirs() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("empty/nested/deeper")).unwrap();
+        std::fs::create_dir_all(dir.path().join("mixed/hollow")).unwrap();
+        std::fs::create_dir_all(dir.path().join("mixed/full/inner")).unwrap();
+        std::fs::write(dir.path().join("mixed/full/inner/data.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("top.txt"), vec![0u8; 3]).unwrap();
+
+        let mut root = Scanner::new().scan(dir.path()).unwrap();
+        let (size, files) = (root.size, root.file_count);
+        // empty, nested, deeper and hollow
+        assert_eq!(root.prune_empty_dirs(), 4);
+        assert!(root.find(&dir.path().join("empty")).is_none());
+        assert!(root.find(&dir.path().join("mixed/hollow")).is_none());
+        assert!(root.find(&dir.path().join("mixed/full/inner/data.bin")).is_some());
+        assert_eq!((root.size, root.file_count), (size, files));
+        assert_eq!(root.prune_empty_dirs(), 0);
+
+        // An empty root stays, and unreadable directories are kept
+        let mut lonely = Node::new("lonely".into(), "/lonely".into(), true);
+        let mut locked = Node::new("locked".into(), "/lonely/locked".into(), true);
+        locked.access_denied = true;
+        lonely.children.push(locked);
+        assert_eq!(lonely.prune_empty_dirs(), 0);
+        assert_eq!(lonely.children.len(), 1);
+    }
+
+    #[test]
+    fn test_files_older_than() {
+        let day = Duration::from_secs(86_400);
+        let now = SystemTime::now();
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("archive")).unwrap();
+        for (name, size, age_days) in [("archive/2019.tar", 300, 2000), ("archive/2023.tar", 200, 400), ("fresh.txt", 5, 0), ("month.log", 40, 45)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, vec![0u8; size]).unwrap();
+            let file = std::fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(now - day * age_days).unwrap();
+        }
+
+        let root = Scanner::new().scan(dir.path()).unwrap();
+        let year_ago = now - day * 365;
+        let old: Vec<&str> = root.files_older_than(year_ago).iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(old, ["2019.tar", "2023.tar"]);
+        assert_eq!(root.size_older_than(year_ago), (500, 2));
+        assert_eq!(root.size_older_than(now - day * 30), (540, 3));
+        assert_eq!(root.size_older_than(SystemTime::UNIX_EPOCH), (0, 0));
+
+        // Nodes built by hand have no mtime and never count as old
+        let bare = Node::new("x".into(), "/x".into(), false);
+        assert!(bare.files_older_than(now).is_empty());
+    }
+
+    #[test]
+    fn test_largest_files() {
+        let mut root = Node::new("root".into(), "/r".into(), true);
+        let mut sub = Node::new("sub".into(), "/r/sub".into(), true);
+        for (name, size) in [("b.bin", 50), ("a.bin", 50), ("c.bin", 10)] {
+            let mut file = Node::new(name.into(), PathBuf::from("/r/sub").join(name), false);
+            file.size = size;
+            sub.children.push(file);
+        }
+        sub.size = 110;
+        let mut big = Node::new("big.iso".into(), "/r/big.iso".into(), false);
+        big.size = 70;
+        root.children.push(sub);
+        root.children.push(big);
+        root.size = 180;
+
+        let names = |files: Vec<&Node>| files.iter().map(|n| n.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(root.largest_files(3)), ["big.iso", "a.bin", "b.bin"]);
+        assert_eq!(names(root.largest_files(2)), ["big.iso", "a.bin"]);
+        assert_eq!(names(root.largest_files(10)), ["big.iso", "a.bin", "b.bin", "c.bin"]);
+        assert!(root.largest_files(0).is_empty());
+        assert_eq!(names(root.children[0].largest_files(1)), ["a.bin"]);
+    }
+
+    #[test]
+    fn test_node_iter_counts_match_fixture() {
+        let mut root = Node::new("root".into(), "/r".into(), true);
+        let mut a = Node::new("a".into(), "/r/a".into(), true);
+        let mut b = Node::new("b".into(), "/r/a/b".into(), true);
+        b.children.push(Node::new("x.bin".into(), "/r/a/b/x.bin".into(), false));
+        b.children.push(Node::new("y.bin".into(), "/r/a/b/y.bin".into(), false));
+        a.children.push(b);
+        a.children.push(Node::new("empty".into(), "/r/a/empty".into(), true));
+        a.children.push(Node::new("z.txt".into(), "/r/a/z.txt".into(), false));
+        root.children.push(a);
+        root.children.push(Node::new("top.txt".into(), "/r/top.txt".into(), false));
+
+        // root, a, b, x.bin, y.bin, empty, z.txt, top.txt
+        assert_eq!(root.iter().count(), 8);
+        let files: Vec<&str> = root.iter_files().map(|n| n.name.as_str()).collect();
+        assert_eq!(files, ["x.bin", "y.bin", "z.txt", "top.txt"]);
+        assert_eq!(root.children[0].iter().count(), 6);
+        assert_eq!(root.children[1].iter_files().count(), 1);
+    }
+
+    #[test]
+    fn test_total_nodes_and_depth() {
+        let file = Node::new("f.txt".into(), "/f.txt".into(), false);
+        assert_eq!(file.total_nodes(), 1);
+        assert_eq!(file.depth(), 0);
+
+        let empty = Node::new("empty".into(), "/empty".into(), true);
+        assert_eq!(empty.total_nodes(), 1);
+        assert_eq!(empty.depth(), 0);
+
+        let mut root = Node::new("root".into(), "/r".into(), true);
+        let mut a = Node::new("a".into(), "/r/a".into(), true);
+        let mut b = Node::new("b".into(), "/r/a/b".into(), true);
+        b.children.push(Node::new("x.bin".into(), "/r/a/b/x.bin".into(), false));
+        a.children.push(b);
+        root.children.push(a);
+        root.children.push(Node::new("top.txt".into(), "/r/top.txt".into(), false));
+        assert_eq!(root.total_nodes(), 5);
+        assert_eq!(root.depth(), 3);
+        assert_eq!(root.children[1].depth(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_many_dedups_hardlinks_across_roots() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("a")).unwrap();
+        std::fs::create_dir(dir.path().join("b")).unwrap();
+        std::fs::write(dir.path().join("a/data.bin"), vec![0u8; 100]).unwrap();
+        std::fs::write(dir.path().join("b/own.bin"), vec![0u8; 7]).unwrap();
+        std::fs::hard_link(dir.path().join("a/data.bin"), dir.path().join("b/link.bin")).unwrap();
+        let roots = [dir.path().join("a"), dir.path().join("b")];
+
+        let plain = Scanner::new().scan_many(&roots).unwrap();
+        assert_eq!(plain[0].0.size, 100);
+        assert_eq!(plain[1].0.size, 107);
+
+        let dedup = Scanner::new().with_dedup_hardlinks(true).scan_many(&roots).unwrap();
+        assert_eq!(dedup[0].0.size, 100);
+        assert_eq!(dedup[0].1.hardlinks_deduplicated, 0);
+        assert_eq!(dedup[1].0.size, 7);
+        assert_eq!(dedup[1].1.hardlinks_deduplicated, 1);
+        assert_eq!(dedup[1].1.hardlink_bytes_saved, 100);
+        // The duplicate link is still listed, just with no size
+        let link = dedup[1].0.children.iter().find(|c| c.name == "link.bin").unwrap();
+        assert_eq!(link.size, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dedup_hardlinks_within_one_scan() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("backup")).unwrap();
+        std::fs::write(dir.path().join("data.bin"), vec![0u8; 1000]).unwrap();
+        std::fs::hard_link(dir.path().join("data.bin"), dir.path().join("backup/data.bin")).unwrap();
+
+        let plain = Scanner::new().scan(dir.path()).unwrap();
+        assert_eq!(plain.size, 2000);
+
+        let (root, report) = Scanner::new()
+            .with_dedup_hardlinks(true)
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        assert_eq!(root.size, 1000);
+        assert_eq!(root.file_count, 2);
+        assert_eq!(report.hardlinks_deduplicated, 1);
+        assert_eq!(report.hardlink_bytes_saved, 1000);
+        // Both links are listed; whichever the walk met second counts zero
+        let sizes: Vec<u64> = root.iter().filter(|n| !n.is_dir).map(|n| n.size).collect();
+        assert_eq!(sizes.len(), 2);
+        assert_eq!(sizes.iter().sum::<u64>(), 1000);
+    }
+
+    #[test]
+    fn test_min_dir_size_folds_small_dirs() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("big")).unwrap();
+        std::fs::create_dir(dir.path().join("tiny_a")).unwrap();
+        std::fs::create_dir(dir.path().join("tiny_b")).unwrap();
+        std::fs::write(dir.path().join("big/data.bin"), vec![0u8; 4096]).unwrap();
+        std::fs::write(dir.path().join("tiny_a/a.txt"), b"abc").unwrap();
+        std::fs::write(dir.path().join("tiny_b/b.txt"), b"defg").unwrap();
+
+        let full = Scanner::new().scan(dir.path()).unwrap();
+        let pruned = Scanner::new().with_min_dir_size(1024).scan(dir.path()).unwrap();
+
+        assert_eq!(pruned.size, full.size);
+        assert_eq!(pruned.children.len(), 2);
+        assert_eq!(pruned.children[0].name, "big");
+        assert_eq!(pruned.children[1].name, "<2 small directories>");
+        assert_eq!(pruned.children[1].size, 7);
+        assert!(!pruned.children.iter().any(|c| c.name.starts_with("tiny")));
+
+        // The summary is not a file, but its files still count toward totals
+        let summary = &pruned.children[1];
+        assert!(summary.is_summary && !summary.is_file());
+        assert_eq!(summary.file_count, 2);
+        assert_eq!(pruned.file_count, 3);
+        let largest: Vec<&str> = pruned.largest_files(5).iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(largest, ["data.bin"]);
+        assert_eq!(pruned.iter_files().count(), 1);
+        assert!(pruned.iter_files().all(|n| n.file_count == 1));
+    }
+
+    #[test]
+    fn test_min_dir_size_never_prunes_root() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("small.txt"), b"hi").unwrap();
+
+        let root = Scanner::new().with_min_dir_size(u64::MAX).scan(dir.path()).unwrap();
+        assert_eq!(root.size, 2);
+        assert_eq!(root.children.len(), 1);
+        assert_eq!(root.children[0].name, "small.txt");
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_csv_export_row_cap() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("big")).unwrap();
+        std::fs::write(dir.path().join("big/huge.bin"), vec![0u8; 1000]).unwrap();
+        std::fs::write(dir.path().join("big/small.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("medium.bin"), vec![0u8; 500]).unwrap();
+        std::fs::write(dir.path().join("tiny.bin"), vec![0u8; 1]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        let output_path = dir.path().join("export.csv");
+        let options = ExportOptions {
+            max_rows: Some(4),
+            ..Default::default()
+        };
+        scanner.export_csv_with_options(&root, &output_path, &options).unwrap();
+
+        let mut reader = csv::Reader::from_path(&output_path).unwrap();
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        let names: Vec<&str> = rows.iter().map(|r| &r[1]).collect();
+
+        // root, big, big/huge.bin, medium.bin are the 4 largest; depth-first order
+        assert_eq!(rows.len(), 5);
+        assert_eq!(&names[1..4], ["big", "huge.bin", "medium.bin"]);
+        assert_eq!(&rows[4][1], "(2 more entries truncated)");
+        assert_eq!(&rows[4][2], "Truncated");
+        assert_eq!(&rows[4][3], "11");
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_csv_export_file_count_column() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("a/one.txt"), b"1").unwrap();
+        std::fs::write(dir.path().join("a/b/two.txt"), b"22").unwrap();
+        std::fs::write(dir.path().join("a/b/three.txt"), b"333").unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        let output_path = dir.path().join("export.csv");
+        let options = ExportOptions {
+            include_file_count: true,
+            ..Default::default()
+        };
+        scanner.export_csv_with_options(&root, &output_path, &options).unwrap();
+
+        let mut reader = csv::Reader::from_path(&output_path).unwrap();
+        assert_eq!(&reader.headers().unwrap()[4], "File Count");
+        let counts: std::collections::HashMap<String, String> = reader
+            .records()
+            .map(|r| r.unwrap())
+            .map(|r| (r[1].to_string(), r[4].to_string()))
+            .collect();
+        assert_eq!(counts[&root.name], "3");
+        assert_eq!(counts["a"], "3");
+        assert_eq!(counts["b"], "2");
+        assert_eq!(counts["one.txt"], "1");
+        assert_eq!(counts["empty"], "0");
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_csv_export_average_file_size_column() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("a")).unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("a/one.txt"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("a/two.txt"), vec![0u8; 30]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        let output_path = dir.path().join("export.csv");
+        let options = ExportOptions {
+            include_average_file_size: true,
+            ..Default::default()
+        };
+        scanner.export_csv_with_options(&root, &output_path, &options).unwrap();
+
+        let mut reader = csv::Reader::from_path(&output_path).unwrap();
+        assert_eq!(&reader.headers().unwrap()[4], "Avg File Size (bytes)");
+        let averages: std::collections::HashMap<String, String> = reader
+            .records()
+            .map(|r| r.unwrap())
+            .map(|r| (r[1].to_string(), r[4].to_string()))
+            .collect();
+        assert_eq!(averages["a"], "20");
+        assert_eq!(averages["two.txt"], "30");
+        assert_eq!(averages["empty"], "");
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_csv_export_files_only() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::write(dir.path().join("a/b/deep.txt"), vec![0u8; 5]).unwrap();
+        std::fs::write(dir.path().join("top.txt"), vec![0u8; 3]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        let output_path = dir.path().join("export.csv");
+        let options = ExportOptions {
+            files_only: true,
+            ..Default::default()
+        };
+        scanner.export_csv_with_options(&root, &output_path, &options).unwrap();
+
+        let mut reader = csv::Reader::from_path(&output_path).unwrap();
+        let rows: Vec<csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert!(rows.iter().all(|r| &r[2] == "File"));
+        let names: Vec<&str> = rows.iter().map(|r| &r[1]).collect();
+        assert_eq!(names, ["deep.txt", "top.txt"]);
+        let root_name = root.name.clone();
+        assert_eq!(&rows[0][0], Path::new(&root_name).join("a/b/deep.txt").display().to_string());
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_csv_export_depth_and_human_size_columns() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::write(dir.path().join("a/b/deep.bin"), vec![0u8; 2048]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        let output_path = dir.path().join("export.csv");
+        let options = ExportOptions {
+            include_human_size: true,
+            include_depth: true,
+            ..Default::default()
+        };
+        scanner.export_csv_with_options(&root, &output_path, &options).unwrap();
+
+        let mut reader = csv::Reader::from_path(&output_path).unwrap();
+        assert_eq!(
+            reader.headers().unwrap().iter().collect::<Vec<_>>(),
+            ["Path", "Name", "Type", "Size (bytes)", "Size", "Depth"]
+        );
+        let rows: Vec<(String, String, String)> = reader
+            .records()
+            .map(|r| r.unwrap())
+            .map(|r| (r[1].to_string(), r[4].to_string(), r[5].to_string()))
+            .collect();
+        let depth_of = |name: &str| rows.iter().find(|r| r.0 == name).unwrap().2.clone();
+        assert_eq!(depth_of(&root.name), "0");
+        assert_eq!(depth_of("a"), "1");
+        assert_eq!(depth_of("b"), "2");
+        assert_eq!(depth_of("deep.bin"), "3");
+        assert!(rows.iter().all(|r| r.1 == "2.00 KB"));
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_csv_export_empty_tree() {
+        let dir = tempdir().unwrap();
+        let scanner = Scanner::new();
+        let result = scanner.scan(dir.path()).unwrap();
+
+        let output_path = dir.path().join("export.csv");
+        let err = scanner.export_csv(&result, &output_path).unwrap_err();
+        assert_eq!(err.downcast_ref::<ExportError>(), Some(&ExportError::EmptyTree));
+        assert!(!output_path.exists());
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_export_json_round_trip() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("a/b/deep.bin"), vec![1u8; 300]).unwrap();
+        std::fs::write(dir.path().join("top.txt"), vec![1u8; 20]).unwrap();
+
+        let scanner = Scanner::new().with_largest_file(true);
+        let root = scanner.scan(dir.path()).unwrap();
+        let pretty_path = dir.path().join("tree.json");
+        let compact_path = dir.path().join("tree.min.json");
+        scanner.export_json(&root, &pretty_path).unwrap();
+        scanner
+            .export_json_with_options(&root, &compact_path, &JsonOptions { compact: true })
+            .unwrap();
+
+        let pretty = std::fs::read_to_string(&pretty_path).unwrap();
+        let compact = std::fs::read_to_string(&compact_path).unwrap();
+        assert!(pretty.lines().count() > 1);
+        assert_eq!(compact.lines().count(), 1);
+
+        for text in [pretty, compact] {
+            let parsed: Node = serde_json::from_str(&text).unwrap();
+            let original: Vec<_> = root.iter().map(|n| (&n.name, n.size, n.is_dir, n.file_count)).collect();
+            let restored: Vec<_> = parsed.iter().map(|n| (&n.name, n.size, n.is_dir, n.file_count)).collect();
+            assert_eq!(restored, original);
+            assert_eq!(parsed.largest_file, root.largest_file);
+        }
+
+        let empty = Scanner::new().scan(dir.path().join("empty")).unwrap();
+        let err = scanner.export_json(&empty, dir.path().join("none.json")).unwrap_err();
+        assert_eq!(err.downcast_ref::<ExportError>(), Some(&ExportError::EmptyTree));
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_export_ndjson_one_line_per_node() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("a/b/deep.bin"), vec![1u8; 300]).unwrap();
+        std::fs::write(dir.path().join("top.txt"), vec![1u8; 20]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        let output_path = dir.path().join("tree.ndjson");
+        scanner.export_ndjson(&root, &output_path).unwrap();
+
+        let text = std::fs::read_to_string(&output_path).unwrap();
+        let lines: Vec<serde_json::Value> = text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines.len(), root.total_nodes());
+
+        // Same depth-first order as `Node::iter`
+        let names: Vec<&str> = lines.iter().map(|l| l["name"].as_str().unwrap()).collect();
+        let expected: Vec<&str> = root.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, expected);
+        let deep = lines.iter().find(|l| l["name"] == "deep.bin").unwrap();
+        assert_eq!(deep["depth"], 3);
+        assert_eq!(deep["size"], 300);
+        assert_eq!(deep["is_dir"], false);
+        assert_eq!(deep["path"], dir.path().join("a/b/deep.bin").to_str().unwrap());
+        assert_eq!(lines[0]["depth"], 0);
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_save_and_load_scan_round_trip() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        std::fs::create_dir(dir.path().join("empty")).unwrap();
+        std::fs::write(dir.path().join("a/b/deep.bin"), vec![1u8; 300]).unwrap();
+        std::fs::write(dir.path().join("top.txt"), vec![1u8; 20]).unwrap();
+
+        let scanner = Scanner::new();
+        let (root, mut report) = scanner
+            .scan_with_progress(dir.path(), Arc::new(SharedProgress::default()))
+            .unwrap();
+        report.skipped.push(SkippedEntry {
+            path: Some(dir.path().join("locked")),
+            reason: SkipReason::PermissionDenied,
+            message: "Permission denied".to_string(),
+        });
+        let output_path = dir.path().join("home.scan");
+        scanner.save_scan(&root, &report, &output_path).unwrap();
+
+        let (loaded_root, loaded_report) = Scanner::load_scan(&output_path).unwrap();
+        // `Node` has no `PartialEq` (its mtime is not saved), so compare the
+        // serialized trees
+        assert_eq!(
+            serde_json::to_value(&loaded_root).unwrap(),
+            serde_json::to_value(&root).unwrap()
+        );
+        assert_eq!(loaded_root.total_nodes(), root.total_nodes());
+        assert_eq!(loaded_report, report);
+
+        std::fs::write(&output_path, b"not a scan").unwrap();
+        assert!(Scanner::load_scan(&output_path).is_err());
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_export_extensions_csv() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("a.log"), vec![0u8; 10]).unwrap();
+        std::fs::write(dir.path().join("b.iso"), vec![0u8; 90]).unwrap();
+        std::fs::write(dir.path().join("c.LOG"), vec![0u8; 5]).unwrap();
+        std::fs::write(dir.path().join("README"), vec![0u8; 1]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        let output_path = dir.path().join("extensions.csv");
+        scanner.export_extensions_csv(&root, &output_path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&output_path).unwrap(),
+            "Extension,Size (bytes),Files\niso,90,1\nlog,15,2\n,1,1\n"
+        );
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_export_du_style() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("b/c")).unwrap();
+        std::fs::create_dir(dir.path().join("a")).unwrap();
+        std::fs::write(dir.path().join("a/small.txt"), vec![1u8; 10]).unwrap();
+        std::fs::write(dir.path().join("b/c/big.bin"), vec![1u8; 100_000]).unwrap();
+
+        let scanner = Scanner::new();
+        let root = scanner.scan(dir.path()).unwrap();
+        let output_path = dir.path().join("du.txt");
+
+        scanner.export_du_style(&root, &output_path, &DuStyleOptions::default()).unwrap();
+        let text = std::fs::read_to_string(&output_path).unwrap();
+        let lines: Vec<(u64, &str)> = text
+            .lines()
+            .map(|line| {
+                let (size, path) = line.split_once('\t').unwrap();
+                (size.parse().unwrap(), path)
+            })
+            .collect();
+        let paths: Vec<PathBuf> = lines.iter().map(|(_, p)| PathBuf::from(p)).collect();
+        assert_eq!(
+            paths,
+            [dir.path().to_path_buf(), dir.path().join("a"), dir.path().join("b"), dir.path().join("b/c")]
+        );
+        // 1K blocks, cumulative
+        assert_eq!(lines[0].0, root.allocated_size.div_ceil(1024));
+        assert!(lines[2].0 >= lines[3].0);
+        assert!(lines[3].0 >= 98);
+
+        let options = DuStyleOptions {
+            human_readable: true,
+            sort: DuSort::Size,
+        };
+        scanner.export_du_style(&root, &output_path, &options).unwrap();
+        let text = std::fs::read_to_string(&output_path).unwrap();
+        let first = text.lines().next().unwrap();
+        assert!(first.ends_with(&format!("\t{}", dir.path().display())));
+        assert!(first.split('\t').next().unwrap().ends_with('K'));
+    }
+
+    #[test]
+    fn test_format_snapshot() {
+        let child = |name: &str, size, is_dir| Node {
+            size,
+            ..Node::new(name.to_string(), PathBuf::from(name), is_dir)
+        };
+        let node = Node {
+            size: 400,
+            children: vec![child("a|b.txt", 300, false), child("src", 100, true)],
+            ..Node::new("proj".to_string(), PathBuf::from("proj"), true)
+        };
+        assert_eq!(
+            format_snapshot("/home/me/proj", &node, SizeMode::Apparent),
+            "## /home/me/proj\n\nTotal: 400 B (2 items)\n\n| Name | Size | % |\n|------|-----:|--:|\n\
+             | a\\|b.txt | 300 B | 75.0% |\n\
+             | src/ | 100 B | 25.0% |\n"
+        );
+
+        // Allocated sizes reorder the rows
+        let mut node = node;
+        node.allocated_size = 8192;
+        node.children[1].allocated_size = 8192;
+        let allocated = format_snapshot("p", &node, SizeMode::Allocated);
+        assert!(allocated.starts_with("## p\n\nTotal: 8.00 KB (2 items)"));
+        assert!(allocated.find("| src/ | 8.00 KB | 100.0% |").unwrap() < allocated.find("a\\|b.txt").unwrap());
+        assert_eq!(percent_of(1, 0), 0.0);
+
+        assert_eq!(snapshot_file_name(&node), "ferris-scan-snapshot-proj.md");
+        let named = |name: &str| snapshot_file_name(&Node::new(name.to_string(), PathBuf::from(name), true));
+        assert_eq!(named("my photos"), "ferris-scan-snapshot-my_photos.md");
+        assert_eq!(named("/"), "ferris-scan-snapshot-root.md");
+        assert_eq!(named(".."), "ferris-scan-snapshot-root.md");
+    }
+
+    #[test]
+    fn test_format_explain() {
+        let child = |name: &str, size, allocated| Node {
+            size,
+            allocated_size: allocated,
+            ..Node::new(name.to_string(), PathBuf::from(name), false)
+        };
+        let node = Node {
+            size: 1000,
+            allocated_size: 1000,
+            children: vec![
+                child("a", 600, 100),
+                child("b", 250, 500),
+                child("c", 100, 300),
+                child("d", 50, 100),
+            ],
+            ..Node::new("dir".to_string(), PathBuf::from("dir"), true)
+        };
+        assert_eq!(
+            format_explain(&node, 2, SizeMode::Apparent),
+            "Why is dir 1000 B?\n\n 60.0%  a (600 B)\n 25.0%  b (250 B)\n 15.0%  2 other items (150 B)\n"
+        );
+        // Ranked by the chosen size; no "others" line when all are named
+        assert_eq!(
+            format_explain(&node, 1, SizeMode::Allocated),
+            "Why is dir 1000 B?\n\n 50.0%  b (500 B)\n 50.0%  3 other items (500 B)\n"
+        );
+        assert!(!format_explain(&node, 4, SizeMode::Apparent).contains("other items"));
+
+        let empty = Node::new("e".to_string(), PathBuf::from("e"), true);
+        assert_eq!(
+            format_explain(&empty, DEFAULT_EXPLAIN_TOP_N, SizeMode::Apparent),
+            "Why is e 0 B?\n\nNo children to break down."
+        );
+    }
+
+    #[test]
+    fn test_flatten_and_sort_files() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/a.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("b.bin"), vec![0u8; 300]).unwrap();
+        std::fs::write(dir.path().join("c.bin"), vec![0u8; 100]).unwrap();
+        let root = Scanner::new().scan(dir.path()).unwrap();
+
+        let (files, omitted) = flatten_files(&root, 2, SizeMode::Apparent);
+        assert_eq!(omitted, 1);
+        assert_eq!(files, [(dir.path().join("b.bin"), 300), (dir.path().join("sub/a.bin"), 300)]);
+
+        let (mut all, omitted) = flatten_files(&root, FLAT_VIEW_LIMIT, SizeMode::Apparent);
+        assert_eq!((all.len(), omitted), (3, 0));
+        sort_flat_files(&mut all, FlatSort::Size.toggled());
+        let names: Vec<&Path> = all.iter().map(|(p, _)| p.strip_prefix(dir.path()).unwrap()).collect();
+        assert_eq!(names, [Path::new("b.bin"), Path::new("c.bin"), Path::new("sub/a.bin")]);
+        sort_flat_files(&mut all, FlatSort::Size);
+        assert_eq!(all[2].0, dir.path().join("c.bin"));
+
+        let allocated = flatten_files(&root, 1, SizeMode::Allocated).0;
+        let largest = root.largest_files_in(1, SizeMode::Allocated)[0];
+        assert_eq!(allocated, [(largest.path.clone(), largest.allocated_size)]);
+    }
+
+    #[test]
+    fn test_format_size_as() {
+        assert_eq!(format_size_as(1536, SizeFormat::Auto), "1.50 KB");
+        assert_eq!(format_size_as(0, SizeFormat::Bytes), "0 B");
+        assert_eq!(format_size_as(999, SizeFormat::Bytes), "999 B");
+        assert_eq!(format_size_as(1_572_864, SizeFormat::Bytes), "1,572,864 B");
+    }
+
+    #[test]
+    fn test_format_size_boundaries() {
+        const TIB: u64 = 1024 * 1024 * 1024 * 1024;
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1024), "1.00 KB");
+        assert_eq!(format_size(1536), "1.50 KB");
+        assert_eq!(format_size(TIB), "1.00 TB");
+        // Past the largest unit the number just keeps growing
+        assert_eq!(format_size(2048 * TIB), "2048.00 TB");
+
+        assert_eq!(format_size_with_units(0, SizeUnits::Binary), "0 B");
+        assert_eq!(format_size_with_units(1023, SizeUnits::Binary), "1023 B");
+        assert_eq!(format_size_with_units(1024, SizeUnits::Binary), "1.00 KiB");
+        assert_eq!(format_size_with_units(TIB, SizeUnits::Binary), "1.00 TiB");
+
+        assert_eq!(format_size_with_units(0, SizeUnits::Decimal), "0 B");
+        assert_eq!(format_size_with_units(999, SizeUnits::Decimal), "999 B");
+        assert_eq!(format_size_with_units(1000, SizeUnits::Decimal), "1.00 KB");
+        assert_eq!(format_size_with_units(1024, SizeUnits::Decimal), "1.02 KB");
+        assert_eq!(format_size_with_units(1_000_000_000_000, SizeUnits::Decimal), "1.00 TB");
+        assert_eq!(format_size_with_units(TIB, SizeUnits::Decimal), "1.10 TB");
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_format_du_human() {
+        assert_eq!(format_du_human(0), "0");
+        assert_eq!(format_du_human(4096), "4.0K");
+        assert_eq!(format_du_human(10_240), "10K");
+        assert_eq!(format_du_human(10_241), "11K");
+        assert_eq!(format_du_human(5 * 1024 * 1024 + 1), "5.1M");
+        assert_eq!(format_du_human(3 * 1024 * 1024 * 1024), "3.0G");
     }
 
     #[cfg(feature = "pro")]
     #[test]
     fn test_csv_export() {
         let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("data.txt"), b"hello").unwrap();
         let scanner = Scanner::new();
         let result = scanner.scan(dir.path()).unwrap();
         
@@ -31,7 +31,7 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ferris_scan::{Node, Scanner, ScanReport, SharedProgress};
+use ferris_scan::{resolve_scan_path, summary_line, Node, Scanner, ScanReport, SharedProgress};
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     layout::{Alignment, Constraint, Direction, Layout, Rect},
@@ -58,7 +58,7 @@ use std::{
 
 enum AppState {
     Scanning,
-    ViewingResults(Node, ScanReport),
+    ViewingResults(Box<Node>, Box<ScanReport>),
 }
 
 struct App {
@@ -132,12 +132,7 @@ impl App {
 // ============================================================================
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
-    let scan_path = if args.len() > 1 {
-        PathBuf::from(&args[1])
-    } else {
-        env::current_dir()?
-    };
+    let scan_path = resolve_scan_path(env::args_os().nth(1))?;
 
     // Setup terminal
     enable_raw_mode()?;
@@ -155,7 +150,7 @@ fn main() -> Result<()> {
 
     let scan_handle = thread::spawn(move || {
         let scanner = Scanner::new();
-        let result = scanner.scan_with_progress(&scan_path, shared_progress);
+        let result = scanner.scan_with_progress(&scan_path, shared_progress).map_err(anyhow::Error::from);
         scan_done_clone.store(true, Ordering::Relaxed);
         result
     });
@@ -199,7 +194,7 @@ where
                 if let Some(handle) = scan_handle.take() {
                     match handle.join() {
                         Ok(Ok((root, report))) => {
-                            app.state = AppState::ViewingResults(root, report);
+                            app.state = AppState::ViewingResults(Box::new(root), Box::new(report));
                         }
                         Ok(Err(e)) => {
                             app.show_popup(format!("Scan error: {}", e));
@@ -362,11 +357,7 @@ fn render_results(f: &mut Frame, area: Rect, root: &Node, report: &ScanReport) {
         ])));
     }
 
-    let title = format!(
-        "Results | Total: {} | Skipped: {}",
-        format_size(root.size),
-        report.skipped.len()
-    );
+    let title = format!("Results | {}", summary_line(root, report));
 
     let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
 